@@ -0,0 +1,45 @@
+//! 对比每次签名都解析商户私钥和复用缓存的[Signer]的耗时：`cargo bench --bench sign`
+use std::time::{Duration, Instant};
+use wechat_pay_rust_sdk::pay::WechatPay;
+use wechat_pay_rust_sdk::request::HttpMethod;
+use wechat_pay_rust_sdk::sign::{RsaSigner, Signer};
+
+const PRIVATE_KEY: &str = include_str!("../testdata/apiclient_key.pem");
+const URL: &str = "/v3/pay/transactions/out-trade-no/1217752501201407033233368018?mchid=1230000109";
+const ITERATIONS: u32 = 500;
+
+fn bench(name: &str, mut f: impl FnMut()) {
+    f();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed: Duration = start.elapsed() / ITERATIONS;
+    println!("{:<24} {:>10.1?}/iter", name, elapsed);
+}
+
+fn main() {
+    let message = format!(
+        "GET\n{}\n1554208460\n593BEC0C930BF1AFEB40B4A08C8FB242\n\n",
+        URL
+    );
+    bench("parse key per request", || {
+        let signer = RsaSigner::new(PRIVATE_KEY).unwrap();
+        signer.sign(message.as_bytes()).unwrap();
+    });
+
+    let wechat_pay = WechatPay::from_pem_str(
+        "wxd678efh567hg6787",
+        "1230000109",
+        PRIVATE_KEY,
+        "5F3A9C2B7D1E4F6A8B0C2D4E6F8091A2B3C4D5E6",
+        "",
+        "",
+    )
+    .unwrap();
+    bench("cached signer", || {
+        wechat_pay
+            .build_authorization(&HttpMethod::GET, URL, "")
+            .unwrap();
+    });
+}
@@ -0,0 +1,59 @@
+use crate::error::PayError;
+use crate::model::{AccountType, BillType, TarType};
+use crate::pay::WechatPay;
+use crate::request::HttpMethod;
+use crate::response::BillResponse;
+
+impl WechatPay {
+    /// 申请交易账单，bill_date格式为yyyy-MM-DD，仅支持三个月内的账单
+    pub async fn trade_bill(
+        &self,
+        bill_date: &str,
+        bill_type: Option<BillType>,
+        tar_type: Option<TarType>,
+    ) -> Result<BillResponse, PayError> {
+        let mut url = format!("/v3/bill/tradebill?bill_date={}", bill_date);
+        if let Some(bill_type) = bill_type {
+            url.push_str(&format!("&bill_type={}", bill_type));
+        }
+        if let Some(tar_type) = tar_type {
+            url.push_str(&format!("&tar_type={}", tar_type));
+        }
+        self.get_pay(&url).await
+    }
+
+    /// 申请资金账单，bill_date格式为yyyy-MM-DD，account_type不填默认为基本账户
+    pub async fn fund_flow_bill(
+        &self,
+        bill_date: &str,
+        account_type: Option<AccountType>,
+        tar_type: Option<TarType>,
+    ) -> Result<BillResponse, PayError> {
+        let mut url = format!("/v3/bill/fundflowbill?bill_date={}", bill_date);
+        if let Some(account_type) = account_type {
+            url.push_str(&format!("&account_type={}", account_type));
+        }
+        if let Some(tar_type) = tar_type {
+            url.push_str(&format!("&tar_type={}", tar_type));
+        }
+        self.get_pay(&url).await
+    }
+
+    /// 下载账单文件，下载地址由申请账单接口返回，该请求不需要签名。
+    /// 下载后校验文件摘要，不一致时返回`PayError::BillHashMismatch`；
+    /// 加密账单（返回了encrypt_key）会解密后返回明文，gzip压缩的账单（tar_type为GZIP）会解压后返回
+    pub async fn download_bill(&self, bill: &BillResponse) -> Result<Vec<u8>, PayError> {
+        let response = self
+            .execute(self.request_builder(HttpMethod::GET, &bill.download_url))
+            .await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let data = response.bytes().await?;
+        if !status.is_success() {
+            return self.parse_response(status, &headers, &data);
+        }
+        let data = self.check_bill(bill, data.to_vec())?;
+        let data = self.decrypt_bill(bill, data)?;
+        self.decompress_bill(data)
+    }
+}
@@ -0,0 +1,98 @@
+use crate::error::PayError;
+use crate::model::{CombineCloseSubOrder, CombineParams};
+use crate::pay::{WechatPay, WechatPayTrait};
+use crate::request::HttpMethod;
+use crate::response::{
+    AppResponse, CombineOrderResponse, H5Response, JsapiResponse, NativeResponse, ResponseTrait,
+};
+use crate::util;
+use serde_json::json;
+
+impl WechatPay {
+    pub(crate) async fn combine_pay<R: ResponseTrait>(
+        &self,
+        url: &str,
+        params: CombineParams,
+    ) -> Result<R, PayError> {
+        let body = self.build_combine_body(&params)?;
+        let response = self.send(HttpMethod::POST, url, body).await?;
+        self.read_response(response).await
+    }
+
+    /// 合单JSAPI下单，返回的prepay_id会重新签名生成调起支付的参数
+    pub async fn combine_jsapi_pay(
+        &self,
+        params: CombineParams,
+    ) -> Result<JsapiResponse, PayError> {
+        let url = "/v3/combine-transactions/jsapi";
+        self.combine_pay(url, params)
+            .await
+            .and_then(|mut result: JsapiResponse| {
+                if let Some(prepay_id) = &result.prepay_id {
+                    result.sign_data = Some(self.jsapi_sign(prepay_id)?);
+                }
+                Ok(result)
+            })
+    }
+
+    /// 合单APP下单，返回的prepay_id会重新签名生成调起支付的参数
+    pub async fn combine_app_pay(&self, params: CombineParams) -> Result<AppResponse, PayError> {
+        let url = "/v3/combine-transactions/app";
+        self.combine_pay(url, params)
+            .await
+            .and_then(|mut result: AppResponse| {
+                if let Some(prepay_id) = &result.prepay_id {
+                    result.sign_data = Some(self.app_sign(prepay_id)?);
+                }
+                Ok(result)
+            })
+    }
+
+    /// 合单H5下单，scene_info中需要包含h5_info
+    pub async fn combine_h5_pay(&self, params: CombineParams) -> Result<H5Response, PayError> {
+        let url = "/v3/combine-transactions/h5";
+        self.combine_pay(url, params).await
+    }
+
+    /// 合单Native下单
+    pub async fn combine_native_pay(
+        &self,
+        params: CombineParams,
+    ) -> Result<NativeResponse, PayError> {
+        let url = "/v3/combine-transactions/native";
+        self.combine_pay(url, params).await
+    }
+
+    /// 合单商户订单号查询合单订单，返回各子单的交易状态
+    pub async fn query_combine_order(
+        &self,
+        combine_out_trade_no: &str,
+    ) -> Result<CombineOrderResponse, PayError> {
+        util::validate_out_trade_no(combine_out_trade_no)?;
+        let url = format!(
+            "/v3/combine-transactions/out-trade-no/{}",
+            util::url_encode(combine_out_trade_no)
+        );
+        self.get_pay(&url).await
+    }
+
+    /// 合单关单，sub_orders需列出全部子单，成功时微信返回204无内容
+    pub async fn close_combine_order(
+        &self,
+        combine_out_trade_no: &str,
+        sub_orders: Vec<CombineCloseSubOrder>,
+    ) -> Result<(), PayError> {
+        util::validate_out_trade_no(combine_out_trade_no)?;
+        let url = format!(
+            "/v3/combine-transactions/out-trade-no/{}/close",
+            util::url_encode(combine_out_trade_no)
+        );
+        let body = json!({
+            "combine_appid": self.appid(),
+            "sub_orders": sub_orders,
+        })
+        .to_string();
+        let response = self.send(HttpMethod::POST, &url, body).await?;
+        self.read_response(response).await
+    }
+}
@@ -1 +1,12 @@
+pub mod bill;
+pub mod combine;
+pub mod complaint;
+pub mod fapiao;
+pub mod marketing;
+pub mod notify;
+#[cfg(feature = "oauth")]
+pub mod oauth;
 pub mod pay;
+pub mod payscore;
+pub mod profit_sharing;
+pub mod transfer;
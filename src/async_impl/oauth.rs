@@ -0,0 +1,23 @@
+//! 网页授权（公众号OAuth2.0），用code换取JSAPI下单需要的openid，需要开启`oauth`
+use crate::error::PayError;
+use crate::pay::{WechatPay, OAUTH_ACCESS_TOKEN_URL};
+use crate::request::HttpMethod;
+use crate::response::OAuthResponse;
+
+impl WechatPay {
+    /// 通过网页授权回调中的code换取openid，请求`api.weixin.qq.com`，不使用商户私钥签名。
+    /// app_secret为公众号的AppSecret，code只能使用一次，5分钟未使用自动过期
+    pub async fn get_openid(
+        &self,
+        app_secret: &str,
+        code: &str,
+    ) -> Result<OAuthResponse, PayError> {
+        let url = self.oauth_url(OAUTH_ACCESS_TOKEN_URL, app_secret, code);
+        let response = self
+            .execute(self.request_builder(HttpMethod::GET, url))
+            .await?;
+        let status = response.status();
+        let body = response.bytes().await?;
+        Self::parse_oauth_response(status, &body)
+    }
+}
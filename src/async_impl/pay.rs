@@ -1,86 +1,222 @@
-use crate::debug;
 use crate::error::PayError;
 use crate::model::AppParams;
+use crate::model::CodepayParams;
 use crate::model::H5Params;
 use crate::model::JsapiParams;
 use crate::model::MicroParams;
 use crate::model::NativeParams;
 use crate::model::ParamsTrait;
+use crate::model::RefundParams;
+use crate::model::TradeState;
 use crate::pay::{WechatPay, WechatPayTrait};
 use crate::request::HttpMethod;
 use crate::response::AppResponse;
+use crate::response::CodepayResponse;
 use crate::response::H5Response;
 use crate::response::JsapiResponse;
 use crate::response::MicroResponse;
+use crate::response::OrderQueryResponse;
+use crate::response::RefundResponse;
 use crate::response::ResponseTrait;
-use crate::response::{CertificateResponse, NativeResponse};
+use crate::response::{CertificateResponse, IdempotentPayResponse, NativeResponse};
+use crate::retry::RetryPolicy;
+use crate::{debug, debug_body, util};
 use reqwest::header::CONTENT_TYPE;
 use reqwest::header::{HeaderMap, REFERER};
 use reqwest::multipart::{Form, Part};
+use reqwest::StatusCode;
 use rsa::sha2::{Digest, Sha256};
+use serde::de::IgnoredAny;
 use serde_json::json;
-use serde_json::{Map, Value};
+use std::future::Future;
+use std::io::{ErrorKind, SeekFrom};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
 
-use std::collections::HashSet;
-use std::sync::OnceLock;
-
-static SUPPORTED_EXTENSIONS: OnceLock<HashSet<&'static str>> = OnceLock::new();
-fn is_supported_image(extension: &str) -> bool {
-    let extensions = SUPPORTED_EXTENSIONS
-        .get_or_init(|| vec!["jpg", "jpeg", "png", "bmp"].into_iter().collect());
-    extensions.contains(extension.to_lowercase().as_str())
-}
 impl WechatPay {
-    pub(crate) async fn pay<P: ParamsTrait, R: ResponseTrait>(
+    /// 使用自定义的reqwest::Client（连接池、代理、超时等），所有请求共用该client
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// 通过[Transport](crate::transport::Transport)发送请求，未设置时使用内部的reqwest client
+    pub(crate) async fn execute(
+        &self,
+        builder: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, PayError> {
+        let mut request = builder.build()?;
+        if let Some(hook) = &self.request_hook {
+            hook.call(&mut request);
+        }
+        match &self.transport {
+            Some(transport) => transport.execute(request).await,
+            None => Ok(self.client.execute(request).await?),
+        }
+    }
+
+    pub(crate) fn request_builder(
+        &self,
+        method: HttpMethod,
+        url: impl AsRef<str>,
+    ) -> reqwest::RequestBuilder {
+        let url = url.as_ref();
+        let builder = match method {
+            HttpMethod::GET => self.client.get(url),
+            HttpMethod::POST => self.client.post(url),
+            HttpMethod::PUT => self.client.put(url),
+            HttpMethod::DELETE => self.client.delete(url),
+            HttpMethod::PATCH => self.client.patch(url),
+        };
+        match self.timeout {
+            Some(timeout) => builder.timeout(timeout),
+            None => builder,
+        }
+    }
+
+    /// 调用SDK尚未封装的v3接口，负责签名、发送请求和解析响应，url为不含域名的请求路径。
+    /// 请求体按[ParamsTrait::inject_fields]注入全局配置的appid、mchid、notify_url，
+    /// 不需要注入时返回[InjectFields::NONE](crate::model::InjectFields::NONE)；
+    /// 服务商模式下`/v3/pay/transactions/*`会改为`/v3/pay/partner/transactions/*`
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    /// use wechat_pay_rust_sdk::model::{InjectFields, ParamsTrait};
+    /// use wechat_pay_rust_sdk::pay::WechatPay;
+    /// use wechat_pay_rust_sdk::request::HttpMethod;
+    /// use wechat_pay_rust_sdk::response::ResponseTrait;
+    ///
+    /// #[derive(Serialize)]
+    /// struct ExampleParams {
+    ///     out_request_no: String,
+    /// }
+    ///
+    /// impl ParamsTrait for ExampleParams {
+    ///     fn to_json(&self) -> String {
+    ///         serde_json::to_string(self).unwrap()
+    ///     }
+    ///     fn inject_fields(&self) -> InjectFields {
+    ///         InjectFields::NONE
+    ///     }
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct ExampleResponse {
+    ///     state: String,
+    /// }
+    ///
+    /// impl ResponseTrait for ExampleResponse {}
+    ///
+    /// async fn example(wechat_pay: &WechatPay) {
+    ///     let params = ExampleParams {
+    ///         out_request_no: "1217752501201407033233368018".to_string(),
+    ///     };
+    ///     let response: ExampleResponse = wechat_pay
+    ///         .pay(HttpMethod::POST, "/v3/example/endpoint", params)
+    ///         .await
+    ///         .expect("pay error");
+    ///     println!("state: {}", response.state);
+    /// }
+    /// ```
+    pub async fn pay<P: ParamsTrait, R: ResponseTrait>(
         &self,
         method: HttpMethod,
         url: &str,
         json: P,
     ) -> Result<R, PayError> {
-        let json_str = json.to_json();
-        debug!("json_str: {}", json_str);
-        let mut map: Map<String, Value> = serde_json::from_str(&json_str)?;
-        map.insert("appid".to_owned(), self.appid().into());
-        map.insert("mchid".to_owned(), self.mch_id().into());
-        map.insert("notify_url".to_owned(), self.notify_url().into());
-        let body = serde_json::to_string(&map)?;
-        let headers = self.build_header(method.clone(), url, body.as_str())?;
-        let client = reqwest::Client::new();
-        let url = format!("{}{}", self.base_url(), url);
-        debug!("url: {} body: {}", url, body);
-        let builder = match method {
-            HttpMethod::GET => client.get(url),
-            HttpMethod::POST => client.post(url),
-            HttpMethod::PUT => client.put(url),
-            HttpMethod::DELETE => client.delete(url),
-            HttpMethod::PATCH => client.patch(url),
-        };
+        let body = self.build_body(&json)?;
+        let url = self.pay_url(url);
+        let response = self.send(method, &url, body).await?;
+        self.read_response(response).await
+    }
 
-        builder
-            .headers(headers)
-            .body(body)
-            .send()
-            .await?
-            .json::<R>()
+    /// 调用SDK尚未封装的v3 GET接口，url为不含域名的请求路径（带查询参数），用法同[pay](Self::pay)。
+    /// 按[RetryPolicy]在连接错误、超时、5xx时重试，429时按`Retry-After`等待后重试
+    pub async fn get_pay<R: ResponseTrait>(&self, url: &str) -> Result<R, PayError> {
+        let mut attempt = 0;
+        loop {
+            let retry = attempt < self.retry_policy.max_retries;
+            let delay = match self.send(HttpMethod::GET, url, "".to_string()).await {
+                Ok(response) if retry && response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    debug!("retry: {} url: {} rate limited", attempt, url);
+                    self.retry_policy
+                        .rate_limit_delay(attempt, response.headers())
+                }
+                Ok(response) if retry && response.status().is_server_error() => {
+                    debug!(
+                        "retry: {} url: {} status: {}",
+                        attempt,
+                        url,
+                        (response.status())
+                    );
+                    self.retry_policy.delay(attempt)
+                }
+                Ok(response) => return self.read_response(response).await,
+                Err(e) if retry && RetryPolicy::is_retryable_error(&e) => {
+                    debug!("retry: {} url: {} error: {}", attempt, url, e);
+                    self.retry_policy.delay(attempt)
+                }
+                Err(e) => return Err(e),
+            };
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// 签名并发送请求，url为不含域名的请求路径
+    pub(crate) async fn send(
+        &self,
+        method: HttpMethod,
+        url: &str,
+        body: String,
+    ) -> Result<reqwest::Response, PayError> {
+        self.send_with_headers(method, url, body, HeaderMap::new())
             .await
-            .map(Ok)?
     }
 
-    pub(crate) async fn get_pay<R: ResponseTrait>(&self, url: &str) -> Result<R, PayError> {
-        let body = "";
-        let headers = self.build_header(HttpMethod::GET, url, body)?;
-        let client = reqwest::Client::new();
+    /// 同`send`，可以额外附加请求头，如加密敏感字段时的`Wechatpay-Serial`
+    pub(crate) async fn send_with_headers(
+        &self,
+        method: HttpMethod,
+        url: &str,
+        body: String,
+        extra_headers: HeaderMap,
+    ) -> Result<reqwest::Response, PayError> {
+        let mut headers = self.build_header(method, url, body.as_str())?;
+        headers.extend(extra_headers);
+        #[cfg(feature = "debug-print")]
+        let span = tracing::debug_span!(
+            "wechat_pay_request",
+            method = %method,
+            path = %util::redact_path(url),
+            request_id = tracing::field::Empty,
+        );
         let url = format!("{}{}", self.base_url(), url);
-        debug!("url: {} body: {}", url, body);
-        client
-            .get(url)
-            .headers(headers)
-            .body(body)
-            .send()
-            .await?
-            .json::<R>()
-            .await
-            .map(Ok)?
+        debug_body!("body: {}", body);
+        let response = self.execute(
+            self.request_builder(method, url)
+                .headers(headers)
+                .body(body),
+        );
+        #[cfg(feature = "debug-print")]
+        let response = tracing::Instrument::instrument(response, span.clone());
+        let response = response.await?;
+        #[cfg(feature = "debug-print")]
+        if let Some(request_id) = response.headers().get("Request-ID") {
+            span.record("request_id", request_id.to_str().unwrap_or_default());
+        }
+        Ok(response)
+    }
+
+    pub(crate) async fn read_response<R: ResponseTrait>(
+        &self,
+        response: reqwest::Response,
+    ) -> Result<R, PayError> {
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?;
+        self.parse_response(status, &headers, &body)
     }
 
     pub async fn h5_pay(&self, params: H5Params) -> Result<H5Response, PayError> {
@@ -91,163 +227,566 @@ impl WechatPay {
         let url = "/v3/pay/transactions/app";
         self.pay(HttpMethod::POST, url, params)
             .await
-            .map(|mut result: AppResponse| {
+            .and_then(|mut result: AppResponse| {
                 if let Some(prepay_id) = &result.prepay_id {
-                    result.sign_data = Some(self.mut_sign_data("", prepay_id));
+                    result.sign_data = Some(self.app_sign(prepay_id)?);
                 }
-                result
+                Ok(result)
             })
     }
     pub async fn jsapi_pay(&self, params: JsapiParams) -> Result<JsapiResponse, PayError> {
         let url = "/v3/pay/transactions/jsapi";
         self.pay(HttpMethod::POST, url, params)
             .await
-            .map(|mut result: JsapiResponse| {
+            .and_then(|mut result: JsapiResponse| {
                 if let Some(prepay_id) = &result.prepay_id {
-                    result.sign_data = Some(self.mut_sign_data("", prepay_id));
+                    result.sign_data = Some(self.jsapi_sign(prepay_id)?);
                 }
-                result
+                Ok(result)
             })
     }
+    #[deprecated(
+        note = "小程序支付使用JSAPI下单接口，请使用mini_program_pay；付款码支付请使用codepay"
+    )]
     pub async fn micro_pay(&self, params: MicroParams) -> Result<MicroResponse, PayError> {
+        self.mini_program_pay(params).await
+    }
+    /// 小程序支付，使用JSAPI下单接口，返回wx.requestPayment所需的签名数据
+    pub async fn mini_program_pay(&self, params: MicroParams) -> Result<MicroResponse, PayError> {
         let url = "/v3/pay/transactions/jsapi";
         self.pay(HttpMethod::POST, url, params)
             .await
-            .map(|mut result: MicroResponse| {
+            .and_then(|mut result: MicroResponse| {
                 if let Some(prepay_id) = &result.prepay_id {
-                    result.sign_data = Some(self.mut_sign_data("", prepay_id));
+                    result.sign_data = Some(self.jsapi_sign(prepay_id)?);
                 }
-                result
+                Ok(result)
             })
     }
+    /// 付款码支付（商户扫用户付款码），同步返回支付结果
+    pub async fn codepay(&self, params: CodepayParams) -> Result<CodepayResponse, PayError> {
+        let url = "/v3/pay/transactions/codepay";
+        self.pay(HttpMethod::POST, url, params).await
+    }
     pub async fn native_pay(&self, params: NativeParams) -> Result<NativeResponse, PayError> {
         let url = "/v3/pay/transactions/native";
         self.pay(HttpMethod::POST, url, params).await
     }
 
+    /// Native下单，商户订单号已被使用时查询原订单并返回其状态，便于网络重试后安全地重复调用
+    pub async fn native_pay_idempotent(
+        &self,
+        params: NativeParams,
+    ) -> Result<IdempotentPayResponse<NativeResponse>, PayError> {
+        let out_trade_no = params.out_trade_no.clone();
+        match self.native_pay(params).await {
+            Ok(response) => Ok(IdempotentPayResponse::Created(response)),
+            Err(e) if e.is_out_trade_no_used() => {
+                debug!(
+                    "out_trade_no {} already used, query existing order",
+                    out_trade_no
+                );
+                self.query_order_by_out_trade_no(&out_trade_no)
+                    .await
+                    .map(|order| IdempotentPayResponse::Existing(Box::new(order)))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 关闭订单，成功时微信返回204无内容
+    pub async fn close_order(&self, out_trade_no: &str) -> Result<(), PayError> {
+        util::validate_out_trade_no(out_trade_no)?;
+        let url = self.pay_url(&format!(
+            "/v3/pay/transactions/out-trade-no/{}/close",
+            util::url_encode(out_trade_no)
+        ));
+        let body = self.merchant_body(false);
+        let response = self.send(HttpMethod::POST, &url, body).await?;
+        self.read_response(response).await
+    }
+
+    /// 撤销付款码订单，POS机未获得支付结果时调用，撤销后已扣款的订单会原路退款。
+    /// 使用v3接口`/v3/pay/transactions/out-trade-no/{out_trade_no}/reverse`，签名方式与其他接口相同，
+    /// 不需要客户端证书；返回`SYSTEM_ERROR`、`USERPAYING`等`WechatApiError`时可以稍后重试撤销
+    pub async fn reverse_micropay(&self, out_trade_no: &str) -> Result<(), PayError> {
+        util::validate_out_trade_no(out_trade_no)?;
+        let url = self.pay_url(&format!(
+            "/v3/pay/transactions/out-trade-no/{}/reverse",
+            util::url_encode(out_trade_no)
+        ));
+        let body = self.merchant_body(true);
+        let response = self.send(HttpMethod::POST, &url, body).await?;
+        self.read_response::<IgnoredAny>(response).await?;
+        Ok(())
+    }
+
+    /// 申请退款，退款请求体不注入appid、mchid，服务商模式下注入sub_mchid，notify_url由参数自行指定
+    pub async fn refund(&self, params: RefundParams) -> Result<RefundResponse, PayError> {
+        let url = "/v3/refund/domestic/refunds";
+        let body = self.build_refund_body(&params)?;
+        let response = self.send(HttpMethod::POST, url, body).await?;
+        self.read_response(response).await
+    }
+
+    /// 商户退款单号查询退款，服务商模式下查询参数会带上sub_mchid
+    pub async fn query_refund(&self, out_refund_no: &str) -> Result<RefundResponse, PayError> {
+        util::validate_out_refund_no(out_refund_no)?;
+        let url = self.refund_query_url(out_refund_no);
+        self.get_pay(&url).await
+    }
+
     pub async fn certificates(&self) -> Result<CertificateResponse, PayError> {
         let url = "/v3/certificates";
         self.get_pay(url).await
     }
+
+    /// 启动探针：调用一次下载平台证书接口（轻量的签名GET），验证商户号、证书序列号、私钥以及网络是否可用。
+    /// 微信返回401时为`PayError::AuthFailed`，网络错误为`PayError::Network`、`PayError::Timeout`
+    pub async fn ping(&self) -> Result<(), PayError> {
+        match self.certificates().await {
+            Ok(_) => Ok(()),
+            Err(PayError::WechatApiError {
+                status: 401,
+                code,
+                message,
+                ..
+            }) => Err(PayError::AuthFailed { code, message }),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 拉取并解密平台证书，替换缓存中的证书
+    pub async fn refresh_certificates(&self) -> Result<(), PayError> {
+        let response = self.certificates().await?;
+        let certificates = self.decrypt_certificates(&response)?;
+        self.platform_certificates.replace(certificates);
+        Ok(())
+    }
+
+    /// 验证微信回调签名，参数取自回调的header：
+    /// `Wechatpay-Serial`、`Wechatpay-Timestamp`、`Wechatpay-Nonce`、`Wechatpay-Signature`。
+    /// 时间戳超出容忍范围时直接返回`PayError::NotificationExpired`，不会请求微信；
+    /// 缓存中没有该序列号的证书、证书已过期或缓存已过期时，先调用`refresh_certificates`刷新平台证书，
+    /// 刷新后证书仍过期时返回`PayError::CertificateExpired`
+    pub async fn verify_signature(
+        &self,
+        serial: &str,
+        timestamp: &str,
+        nonce: &str,
+        body: &str,
+        signature_b64: &str,
+    ) -> Result<(), PayError> {
+        self.replay_guard.check_timestamp(timestamp)?;
+        if self.needs_certificate_refresh(serial) {
+            self.refresh_certificates().await?;
+        }
+        self.verify_signature_cached(serial, timestamp, nonce, body, signature_b64)
+    }
+
+    /// 商户订单号查询订单
+    pub async fn query_order_by_out_trade_no(
+        &self,
+        out_trade_no: &str,
+    ) -> Result<OrderQueryResponse, PayError> {
+        util::validate_out_trade_no(out_trade_no)?;
+        let url = self.pay_url(&format!(
+            "/v3/pay/transactions/out-trade-no/{}?{}",
+            util::url_encode(out_trade_no),
+            self.merchant_query(),
+        ));
+        self.get_pay(&url).await
+    }
+
+    /// 微信支付订单号查询订单
+    pub async fn query_order_by_transaction_id(
+        &self,
+        transaction_id: &str,
+    ) -> Result<OrderQueryResponse, PayError> {
+        let url = self.pay_url(&format!(
+            "/v3/pay/transactions/id/{}?{}",
+            util::url_encode(transaction_id),
+            self.merchant_query(),
+        ));
+        self.get_pay(&url).await
+    }
+    /// 轮询查询订单，直到交易状态为终态或到达截止时间，超时返回 [PayError::PollTimeout]
+    pub async fn wait_for_payment(
+        &self,
+        out_trade_no: &str,
+        poll_interval: Duration,
+        deadline: Instant,
+    ) -> Result<OrderQueryResponse, PayError> {
+        self.wait_for_payment_with(out_trade_no, poll_interval, deadline, tokio::time::sleep)
+            .await
+    }
+    /// 同 [WechatPay::wait_for_payment]，使用自定义的 sleep 函数等待
+    pub async fn wait_for_payment_with<F, Fut>(
+        &self,
+        out_trade_no: &str,
+        poll_interval: Duration,
+        deadline: Instant,
+        sleep: F,
+    ) -> Result<OrderQueryResponse, PayError>
+    where
+        F: Fn(Duration) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        loop {
+            let order = self.query_order_by_out_trade_no(out_trade_no).await?;
+            if order.trade_state.as_ref().is_some_and(TradeState::is_final) {
+                return Ok(order);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(PayError::PollTimeout(out_trade_no.to_string()));
+            }
+            sleep(poll_interval.min(remaining)).await;
+        }
+    }
     pub async fn get_weixin<S>(&self, h5_url: S, referer: S) -> Result<Option<String>, PayError>
     where
         S: AsRef<str>,
     {
-        let client = reqwest::Client::new();
         let mut headers = HeaderMap::new();
         headers.insert(REFERER, referer.as_ref().parse().unwrap());
-        let text = client
-            .get(h5_url.as_ref())
-            .headers(headers)
-            .send()
+        let text = self
+            .execute(
+                self.request_builder(HttpMethod::GET, h5_url.as_ref())
+                    .headers(headers),
+            )
             .await?
             .text()
             .await?;
-        text.split("\n")
-            .find(|line| line.contains("weixin://"))
-            .map(|line| {
-                line.split(r#"""#)
-                    .find(|line| line.contains("weixin://"))
-                    .map(|line| line.to_string())
+        util::find_weixin_url(&text)
+            .map(Some)
+            .ok_or_else(|| PayError::WeixinNotFound {
+                body_snippet: util::body_snippet(&text),
             })
-            .ok_or_else(|| PayError::WeixinNotFound)
     }
+    /// 图片上传，支持jpg、jpeg、png、bmp，最大2M，文件内容需要与扩展名一致
     pub async fn upload_image(
         &self,
         image: Vec<u8>,
         filename: &str,
     ) -> Result<crate::response::UploadResponse, PayError> {
-        const MAX_SIZE: usize = 2 * 1024 * 1024;
-        const URL: &str = "/v3/merchant/media/upload";
-        // image's size must be less than 2M
-        if image.len() > MAX_SIZE {
-            return Err(PayError::WechatError(format!(
-                "Image size ({} bytes) exceeds the maximum allowed size ({} bytes)",
-                image.len(),
-                MAX_SIZE
-            )));
+        let mime = util::check_image(&image, filename)?;
+        self.upload_media("/v3/merchant/media/upload", image, filename, mime)
+            .await
+    }
+
+    /// 视频上传，支持avi、wmv、mpeg、mp4、mov、mkv、flv、f4v、m4v、rmvb，最大5M
+    pub async fn upload_video(
+        &self,
+        video: Vec<u8>,
+        filename: &str,
+    ) -> Result<crate::response::UploadResponse, PayError> {
+        let mime = util::check_video(&video, filename)?;
+        self.upload_media("/v3/merchant/media/video_upload", video, filename, mime)
+            .await
+    }
+
+    /// 从reader流式上传图片或视频，按扩展名选择图片或视频上传接口，len为文件大小。
+    ///
+    /// 签名的meta中需要整个文件的sha256，所以会先读一遍计算sha256，再seek回起始位置边读边上传。
+    /// 内存占用固定，代价是源文件要读两遍，reader必须支持seek；文件大小在读取前通过seek获取并校验
+    pub async fn upload_media_from_reader<R>(
+        &self,
+        mut reader: R,
+        len: u64,
+        filename: &str,
+    ) -> Result<crate::response::UploadResponse, PayError>
+    where
+        R: AsyncRead + AsyncSeek + Unpin + Send + Sync + 'static,
+    {
+        let start = reader.stream_position().await?;
+        let size = reader.seek(SeekFrom::End(0)).await? - start;
+        if size != len {
+            return Err(std::io::Error::new(
+                ErrorKind::UnexpectedEof,
+                format!("expected {} bytes, found {} bytes", len, size),
+            )
+            .into());
         }
-        // check image format is supported
-        let ext = filename.split('.').last().ok_or_else(|| {
-            PayError::WechatError("Invalid filename, no extension found".to_string())
-        })?;
-        if !is_supported_image(ext) {
-            return Err(PayError::WechatError(format!(
-                "Unsupported image format: {}",
-                ext
-            )));
+        reader.seek(SeekFrom::Start(start)).await?;
+        let mut head = Vec::with_capacity(8);
+        (&mut reader).take(8).read_to_end(&mut head).await?;
+        // 先按大小和文件头校验，过大的文件不会被整个读一遍
+        let (kind, mime) = util::check_media(&head, size as usize, filename)?;
+        reader.seek(SeekFrom::Start(start)).await?;
+        let mut hasher = Sha256::new();
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let n = reader.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
         }
+        reader.seek(SeekFrom::Start(start)).await?;
+        let body = reqwest::Body::wrap_stream(ReaderStream::new(reader));
+        let file_part = Part::stream_with_length(body, len)
+            .file_name(filename.to_string())
+            .mime_str(mime)?;
+        let sha256 = hex::encode(hasher.finalize());
+        self.send_media(kind.upload_url(), filename, sha256, file_part)
+            .await
+    }
 
+    /// 上传图片、视频
+    async fn upload_media(
+        &self,
+        url: &str,
+        media: Vec<u8>,
+        filename: &str,
+        mime: &str,
+    ) -> Result<crate::response::UploadResponse, PayError> {
         // calculate sha256
         let mut hasher = Sha256::new();
-        hasher.update(&image);
+        hasher.update(&media);
         let hash = hasher.finalize();
         let hash = hex::encode(hash.as_slice());
+        let file_part = Part::bytes(media)
+            .file_name(filename.to_string())
+            .mime_str(mime)?;
+        self.send_media(url, filename, hash, file_part).await
+    }
 
+    /// meta中的sha256参与签名，file_part为文件内容
+    async fn send_media(
+        &self,
+        url: &str,
+        filename: &str,
+        hash: String,
+        file_part: Part,
+    ) -> Result<crate::response::UploadResponse, PayError> {
         let meta = json!( {
             "filename": filename,
             "sha256": hash
         });
 
         let method = HttpMethod::POST;
-        let mut headers = self.build_header(method.clone(), &URL, meta.to_string())?;
-        headers.insert(CONTENT_TYPE, "multipart/form-data".parse().unwrap());
+        let mut headers = self.build_header(method, url, meta.to_string())?;
+        // Content-Type由multipart设置，需要带上boundary
+        headers.remove(CONTENT_TYPE);
 
         let mut json_part_headers = HeaderMap::new();
         json_part_headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
         let json_part = Part::text(meta.to_string()).headers(json_part_headers);
 
-        let mime = match ext {
-            "jpg" | "jpeg" => "image/jpeg",
-            "png" => "image/png",
-            "bmp" => "image/bmp",
-            _ => "image/jpeg",
-        };
-
-        let form_part = Part::bytes(image)
-            .file_name(filename.to_string())
-            .mime_str(mime)?;
+        let form = Form::new().part("meta", json_part).part("file", file_part);
 
-        let form = Form::new().part("meta", json_part).part("file", form_part);
-
-        let client = reqwest::Client::new();
-        let url = format!("{}{}", self.base_url(), URL);
-        client
-            .post(url)
-            .headers(headers)
-            .multipart(form)
-            .send()
-            .await?
-            .json()
-            .await
-            .map(Ok)?
+        let url = format!("{}{}", self.base_url(), url);
+        let response = self
+            .execute(
+                self.request_builder(HttpMethod::POST, url)
+                    .headers(headers)
+                    .multipart(form),
+            )
+            .await?;
+        self.read_response(response).await
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::model::NativeParams;
-    use crate::pay::WechatPay;
-    use dotenvy::dotenv;
-    use tracing::debug;
-
-    #[inline]
-    fn init_log() {
-        tracing_subscriber::fmt()
-            .with_max_level(tracing::Level::DEBUG)
-            .with_line_number(true)
-            .init();
-    }
+    use crate::error::PayError;
+    use crate::mock::{
+        json_response, test_wechat_pay, verify_authorization, FakeTransport, MockServer,
+    };
+    use crate::model::{NativeParams, TradeState};
+    use crate::pay::BASE_URL;
+    use crate::util;
+    use reqwest::header::AUTHORIZATION;
+    use rsa::sha2::{Digest, Sha256};
+    use std::io::SeekFrom;
+    use std::time::{Duration, Instant};
+    use tokio::io::{AsyncRead, AsyncSeek};
 
     #[tokio::test]
     pub async fn test_native_pay() {
-        init_log();
-        dotenv().ok();
-        let wechat_pay = WechatPay::from_env();
+        let transport = FakeTransport::new(vec![(
+            200,
+            r#"{"code_url":"weixin://wxpay/bizpayurl?pr=p4lpSuKzz"}"#,
+        )]);
+        let wechat_pay = test_wechat_pay(BASE_URL).with_transport(transport.clone());
         let body = wechat_pay
             .native_pay(NativeParams::new("测试支付1分", "1243243", 1.into()))
             .await
             .expect("pay fail");
-        debug!("body: {:?}", body);
+        assert_eq!(
+            body.code_url.as_deref(),
+            Some("weixin://wxpay/bizpayurl?pr=p4lpSuKzz")
+        );
+        let request = transport.requests().remove(0);
+        assert_eq!(request.method, "POST");
+        assert_eq!(
+            request.url,
+            "https://api.mch.weixin.qq.com/v3/pay/transactions/native"
+        );
+        let authorization = request.headers[AUTHORIZATION].to_str().unwrap();
+        verify_authorization(
+            authorization,
+            "POST",
+            "/v3/pay/transactions/native",
+            &request.body,
+        );
+    }
+
+    #[tokio::test]
+    pub async fn test_wait_for_payment() {
+        let not_pay =
+            ORDER_QUERY_JSON.replace(r#""trade_state":"SUCCESS""#, r#""trade_state":"NOTPAY""#);
+        let server = MockServer::start(vec![
+            json_response(200, &not_pay),
+            json_response(200, &not_pay),
+            json_response(200, ORDER_QUERY_JSON),
+        ]);
+        let wechat_pay = test_wechat_pay(&server.base_url);
+        let sleeps = std::sync::Mutex::new(vec![]);
+        let order = wechat_pay
+            .wait_for_payment_with(
+                "1217752501201407033233368018",
+                Duration::from_secs(2),
+                Instant::now() + Duration::from_secs(60),
+                |duration| {
+                    sleeps.lock().unwrap().push(duration);
+                    std::future::ready(())
+                },
+            )
+            .await
+            .expect("wait_for_payment error");
+        assert_eq!(order.trade_state, Some(TradeState::Success));
+        assert_eq!(server.requests().len(), 3);
+        assert_eq!(*sleeps.lock().unwrap(), vec![Duration::from_secs(2); 2]);
+    }
+
+    #[tokio::test]
+    pub async fn test_wait_for_payment_partner_mode() {
+        let not_pay =
+            ORDER_QUERY_JSON.replace(r#""trade_state":"SUCCESS""#, r#""trade_state":"NOTPAY""#);
+        let server = MockServer::start(vec![
+            json_response(200, &not_pay),
+            json_response(200, ORDER_QUERY_JSON),
+        ]);
+        let wechat_pay = test_wechat_pay(&server.base_url).with_sub_merchant("1900000109", None);
+        let order = wechat_pay
+            .wait_for_payment_with(
+                "1217752501201407033233368018",
+                Duration::from_secs(2),
+                Instant::now() + Duration::from_secs(60),
+                |_| std::future::ready(()),
+            )
+            .await
+            .expect("wait_for_payment error");
+        assert_eq!(order.trade_state, Some(TradeState::Success));
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        for request in requests {
+            assert!(request.starts_with(
+                "GET /v3/pay/partner/transactions/out-trade-no/1217752501201407033233368018?sp_mchid=1230000109&sub_mchid=1900000109 "
+            ));
+        }
+    }
+
+    #[tokio::test]
+    pub async fn test_wait_for_payment_timeout() {
+        let not_pay =
+            ORDER_QUERY_JSON.replace(r#""trade_state":"SUCCESS""#, r#""trade_state":"NOTPAY""#);
+        let server = MockServer::start(vec![json_response(200, &not_pay)]);
+        let wechat_pay = test_wechat_pay(&server.base_url);
+        let result = wechat_pay
+            .wait_for_payment_with(
+                "1217752501201407033233368018",
+                Duration::from_secs(2),
+                Instant::now(),
+                |_| async { panic!("should not sleep after deadline") },
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(PayError::PollTimeout(no)) if no == "1217752501201407033233368018"
+        ));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    const ORDER_QUERY_JSON: &str = r#"{"amount":{"currency":"CNY","payer_currency":"CNY","payer_total":1,"total":1},"appid":"wxd678efh567hg6787","attach":"","bank_type":"OTHERS","mchid":"1230000109","out_trade_no":"1217752501201407033233368018","payer":{"openid":"oUpF8uMuAJO_M2pxb1Q9zNjWeS6o"},"promotion_detail":[],"success_time":"2018-06-08T10:34:56+08:00","trade_state":"SUCCESS","trade_state_desc":"支付成功","trade_type":"NATIVE","transaction_id":"1217752501201407033233368018"}"#;
+
+    #[tokio::test]
+    pub async fn test_upload_media_from_reader() {
+        let server = MockServer::start(vec![json_response(
+            200,
+            r#"{"media_id":"H1ihR9JUtVj-J7CJqBUY5ZOrG_Je75H-rKit7K7bxQ"}"#,
+        )]);
+        let wechat_pay = test_wechat_pay(&server.base_url);
+        let mut image = vec![7u8; 1024 * 1024];
+        image[..8].copy_from_slice(b"\x89PNG\r\n\x1a\n");
+        let sha256 = hex::encode(Sha256::digest(&image));
+        let len = image.len() as u64;
+        let result = wechat_pay
+            .upload_media_from_reader(std::io::Cursor::new(image), len, "logo.png")
+            .await
+            .expect("upload_media_from_reader error");
+        assert!(result.media_id.is_some());
+        let request = server.requests().remove(0);
+        assert!(request.starts_with("POST /v3/merchant/media/upload "));
+        assert!(request.contains(&sha256));
+        assert!(request.len() > 1024 * 1024);
+
+        let result = wechat_pay
+            .upload_media_from_reader(std::io::Cursor::new(vec![0u8; 16]), 32, "intro.mp4")
+            .await;
+        assert!(matches!(result, Err(PayError::IoError(_))));
+    }
+
+    #[tokio::test]
+    pub async fn test_upload_media_from_reader_too_large() {
+        /// 记录读取字节数的reader
+        struct CountingReader {
+            inner: std::io::Cursor<Vec<u8>>,
+            read: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        }
+        impl AsyncRead for CountingReader {
+            fn poll_read(
+                mut self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+                buf: &mut tokio::io::ReadBuf<'_>,
+            ) -> std::task::Poll<std::io::Result<()>> {
+                let before = buf.filled().len();
+                let poll = std::pin::Pin::new(&mut self.inner).poll_read(cx, buf);
+                let n = buf.filled().len() - before;
+                self.read.fetch_add(n, std::sync::atomic::Ordering::SeqCst);
+                poll
+            }
+        }
+        impl AsyncSeek for CountingReader {
+            fn start_seek(
+                mut self: std::pin::Pin<&mut Self>,
+                position: SeekFrom,
+            ) -> std::io::Result<()> {
+                std::pin::Pin::new(&mut self.inner).start_seek(position)
+            }
+            fn poll_complete(
+                mut self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<std::io::Result<u64>> {
+                std::pin::Pin::new(&mut self.inner).poll_complete(cx)
+            }
+        }
+
+        let wechat_pay = test_wechat_pay("http://api.mch.weixin.invalid");
+        let read = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut image = vec![0u8; util::MAX_IMAGE_SIZE + 1];
+        image[..8].copy_from_slice(b"\x89PNG\r\n\x1a\n");
+        let len = image.len() as u64;
+        let reader = CountingReader {
+            inner: std::io::Cursor::new(image),
+            read: read.clone(),
+        };
+        let result = wechat_pay
+            .upload_media_from_reader(reader, len, "logo.png")
+            .await;
+        assert!(matches!(result, Err(PayError::ImageTooLarge { .. })));
+        assert!(read.load(std::sync::atomic::Ordering::SeqCst) <= 8);
     }
 }
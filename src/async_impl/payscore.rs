@@ -0,0 +1,74 @@
+use crate::error::PayError;
+use crate::model::{PayScoreModifyParams, PayScoreParams};
+use crate::pay::{WechatPay, WechatPayTrait};
+use crate::request::HttpMethod;
+use crate::response::{PayScoreCancelResponse, PayScoreResponse};
+use crate::util;
+use serde_json::json;
+
+impl WechatPay {
+    /// 创建支付分订单，请求体注入appid（服务商模式下也不改为sp_appid）、notify_url。
+    /// need_user_confirm为true时返回的package用于小程序调起确认订单页
+    pub async fn create_payscore_order(
+        &self,
+        params: PayScoreParams,
+    ) -> Result<PayScoreResponse, PayError> {
+        let url = "/v3/payscore/serviceorder";
+        let body = self.build_direct_body(&params)?;
+        let response = self.send(HttpMethod::POST, url, body).await?;
+        self.read_response(response).await
+    }
+
+    /// 通过商户服务订单号查询支付分订单
+    pub async fn query_payscore_order(
+        &self,
+        out_order_no: &str,
+        service_id: &str,
+    ) -> Result<PayScoreResponse, PayError> {
+        let url = format!(
+            "/v3/payscore/serviceorder?out_order_no={}&service_id={}&appid={}",
+            util::url_encode(out_order_no),
+            util::url_encode(service_id),
+            util::url_encode(self.appid()),
+        );
+        self.get_pay(&url).await
+    }
+
+    /// 取消支付分订单，取消后订单状态变为REVOKED
+    pub async fn cancel_payscore_order(
+        &self,
+        out_order_no: &str,
+        service_id: &str,
+        reason: &str,
+    ) -> Result<PayScoreCancelResponse, PayError> {
+        util::validate_out_order_no(out_order_no)?;
+        let url = format!(
+            "/v3/payscore/serviceorder/{}/cancel",
+            util::url_encode(out_order_no)
+        );
+        let body = json!({
+            "appid": self.appid(),
+            "service_id": service_id,
+            "reason": reason,
+        })
+        .to_string();
+        let response = self.send(HttpMethod::POST, &url, body).await?;
+        self.read_response(response).await
+    }
+
+    /// 修改支付分订单金额，只能在订单完结前修改
+    pub async fn modify_payscore_order(
+        &self,
+        out_order_no: &str,
+        params: PayScoreModifyParams,
+    ) -> Result<PayScoreResponse, PayError> {
+        util::validate_out_order_no(out_order_no)?;
+        let url = format!(
+            "/v3/payscore/serviceorder/{}/modify",
+            util::url_encode(out_order_no)
+        );
+        let body = self.build_direct_body(&params)?;
+        let response = self.send(HttpMethod::POST, &url, body).await?;
+        self.read_response(response).await
+    }
+}
@@ -0,0 +1,299 @@
+//! 解析下载的交易账单（CSV），账单中每个字段前带有`` ` ``防止Excel把订单号转成科学计数法，
+//! 最后两行为汇总表头和汇总数据。账单为UTF-8编码，GBK编码的账单会先转为UTF-8
+use crate::error::PayError;
+use crate::model::Fen;
+use std::collections::HashMap;
+
+/// 交易账单中的一笔交易
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TradeBillRow {
+    ///【交易时间】 例如：2018-06-08 10:34:56
+    pub trade_time: String,
+    ///【公众账号ID】 公众账号ID
+    pub appid: String,
+    ///【商户号】 商户号
+    pub mchid: String,
+    ///【特约商户号】 服务商模式下的子商户号，普通商户为0
+    pub sub_mchid: String,
+    ///【设备号】 设备号
+    pub device_info: String,
+    ///【微信订单号】 微信支付订单号
+    pub transaction_id: String,
+    ///【商户订单号】 商户订单号
+    pub out_trade_no: String,
+    ///【用户标识】 用户标识
+    pub openid: String,
+    ///【交易类型】 JSAPI、NATIVE、APP、MWEB、MICROPAY等
+    pub trade_type: String,
+    ///【交易状态】 SUCCESS、REFUND、REVOKED等
+    pub trade_state: String,
+    ///【付款银行】 付款银行
+    pub bank_type: String,
+    ///【货币种类】 货币种类
+    pub currency: String,
+    ///【应结订单金额】 应结订单金额
+    pub settlement_total: Fen,
+    ///【代金券金额】 代金券金额
+    pub coupon_amount: Fen,
+    ///【微信退款单号】 微信退款单号，没有退款时为0
+    pub refund_id: String,
+    ///【商户退款单号】 商户退款单号，没有退款时为0
+    pub out_refund_no: String,
+    ///【退款金额】 退款金额
+    pub refund_amount: Fen,
+    ///【充值券退款金额】 充值券退款金额
+    pub coupon_refund_amount: Fen,
+    ///【退款类型】 退款类型
+    pub refund_type: String,
+    ///【退款状态】 退款状态
+    pub refund_status: String,
+    ///【商品名称】 商品名称
+    pub description: String,
+    ///【商户数据包】 商户数据包
+    pub attach: String,
+    ///【手续费】 手续费，单位为元，精确到小数点后5位
+    pub fee: String,
+    ///【费率】 费率，例如：0.60%
+    pub fee_rate: String,
+    ///【订单金额】 订单金额
+    pub total_amount: Fen,
+    ///【申请退款金额】 申请退款金额
+    pub apply_refund_amount: Fen,
+    ///【费率备注】 费率备注
+    pub fee_rate_remark: String,
+}
+
+/// 交易账单汇总
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TradeBillSummary {
+    ///【总交易单数】 总交易单数
+    pub total_count: u64,
+    ///【应结订单总金额】 应结订单总金额
+    pub settlement_total: Fen,
+    ///【退款总金额】 退款总金额
+    pub refund_total: Fen,
+    ///【充值券退款总金额】 充值券退款总金额
+    pub coupon_refund_total: Fen,
+    ///【手续费总金额】 手续费总金额，单位为元，精确到小数点后5位
+    pub fee_total: String,
+    ///【订单总金额】 订单总金额
+    pub order_total: Fen,
+    ///【申请退款总金额】 申请退款总金额
+    pub apply_refund_total: Fen,
+}
+
+/// 交易账单
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TradeBill {
+    /// 交易明细，按账单中的顺序
+    pub rows: Vec<TradeBillRow>,
+    /// 汇总数据
+    pub summary: TradeBillSummary,
+}
+
+/// 按表头取值，账单类型（ALL、SUCCESS、REFUND）不同时列不完全相同，缺少的列为空
+struct Record<'a> {
+    fields: HashMap<&'a str, &'a str>,
+    line: usize,
+}
+
+impl<'a> Record<'a> {
+    fn new(header: &[&'a str], line: &'a str, number: usize) -> Result<Self, PayError> {
+        let values: Vec<&str> = split_line(line);
+        if values.len() != header.len() {
+            return Err(PayError::BillParseError(format!(
+                "line {}: expected {} fields, got {}",
+                number,
+                header.len(),
+                values.len()
+            )));
+        }
+        Ok(Self {
+            fields: header.iter().copied().zip(values).collect(),
+            line: number,
+        })
+    }
+
+    fn text(&self, name: &str) -> String {
+        self.fields
+            .get(name)
+            .copied()
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn amount(&self, name: &str) -> Result<Fen, PayError> {
+        match self.fields.get(name) {
+            Some(value) if !value.is_empty() => parse_yuan(value).ok_or_else(|| {
+                PayError::BillParseError(format!(
+                    "line {}: invalid amount {}={}",
+                    self.line, name, value
+                ))
+            }),
+            _ => Ok(Fen(0)),
+        }
+    }
+}
+
+/// 按逗号拆分并去掉字段前的`` ` ``
+fn split_line(line: &str) -> Vec<&str> {
+    line.split(',')
+        .map(|value| value.trim().trim_start_matches('`'))
+        .collect()
+}
+
+/// 解析以元为单位的金额，例如`100.01`，超过两位小数时四舍五入到分
+fn parse_yuan(value: &str) -> Option<Fen> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value),
+    };
+    let (yuan, decimal) = value.split_once('.').unwrap_or((value, ""));
+    if yuan.is_empty()
+        || !yuan
+            .bytes()
+            .chain(decimal.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let digits = |s: &str| s.bytes().fold(0i64, |acc, b| acc * 10 + (b - b'0') as i64);
+    let mut fen = digits(yuan) * 100 + digits(&format!("{:0<2}", &decimal[..decimal.len().min(2)]));
+    if decimal.len() > 2 && decimal.as_bytes()[2] >= b'5' {
+        fen += 1;
+    }
+    Some(Fen(if negative { -fen } else { fen }))
+}
+
+/// 账单内容转为UTF-8，去掉BOM，非UTF-8时按GBK解码
+fn decode(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => encoding_rs::GBK.decode(bytes).0.into_owned(),
+    }
+}
+
+/// 解析交易账单，`download_bill`返回的内容可以直接传入
+pub fn parse_trade_bill(bytes: &[u8]) -> Result<TradeBill, PayError> {
+    let text = decode(bytes);
+    let lines: Vec<(usize, &str)> = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim_end()))
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+    if lines.len() < 3 {
+        return Err(PayError::BillParseError(
+            "bill must contain a header and a summary".to_string(),
+        ));
+    }
+    let (details, summary) = lines.split_at(lines.len() - 2);
+    let header = split_line(details[0].1);
+    let mut rows = Vec::with_capacity(details.len() - 1);
+    for &(number, line) in &details[1..] {
+        let record = Record::new(&header, line, number)?;
+        rows.push(TradeBillRow {
+            trade_time: record.text("交易时间"),
+            appid: record.text("公众账号ID"),
+            mchid: record.text("商户号"),
+            sub_mchid: record.text("特约商户号"),
+            device_info: record.text("设备号"),
+            transaction_id: record.text("微信订单号"),
+            out_trade_no: record.text("商户订单号"),
+            openid: record.text("用户标识"),
+            trade_type: record.text("交易类型"),
+            trade_state: record.text("交易状态"),
+            bank_type: record.text("付款银行"),
+            currency: record.text("货币种类"),
+            settlement_total: record.amount("应结订单金额")?,
+            coupon_amount: record.amount("代金券金额")?,
+            refund_id: record.text("微信退款单号"),
+            out_refund_no: record.text("商户退款单号"),
+            refund_amount: record.amount("退款金额")?,
+            coupon_refund_amount: record.amount("充值券退款金额")?,
+            refund_type: record.text("退款类型"),
+            refund_status: record.text("退款状态"),
+            description: record.text("商品名称"),
+            attach: record.text("商户数据包"),
+            fee: record.text("手续费"),
+            fee_rate: record.text("费率"),
+            total_amount: record.amount("订单金额")?,
+            apply_refund_amount: record.amount("申请退款金额")?,
+            fee_rate_remark: record.text("费率备注"),
+        });
+    }
+    let summary_header = split_line(summary[0].1);
+    let record = Record::new(&summary_header, summary[1].1, summary[1].0)?;
+    let total_count = record.text("总交易单数");
+    let summary = TradeBillSummary {
+        total_count: total_count.parse().map_err(|_| {
+            PayError::BillParseError(format!("invalid total count: {}", total_count))
+        })?,
+        settlement_total: record.amount("应结订单总金额")?,
+        refund_total: record.amount("退款总金额")?,
+        coupon_refund_total: record.amount("充值券退款总金额")?,
+        fee_total: record.text("手续费总金额"),
+        order_total: record.amount("订单总金额")?,
+        apply_refund_total: record.amount("申请退款总金额")?,
+    };
+    Ok(TradeBill { rows, summary })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bill::{parse_trade_bill, parse_yuan};
+    use crate::error::PayError;
+    use crate::model::Fen;
+
+    const TRADE_BILL: &str = include_str!("../testdata/trade_bill.csv");
+
+    #[test]
+    fn test_parse_trade_bill() {
+        let bill = parse_trade_bill(TRADE_BILL.as_bytes()).expect("parse_trade_bill error");
+        assert_eq!(bill.rows.len(), 3);
+        let row = &bill.rows[0];
+        assert_eq!(row.trade_time, "2018-06-08 10:34:56");
+        assert_eq!(row.transaction_id, "1217752501201407033233368018");
+        assert_eq!(row.trade_type, "NATIVE");
+        assert_eq!(row.settlement_total, Fen(10000));
+        assert_eq!(row.description, "Image形象店-深圳腾大-QQ公仔");
+        assert_eq!(row.fee, "0.60000");
+        assert_eq!(row.fee_rate, "0.60%");
+        assert_eq!(row.fee_rate_remark, "");
+        let refund = &bill.rows[1];
+        assert_eq!(refund.trade_state, "REFUND");
+        assert_eq!(refund.refund_amount, Fen(30));
+        assert_eq!(refund.out_refund_no, "1217752501201407033233368020");
+        assert_eq!(refund.fee, "-0.00180");
+        assert_eq!(bill.rows[2].total_amount, Fen(1));
+        assert_eq!(bill.summary.total_count, 3);
+        assert_eq!(bill.summary.order_total, Fen(10001));
+        assert_eq!(bill.summary.refund_total, Fen(30));
+        assert_eq!(bill.summary.fee_total, "0.59820");
+
+        let (gbk, _, _) = encoding_rs::GBK.encode(TRADE_BILL);
+        assert_eq!(parse_trade_bill(&gbk).unwrap(), bill);
+        let bom = [b"\xEF\xBB\xBF".as_slice(), TRADE_BILL.as_bytes()].concat();
+        assert_eq!(parse_trade_bill(&bom).unwrap(), bill);
+    }
+
+    #[test]
+    fn test_parse_trade_bill_error() {
+        let truncated = TRADE_BILL.lines().take(2).collect::<Vec<_>>().join("\n");
+        assert!(matches!(
+            parse_trade_bill(truncated.as_bytes()),
+            Err(PayError::BillParseError(_))
+        ));
+        let invalid = TRADE_BILL.replace("`100.00,`0.00,`0,", "`abc,`0.00,`0,");
+        assert!(matches!(
+            parse_trade_bill(invalid.as_bytes()),
+            Err(PayError::BillParseError(message)) if message.starts_with("line 2:")
+        ));
+        assert_eq!(parse_yuan("0.01"), Some(Fen(1)));
+        assert_eq!(parse_yuan("12"), Some(Fen(1200)));
+        assert_eq!(parse_yuan("0.5"), Some(Fen(50)));
+        assert_eq!(parse_yuan("-0.305"), Some(Fen(-31)));
+        assert_eq!(parse_yuan(""), None);
+    }
+}
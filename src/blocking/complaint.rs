@@ -0,0 +1,65 @@
+use crate::error::PayError;
+use crate::model::ComplaintResponseParams;
+use crate::pay::WechatPay;
+use crate::request::HttpMethod;
+use crate::response::ComplaintListResponse;
+use crate::util;
+use serde_json::json;
+
+impl WechatPay {
+    /// 查询投诉单列表，日期格式为yyyy-MM-dd，begin_date与end_date间隔不能超过30天，
+    /// 服务商查询子商户投诉时传入complainted_mchid，投诉人联系方式会使用商户私钥解密
+    pub fn query_complaints(
+        &self,
+        begin_date: &str,
+        end_date: &str,
+        limit: u32,
+        offset: u32,
+        complainted_mchid: Option<&str>,
+    ) -> Result<ComplaintListResponse, PayError> {
+        let mut url = format!(
+            "/v3/merchant-service/complaints-v2?limit={}&offset={}&begin_date={}&end_date={}",
+            limit,
+            offset,
+            util::url_encode(begin_date),
+            util::url_encode(end_date),
+        );
+        if let Some(complainted_mchid) = complainted_mchid {
+            url.push_str(&format!(
+                "&complainted_mchid={}",
+                util::url_encode(complainted_mchid)
+            ));
+        }
+        let mut response: ComplaintListResponse = self.get_pay(&url)?;
+        self.decrypt_complaint_phones(&mut response)?;
+        Ok(response)
+    }
+
+    /// 回复用户投诉，成功时微信返回204无内容
+    pub fn respond_complaint(
+        &self,
+        complaint_id: &str,
+        params: ComplaintResponseParams,
+    ) -> Result<(), PayError> {
+        let url = format!(
+            "/v3/merchant-service/complaints-v2/{}/response",
+            util::url_encode(complaint_id)
+        );
+        self.pay(HttpMethod::POST, &url, params)
+    }
+
+    /// 反馈投诉处理完成，需要先回复用户，成功时微信返回204无内容
+    pub fn complete_complaint(
+        &self,
+        complaint_id: &str,
+        complainted_mchid: &str,
+    ) -> Result<(), PayError> {
+        let url = format!(
+            "/v3/merchant-service/complaints-v2/{}/complete",
+            util::url_encode(complaint_id)
+        );
+        let body = json!({ "complainted_mchid": complainted_mchid }).to_string();
+        let response = self.send(HttpMethod::POST, &url, body)?;
+        self.read_response(response)
+    }
+}
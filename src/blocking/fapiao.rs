@@ -0,0 +1,48 @@
+use crate::error::PayError;
+use crate::model::{FapiaoBuyerInformation, FapiaoParams, FapiaoScene, ParamsTrait};
+use crate::pay::WechatPay;
+use crate::request::HttpMethod;
+use crate::response::FapiaoResponse;
+use crate::util;
+use reqwest::header::HeaderMap;
+
+impl WechatPay {
+    /// 获取用户在微信中填写的发票抬头，fapiao_apply_id为调起抬头填写页时使用的申请单号
+    pub fn get_fapiao_user_title(
+        &self,
+        fapiao_apply_id: &str,
+        scene: FapiaoScene,
+    ) -> Result<FapiaoBuyerInformation, PayError> {
+        util::validate_fapiao_apply_id(fapiao_apply_id)?;
+        let url = format!(
+            "/v3/new-tax-control-fapiao/user-title?fapiao_apply_id={}&scene={}",
+            util::url_encode(fapiao_apply_id),
+            scene.as_str(),
+        );
+        self.get_pay(&url)
+    }
+
+    /// 开具电子发票，购买方手机号、邮箱会使用平台证书加密，
+    /// 平台证书缓存为空或已过期时先调用`refresh_certificates`
+    pub fn create_fapiao(&self, mut params: FapiaoParams) -> Result<FapiaoResponse, PayError> {
+        let url = "/v3/new-tax-control-fapiao/fapiao-applications";
+        params.validate()?;
+        let mut headers = HeaderMap::new();
+        let buyer = &mut params.buyer_information;
+        if buyer.phone.is_some() || buyer.email.is_some() {
+            if self.platform_certificates.needs_refresh() {
+                self.refresh_certificates()?;
+            }
+            let serial_no = self.encryption_certificate()?.serial_no;
+            for field in [&mut buyer.phone, &mut buyer.email].into_iter().flatten() {
+                *field = self.rsa_encrypt_field(field)?;
+            }
+            headers.insert("Wechatpay-Serial", serial_no.parse().unwrap());
+        }
+        let response = self.send_with_headers(HttpMethod::POST, url, params.to_json(), headers)?;
+        self.read_response::<()>(response)?;
+        Ok(FapiaoResponse {
+            fapiao_apply_id: params.fapiao_apply_id,
+        })
+    }
+}
@@ -0,0 +1,68 @@
+use crate::error::PayError;
+use crate::model::CouponStockParams;
+use crate::pay::{WechatPay, WechatPayTrait};
+use crate::request::HttpMethod;
+use crate::response::{
+    CouponStockInfo, CouponStockResponse, SendCouponResponse, StartCouponStockResponse,
+};
+use crate::util;
+use serde_json::json;
+
+impl WechatPay {
+    /// 创建代金券批次，未指定归属商户号时使用当前商户号，创建后需调用`start_coupon_stock`激活
+    pub fn create_coupon_stock(
+        &self,
+        mut params: CouponStockParams,
+    ) -> Result<CouponStockResponse, PayError> {
+        let url = "/v3/marketing/favor/coupon-stocks";
+        params
+            .belong_merchant
+            .get_or_insert_with(|| self.mch_id().to_string());
+        self.pay(HttpMethod::POST, url, params)
+    }
+
+    /// 激活代金券批次
+    pub fn start_coupon_stock(&self, stock_id: &str) -> Result<StartCouponStockResponse, PayError> {
+        let url = format!(
+            "/v3/marketing/favor/stocks/{}/start",
+            util::url_encode(stock_id)
+        );
+        let body = json!({ "stock_creator_mchid": self.mch_id() }).to_string();
+        let response = self.send(HttpMethod::POST, &url, body)?;
+        self.read_response(response)
+    }
+
+    /// 查询批次详情
+    pub fn query_coupon_stock(&self, stock_id: &str) -> Result<CouponStockInfo, PayError> {
+        let url = format!(
+            "/v3/marketing/favor/stocks/{}?stock_creator_mchid={}",
+            util::url_encode(stock_id),
+            util::url_encode(self.mch_id()),
+        );
+        self.get_pay(&url)
+    }
+
+    /// 发放代金券，appid为用户openid所属的应用ID。
+    /// 相同out_request_no重复请求时微信返回同一张券，网络异常时可以使用原单号重试
+    pub fn send_coupon(
+        &self,
+        stock_id: &str,
+        openid: &str,
+        out_request_no: &str,
+        appid: &str,
+    ) -> Result<SendCouponResponse, PayError> {
+        let url = format!(
+            "/v3/marketing/favor/users/{}/coupons",
+            util::url_encode(openid)
+        );
+        let body = json!({
+            "stock_id": stock_id,
+            "out_request_no": out_request_no,
+            "appid": appid,
+            "stock_creator_mchid": self.mch_id(),
+        })
+        .to_string();
+        let response = self.send(HttpMethod::POST, &url, body)?;
+        self.read_response(response)
+    }
+}
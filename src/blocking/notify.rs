@@ -0,0 +1,22 @@
+use crate::error::PayError;
+use crate::model::{Notification, NotificationHeaders};
+use crate::pay::WechatPay;
+
+impl WechatPay {
+    /// 处理微信回调：使用[NotificationHeaders]中的`Wechatpay-*`值验证签名（必要时刷新平台证书），
+    /// 再解密resource并按event_type返回对应的[Notification]
+    pub fn handle_notification(
+        &self,
+        headers: &NotificationHeaders,
+        body: &str,
+    ) -> Result<Notification, PayError> {
+        self.verify_signature(
+            &headers.serial,
+            &headers.timestamp,
+            &headers.nonce,
+            body,
+            &headers.signature,
+        )?;
+        self.parse_notification(body)
+    }
+}
@@ -0,0 +1,145 @@
+use crate::error::PayError;
+use crate::model::{
+    ParamsTrait, ProfitSharingParams, ProfitSharingReceiverAddParams,
+    ProfitSharingReceiverDeleteParams, ProfitSharingReturnParams, ProfitSharingUnfreezeParams,
+};
+use crate::pay::{WechatPay, WechatPayTrait};
+use crate::request::HttpMethod;
+use crate::response::{
+    ProfitSharingAmountResponse, ProfitSharingReceiverResponse, ProfitSharingResponse,
+    ProfitSharingReturnResponse,
+};
+use crate::util;
+use reqwest::header::HeaderMap;
+use serde_json::{Map, Value};
+
+impl WechatPay {
+    /// 请求分账，请求体注入appid，服务商模式下注入sub_mchid、sub_appid，不注入notify_url
+    pub fn profit_sharing(
+        &self,
+        params: ProfitSharingParams,
+    ) -> Result<ProfitSharingResponse, PayError> {
+        let url = "/v3/profitsharing/orders";
+        let mut map: Map<String, Value> = serde_json::from_str(&params.to_json())?;
+        map.insert("appid".to_owned(), self.appid().into());
+        self.insert_sub_merchant(&mut map);
+        let response = self.send(HttpMethod::POST, url, Value::Object(map).to_string())?;
+        self.read_response(response)
+    }
+
+    /// 查询分账结果，服务商模式下查询参数会带上sub_mchid
+    pub fn query_profit_sharing(
+        &self,
+        transaction_id: &str,
+        out_order_no: &str,
+    ) -> Result<ProfitSharingResponse, PayError> {
+        let mut url = format!(
+            "/v3/profitsharing/orders/{}?transaction_id={}",
+            util::url_encode(out_order_no),
+            util::url_encode(transaction_id),
+        );
+        if let Some(sub_mchid) = self.sub_mchid() {
+            url.push_str(&format!("&sub_mchid={}", util::url_encode(sub_mchid)));
+        }
+        self.get_pay(&url)
+    }
+
+    /// 查询订单剩余待分金额，再次请求分账前可以先确认剩余可分金额
+    pub fn query_profit_sharing_amount(
+        &self,
+        transaction_id: &str,
+    ) -> Result<ProfitSharingAmountResponse, PayError> {
+        let url = format!(
+            "/v3/profitsharing/transactions/{}/amounts",
+            util::url_encode(transaction_id)
+        );
+        self.get_pay(&url)
+    }
+
+    /// 解冻剩余资金，不注入appid，服务商模式下注入sub_mchid
+    pub fn unfreeze_profit_sharing(
+        &self,
+        params: ProfitSharingUnfreezeParams,
+    ) -> Result<ProfitSharingResponse, PayError> {
+        let url = "/v3/profitsharing/orders/unfreeze";
+        let mut map: Map<String, Value> = serde_json::from_str(&params.to_json())?;
+        if let Some(sub_mchid) = self.sub_mchid() {
+            map.insert("sub_mchid".to_owned(), sub_mchid.into());
+        }
+        let response = self.send(HttpMethod::POST, url, Value::Object(map).to_string())?;
+        self.read_response(response)
+    }
+
+    /// 请求分账回退，服务商模式下注入sub_mchid
+    pub fn return_profit_sharing(
+        &self,
+        params: ProfitSharingReturnParams,
+    ) -> Result<ProfitSharingReturnResponse, PayError> {
+        let url = "/v3/profitsharing/return-orders";
+        params.validate()?;
+        let mut map: Map<String, Value> = serde_json::from_str(&params.to_json())?;
+        if let Some(sub_mchid) = self.sub_mchid() {
+            map.insert("sub_mchid".to_owned(), sub_mchid.into());
+        }
+        let response = self.send(HttpMethod::POST, url, Value::Object(map).to_string())?;
+        self.read_response(response)
+    }
+
+    /// 查询分账回退结果，服务商模式下查询参数会带上sub_mchid
+    pub fn query_profit_sharing_return(
+        &self,
+        out_order_no: &str,
+        out_return_no: &str,
+    ) -> Result<ProfitSharingReturnResponse, PayError> {
+        let mut url = format!(
+            "/v3/profitsharing/return-orders/{}?out_order_no={}",
+            util::url_encode(out_return_no),
+            util::url_encode(out_order_no),
+        );
+        if let Some(sub_mchid) = self.sub_mchid() {
+            url.push_str(&format!("&sub_mchid={}", util::url_encode(sub_mchid)));
+        }
+        self.get_pay(&url)
+    }
+
+    /// 添加分账接收方，接收方名称会使用平台证书加密，
+    /// 平台证书缓存为空或已过期时先调用`refresh_certificates`
+    pub fn add_profit_sharing_receiver(
+        &self,
+        mut params: ProfitSharingReceiverAddParams,
+    ) -> Result<ProfitSharingReceiverResponse, PayError> {
+        let url = "/v3/profitsharing/receivers/add";
+        let mut headers = HeaderMap::new();
+        if let Some(name) = &params.name {
+            if self.platform_certificates.needs_refresh() {
+                self.refresh_certificates()?;
+            }
+            let serial_no = self.encryption_certificate()?.serial_no;
+            params.name = Some(self.rsa_encrypt_field(name)?);
+            headers.insert("Wechatpay-Serial", serial_no.parse().unwrap());
+        }
+        let mut map: Map<String, Value> = serde_json::from_str(&params.to_json())?;
+        map.insert("appid".to_owned(), self.appid().into());
+        self.insert_sub_merchant(&mut map);
+        let response = self.send_with_headers(
+            HttpMethod::POST,
+            url,
+            Value::Object(map).to_string(),
+            headers,
+        )?;
+        self.read_response(response)
+    }
+
+    /// 删除分账接收方，删除后不能再向该接收方分账
+    pub fn delete_profit_sharing_receiver(
+        &self,
+        params: ProfitSharingReceiverDeleteParams,
+    ) -> Result<ProfitSharingReceiverResponse, PayError> {
+        let url = "/v3/profitsharing/receivers/delete";
+        let mut map: Map<String, Value> = serde_json::from_str(&params.to_json())?;
+        map.insert("appid".to_owned(), self.appid().into());
+        self.insert_sub_merchant(&mut map);
+        let response = self.send(HttpMethod::POST, url, Value::Object(map).to_string())?;
+        self.read_response(response)
+    }
+}
@@ -0,0 +1,69 @@
+use crate::error::PayError;
+use crate::model::TransferBatchParams;
+use crate::pay::WechatPay;
+use crate::request::HttpMethod;
+use crate::response::{TransferBatchQueryResponse, TransferBatchResponse, TransferDetailResponse};
+use crate::util;
+use reqwest::header::HeaderMap;
+
+impl WechatPay {
+    /// 发起商家转账到零钱，收款用户姓名会使用平台证书加密，
+    /// 平台证书缓存为空或已过期时先调用`refresh_certificates`
+    pub fn batch_transfer(
+        &self,
+        mut params: TransferBatchParams,
+    ) -> Result<TransferBatchResponse, PayError> {
+        let url = "/v3/transfer/batches";
+        let has_user_name = params
+            .transfer_detail_list
+            .iter()
+            .any(|detail| detail.user_name.is_some());
+        if has_user_name && self.platform_certificates.needs_refresh() {
+            self.refresh_certificates()?;
+        }
+        let serial_no = self.encrypt_transfer_user_names(&mut params)?;
+        let mut headers = HeaderMap::new();
+        if let Some(serial_no) = serial_no {
+            headers.insert("Wechatpay-Serial", serial_no.parse().unwrap());
+        }
+        let body = self.build_direct_body(&params)?;
+        let response = self.send_with_headers(HttpMethod::POST, url, body, headers)?;
+        self.read_response(response)
+    }
+
+    /// 通过商家批次单号查询批次单，need_query_detail为true时按offset、limit分页返回转账明细单
+    pub fn query_transfer_batch(
+        &self,
+        out_batch_no: &str,
+        need_query_detail: bool,
+        offset: u32,
+        limit: u32,
+    ) -> Result<TransferBatchQueryResponse, PayError> {
+        util::validate_out_batch_no(out_batch_no)?;
+        let url = format!(
+            "/v3/transfer/batches/out-batch-no/{}?need_query_detail={}&offset={}&limit={}",
+            util::url_encode(out_batch_no),
+            need_query_detail,
+            offset,
+            limit,
+        );
+        self.get_pay(&url)
+    }
+
+    /// 通过商家明细单号查询明细单，收款用户姓名会用商户私钥解密后返回
+    pub fn query_transfer_detail(
+        &self,
+        out_batch_no: &str,
+        out_detail_no: &str,
+    ) -> Result<TransferDetailResponse, PayError> {
+        util::validate_out_batch_no(out_batch_no)?;
+        let url = format!(
+            "/v3/transfer/batches/out-batch-no/{}/details/out-detail-no/{}",
+            util::url_encode(out_batch_no),
+            util::url_encode(out_detail_no),
+        );
+        let mut response: TransferDetailResponse = self.get_pay(&url)?;
+        self.decrypt_transfer_user_name(&mut response)?;
+        Ok(response)
+    }
+}
@@ -0,0 +1,209 @@
+//! 平台证书缓存，按证书序列号保存解密后的平台证书，超过有效期（默认12小时）后需要重新拉取
+use crate::response::PlatformCertificate;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default)]
+struct StoreInner {
+    certificates: HashMap<String, PlatformCertificate>,
+    refreshed_at: Option<Instant>,
+}
+
+/// 平台证书缓存，clone后共享同一份缓存
+#[derive(Debug, Clone)]
+pub struct CertificateStore {
+    inner: Arc<RwLock<StoreInner>>,
+    ttl: Duration,
+}
+
+impl Default for CertificateStore {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_TTL)
+    }
+}
+
+impl CertificateStore {
+    /// 默认12小时刷新一次
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(12 * 60 * 60);
+
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(StoreInner::default())),
+            ttl,
+        }
+    }
+
+    /// 按证书序列号查找平台证书
+    pub fn get(&self, serial_no: &str) -> Option<PlatformCertificate> {
+        self.inner
+            .read()
+            .unwrap()
+            .certificates
+            .get(serial_no)
+            .cloned()
+    }
+
+    /// 当前处于有效期内、弃用时间最晚的平台证书，用于加密敏感字段。
+    /// 证书轮换期间尚未启用的新证书不会被选中
+    pub fn newest(&self) -> Option<PlatformCertificate> {
+        let now = chrono::Utc::now();
+        self.inner
+            .read()
+            .unwrap()
+            .certificates
+            .values()
+            .filter(|cert| cert.is_valid_at(&now))
+            .filter_map(|cert| cert.not_after().ok().map(|not_after| (not_after, cert)))
+            .max_by_key(|(not_after, _)| *not_after)
+            .map(|(_, cert)| cert.clone())
+    }
+
+    /// 追加平台证书（同序列号覆盖）
+    pub fn extend(&self, certificates: Vec<PlatformCertificate>) {
+        let mut inner = self.inner.write().unwrap();
+        for cert in certificates {
+            inner.certificates.insert(cert.serial_no.clone(), cert);
+        }
+        inner.refreshed_at = Some(Instant::now());
+    }
+
+    /// 用新拉取的平台证书替换缓存
+    pub fn replace(&self, certificates: Vec<PlatformCertificate>) {
+        let mut inner = self.inner.write().unwrap();
+        inner.certificates = certificates
+            .into_iter()
+            .map(|cert| (cert.serial_no.clone(), cert))
+            .collect();
+        inner.refreshed_at = Some(Instant::now());
+    }
+
+    /// 从未加载过或超过ttl时需要刷新
+    pub fn needs_refresh(&self) -> bool {
+        match self.inner.read().unwrap().refreshed_at {
+            Some(refreshed_at) => refreshed_at.elapsed() >= self.ttl,
+            None => true,
+        }
+    }
+
+    /// 当前处于有效期内的平台证书序列号，按序列号排序
+    pub fn active_serials(&self) -> Vec<String> {
+        let now = chrono::Utc::now();
+        let mut serials: Vec<String> = self
+            .inner
+            .read()
+            .unwrap()
+            .certificates
+            .values()
+            .filter(|cert| cert.is_valid_at(&now))
+            .map(|cert| cert.serial_no.clone())
+            .collect();
+        serials.sort();
+        serials
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.read().unwrap().certificates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::certificate::CertificateStore;
+    use crate::mock::test_platform_certificate;
+    use crate::response::PlatformCertificate;
+    use chrono::{DateTime, Utc};
+    use std::time::Duration;
+
+    fn platform_certificate(serial_no: &str) -> PlatformCertificate {
+        PlatformCertificate {
+            serial_no: serial_no.to_string(),
+            ..test_platform_certificate()
+        }
+    }
+
+    #[test]
+    fn test_certificate_store() {
+        let store = CertificateStore::default();
+        assert!(store.needs_refresh());
+        store.extend(vec![platform_certificate("A"), platform_certificate("B")]);
+        assert!(!store.needs_refresh());
+        let shared = store.clone();
+        assert_eq!(shared.len(), 2);
+        store.replace(vec![platform_certificate("C")]);
+        assert!(shared.get("A").is_none());
+        assert!(shared.get("C").is_some());
+
+        let store = CertificateStore::new(Duration::ZERO);
+        store.extend(vec![platform_certificate("A")]);
+        assert!(store.needs_refresh());
+    }
+
+    #[test]
+    fn test_certificate_validity() {
+        let cert = platform_certificate("A");
+        assert_eq!(cert.serial_no(), "A");
+        let not_before = cert.not_before().unwrap();
+        let not_after = cert.not_after().unwrap();
+        assert_eq!(not_before.to_rfc3339(), "2024-01-01T08:00:00+08:00");
+        assert_eq!(not_after.timestamp(), 4070908800);
+        let at = |time: &str| DateTime::parse_from_rfc3339(time).unwrap();
+        assert!(!cert.is_valid_at(&at("2023-12-31T23:59:59Z")));
+        assert!(cert.is_valid_at(&at("2024-01-01T00:00:00Z")));
+        assert!(cert.is_valid_at(&at("2098-12-31T23:59:59Z").with_timezone(&Utc)));
+        assert!(!cert.is_valid_at(&at("2099-01-01T08:00:00+08:00")));
+
+        let store = CertificateStore::default();
+        let mut expired = platform_certificate("B");
+        expired.effective_time = "2000-01-01T08:00:00+08:00".to_string();
+        expired.expire_time = "2001-01-01T08:00:00+08:00".to_string();
+        let mut active = platform_certificate("C");
+        active.effective_time = "2000-01-01T08:00:00+08:00".to_string();
+        active.expire_time = "2099-01-01T08:00:00+08:00".to_string();
+        let mut invalid = platform_certificate("D");
+        invalid.expire_time = "unknown".to_string();
+        assert!(invalid.not_after().is_err());
+        store.extend(vec![active, expired, invalid]);
+        assert_eq!(store.active_serials(), vec!["C".to_string()]);
+    }
+
+    #[test]
+    fn test_newest() {
+        let certificate = |serial_no: &str, effective_time: &str, expire_time: &str| {
+            let mut cert = platform_certificate(serial_no);
+            cert.effective_time = effective_time.to_string();
+            cert.expire_time = expire_time.to_string();
+            cert
+        };
+        let store = CertificateStore::default();
+        store.extend(vec![
+            certificate(
+                "A",
+                "2000-01-01T08:00:00+08:00",
+                "2001-01-01T08:00:00+08:00",
+            ),
+            certificate(
+                "B",
+                "2000-01-01T08:00:00+08:00",
+                "2090-01-01T07:00:00+08:00",
+            ),
+            certificate("C", "2000-01-01T08:00:00+08:00", "2089-12-31T23:30:00Z"),
+            certificate(
+                "D",
+                "2098-01-01T08:00:00+08:00",
+                "2099-01-01T08:00:00+08:00",
+            ),
+        ]);
+        assert_eq!(store.newest().unwrap().serial_no, "C");
+        store.replace(vec![certificate(
+            "D",
+            "2098-01-01T08:00:00+08:00",
+            "2099-01-01T08:00:00+08:00",
+        )]);
+        assert!(store.newest().is_none());
+    }
+}
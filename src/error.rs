@@ -1,17 +1,152 @@
+use std::time::Duration;
+
 #[derive(Debug, thiserror::Error)]
 pub enum PayError {
-    #[error("http error: {0}")]
-    RequestError(#[from] reqwest::Error),
+    /// 连接失败、DNS解析失败等网络错误，连接失败时`is_connect()`为true
+    #[error("network error: {0}")]
+    Network(reqwest::Error),
+    /// 读取或解码响应体失败
+    #[error("decode error: {0}")]
+    Decode(reqwest::Error),
+    #[error("request timeout: {0}")]
+    Timeout(reqwest::Error),
     #[error("pay error: {0}")]
     WechatError(String),
+    #[error(
+        "wechat api error: status={status} code={code} message={message} request_id={request_id:?}"
+    )]
+    WechatApiError {
+        status: u16,
+        code: String,
+        message: String,
+        detail: Option<serde_json::Value>,
+        /// 响应头中的`Request-ID`，向微信支付反馈问题时需要提供
+        request_id: Option<String>,
+    },
+    /// 触发微信频率限制（HTTP 429或错误码`FREQUENCY_LIMITED`），retry_after取自响应头`Retry-After`
+    #[error("rate limited: retry_after={retry_after:?}")]
+    RateLimited { retry_after: Option<Duration> },
+    /// 微信返回401，通常是商户号、商户证书序列号与私钥不匹配
+    #[error("auth failed: code={code} message={message}")]
+    AuthFailed { code: String, message: String },
+    #[error("io error: {0}")]
+    IoError(#[from] std::io::Error),
     #[error("json error: {0}")]
     JsonError(#[from] serde_json::Error),
+    /// 开启`set_capture_raw`时，响应体反序列化失败会附带原始响应体
+    #[error("deserialize error: {source}, body: {body}")]
+    Deserialize {
+        source: serde_json::Error,
+        body: String,
+    },
     #[error("Decrypt error: {0}")]
     DecryptError(String),
     #[error("Base64 decode error: {0}")]
     DecodeError(#[from] base64::DecodeError),
     #[error("verify error: {0}")]
     VerifyError(String),
-    #[error("weixin not found error")]
-    WeixinNotFound,
+    #[error("encrypt error: {0}")]
+    EncryptError(String),
+    #[error("invalid private key: {0}")]
+    InvalidPrivateKey(String),
+    #[error("missing config: {0}")]
+    MissingConfig(&'static str),
+    #[error("signature verify failed")]
+    SignatureVerifyFailed,
+    #[error("notification expired: timestamp={timestamp} now={now}")]
+    NotificationExpired { timestamp: i64, now: i64 },
+    #[error("notification replayed: nonce={0}")]
+    NotificationReplayed(String),
+    #[error("invalid param {field}: {reason}")]
+    InvalidParam { field: &'static str, reason: String },
+    #[error("poll timeout: out_trade_no={0}")]
+    PollTimeout(String),
+    #[error("platform certificate not found: {0}")]
+    CertificateNotFound(String),
+    /// 缓存中该序列号的平台证书已过期，需要调用`refresh_certificates`
+    #[error("platform certificate expired: {serial}")]
+    CertificateExpired { serial: String },
+    #[error("certificate error: {0}")]
+    CertificateError(String),
+    #[error("bill hash mismatch: expected {expected}, actual {actual}")]
+    BillHashMismatch { expected: String, actual: String },
+    #[error("bill parse error: {0}")]
+    BillParseError(String),
+    #[error("image size ({size} bytes) exceeds the maximum allowed size ({max} bytes)")]
+    ImageTooLarge { size: usize, max: usize },
+    #[error("video size ({size} bytes) exceeds the maximum allowed size ({max} bytes)")]
+    VideoTooLarge { size: usize, max: usize },
+    #[error("unsupported image format: {0}")]
+    UnsupportedImageFormat(String),
+    #[error("unsupported video format: {0}")]
+    UnsupportedVideoFormat(String),
+    #[error("image format mismatch: extension {extension}, detected {detected}")]
+    ImageFormatMismatch { extension: String, detected: String },
+    #[error("invalid filename, no extension found")]
+    MissingFileExtension,
+    /// H5中间页中没有找到`weixin://`链接，body_snippet为页面开头的内容，开启`log-bodies`时为完整页面
+    #[error("weixin not found error, body: {body_snippet}")]
+    WeixinNotFound { body_snippet: String },
+}
+
+impl PayError {
+    /// 微信接口返回的错误码，其他错误返回None
+    pub fn wechat_code(&self) -> Option<&str> {
+        match self {
+            PayError::WechatApiError { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+
+    /// 商户订单号已被使用：重复下单或订单已支付
+    pub(crate) fn is_out_trade_no_used(&self) -> bool {
+        matches!(self.wechat_code(), Some("OUT_TRADE_NO_USED" | "ORDERPAID"))
+    }
+}
+
+impl From<reqwest::Error> for PayError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            PayError::Timeout(e)
+        } else if e.is_decode() || e.is_body() {
+            PayError::Decode(e)
+        } else {
+            PayError::Network(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PayError;
+    use crate::mock::{response, MockServer};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_reqwest_error_classification() {
+        let server = MockServer::start(vec![response(200, &[], "not json")]);
+        let error = reqwest::get(&server.base_url)
+            .await
+            .unwrap()
+            .json::<serde_json::Value>()
+            .await
+            .unwrap_err();
+        assert!(matches!(PayError::from(error), PayError::Decode(_)));
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let error = reqwest::get(&url).await.unwrap_err();
+        assert!(matches!(PayError::from(error), PayError::Network(e) if e.is_connect()));
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let error = client.get(&url).send().await.unwrap_err();
+        assert!(matches!(PayError::from(error), PayError::Timeout(_)));
+        drop(listener);
+    }
 }
@@ -25,6 +25,19 @@ wechat-pay-rust-sdk = {version = "x.x.x"}
 wechat-pay-rust-sdk = {version = "x.x.x", features = ["blocking"]}
 # debug日志开启
 wechat-pay-rust-sdk = {version = "x.x.x", features = ["blocking","debug-print"]}
+# 同时打印请求体/响应体（openid会被隐藏）
+wechat-pay-rust-sdk = {version = "x.x.x", features = ["blocking","log-bodies"]}
+```
+
+请求签名默认使用纯Rust实现（rsa crate），QPS较高时可以开启`openssl-crypto`改用openssl签名，
+RSA-2048单次签名约1.6ms降到约0.37ms（release编译，单核）；也可以通过`with_signer`使用自定义的`sign::Signer`实现
+```toml
+wechat-pay-rust-sdk = {version = "x.x.x", features = ["openssl-crypto"]}
+```
+
+JSAPI下单需要的openid可以开启`oauth`后通过`get_openid`用网页授权code换取
+```toml
+wechat-pay-rust-sdk = {version = "x.x.x", features = ["oauth"]}
 ```
 
 ## native支付
@@ -155,12 +168,12 @@ use wechat_pay_rust_sdk::model::MicroParams;
 use wechat_pay_rust_sdk::pay::WechatPay;
 
 let wechat_pay = WechatPay::from_env();
-let body = wechat_pay.micro_pay(MicroParams::new(
+let body = wechat_pay.mini_program_pay(MicroParams::new(
     "测试支付1分",
     "1243243",
     1.into(),
     "open_id".into()
-    )).expect("micro_pay error");
+    )).expect("mini_program_pay error");
 println!("body: {:?}", body);
 ```
 输出
@@ -352,12 +365,21 @@ async fn pay_notify(bytes: Bytes, req: HttpRequest) -> impl Responder {
 pub mod blocking;
 #[cfg(not(feature = "blocking"))]
 pub mod async_impl;
+pub mod bill;
+pub mod certificate;
 pub mod error;
 pub mod model;
 pub mod pay;
 pub mod pay_type;
 pub mod request;
+pub mod replay;
 pub mod response;
+pub mod retry;
 pub mod sign;
+pub mod transport;
 pub mod util;
 pub(crate) mod macros;
+#[cfg(test)]
+pub(crate) mod mock;
+#[cfg(test)]
+mod tests;
@@ -32,3 +32,18 @@ macro_rules! debug {
     ($name:expr)=>{};
     ($name:expr $(,$arg:tt)+ $(,)?) => {};
 }
+
+/// 打印请求体/响应体，需要开启`log-bodies`，openid会被隐藏
+#[cfg(feature = "log-bodies")]
+#[macro_export]
+macro_rules! debug_body {
+    ($name:expr, $body:expr) => {
+        tracing::debug!($name, $crate::util::redact_body(&$body));
+    };
+}
+
+#[cfg(not(feature = "log-bodies"))]
+#[macro_export]
+macro_rules! debug_body {
+    ($name:expr, $body:expr) => {};
+}
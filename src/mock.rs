@@ -0,0 +1,365 @@
+//! 测试用的本地http服务，按顺序返回预设的响应，并记录收到的原始请求
+#![allow(dead_code)]
+#[cfg(feature = "blocking")]
+use crate::error::PayError;
+use crate::pay::WechatPay;
+use crate::response::PlatformCertificate;
+#[cfg(feature = "blocking")]
+use crate::transport::Transport;
+#[cfg(not(feature = "blocking"))]
+use crate::transport::{Transport, TransportFuture};
+use crate::util;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::Aes256Gcm;
+use reqwest::header::HeaderMap;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::sha2::{Digest, Sha256};
+use rsa::{Pkcs1v15Sign, RsaPrivateKey, RsaPublicKey};
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub(crate) const TEST_PRIVATE_KEY: &str = include_str!("../testdata/apiclient_key.pem");
+pub(crate) const TEST_V3_KEY: &str = "a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6";
+/// 测试用平台证书，序列号3B2F6E8A1C4D5E6F708192A3B4C5D6E7F8091A2B，有效期2024-01-01至2029-01-01
+pub(crate) const TEST_PLATFORM_CERT: &str = include_str!("../testdata/platform_cert.pem");
+pub(crate) const TEST_PLATFORM_KEY: &str = include_str!("../testdata/platform_key.pem");
+
+/// 用TEST_PLATFORM_KEY签名的回调（openssl dgst -sha256 -sign testdata/platform_key.pem）
+pub(crate) const TEST_PLATFORM_SERIAL: &str = "3B2F6E8A1C4D5E6F708192A3B4C5D6E7F8091A2B";
+pub(crate) const TEST_NOTIFY_TIMESTAMP: &str = "1705066785";
+/// 测试用的当前时间，回调时间戳在容忍范围内
+pub(crate) const TEST_NOW: i64 = 1705066800;
+pub(crate) const TEST_NOTIFY_NONCE: &str = "Jh9oPZelCJIQeQ47kz4stzvDKpLEUhCX";
+pub(crate) const TEST_NOTIFY_BODY: &str =
+    r#"{"id":"29a61973-babf-599a-966d-6bcdcf17360c","event_type":"TRANSACTION.SUCCESS"}"#;
+pub(crate) const TEST_NOTIFY_SIGNATURE: &str = "Wda8PfoCus5VtgxUWok5U1v5k+C67lTVwww8N6FMat8Qa7+UkKnU8xwzz9Y5632v+5/oFMCSbSXoLmxwfPt8eKO9DsJltmck7Zp8UO6JWtofebaT7YW7EHvJvgb6Zr/ZAwmWf2FFBvezaP0qEKHTXOZEEMLhIThI8W1LW98uardyiUhCVeBiVbxyyQW8mHX6Zq26FgG8kQ5yGVjJrKwpnW5gUyCFH8uj9D1o+guURNx6Af7NRzDXBt7l9zg+N3a4EF/2k87rrGAJ56xicEt/swZEbNXQ7qEa1sN5lfwmPKVtdGwpIuRK+N1665B5l65gireX5YHL10btQ9QDGhLrJw==";
+
+pub(crate) fn test_wechat_pay(base_url: &str) -> WechatPay {
+    WechatPay::new(
+        "wxd678efh567hg6787",
+        "1230000109",
+        TEST_PRIVATE_KEY,
+        "5F3A9C2B7D1E4F6A8B0C2D4E6F8091A2B3C4D5E6",
+        TEST_V3_KEY,
+        "https://mydomain.com/notify",
+    )
+    .with_base_url(base_url)
+    .with_clock(|| TEST_NOW)
+}
+
+/// 在测试里驱动一次请求：异步版本用共享的运行时执行，阻塞版本原样返回
+#[cfg(not(feature = "blocking"))]
+pub(crate) fn run<F: std::future::Future>(future: F) -> F::Output {
+    static RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();
+    RUNTIME
+        .get_or_init(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .expect("build test runtime")
+        })
+        .block_on(future)
+}
+
+/// 在测试里驱动一次请求：异步版本用共享的运行时执行，阻塞版本原样返回
+#[cfg(feature = "blocking")]
+pub(crate) fn run<T>(output: T) -> T {
+    output
+}
+
+/// 不经过网络的[Transport]，按顺序返回预设的响应，并记录收到的请求
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FakeTransport {
+    responses: Arc<Mutex<VecDeque<(u16, String)>>>,
+    requests: Arc<Mutex<Vec<FakeRequest>>>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct FakeRequest {
+    pub(crate) method: String,
+    pub(crate) url: String,
+    pub(crate) headers: HeaderMap,
+    pub(crate) body: String,
+}
+
+impl FakeTransport {
+    pub(crate) fn new(responses: Vec<(u16, &str)>) -> Self {
+        let responses = responses
+            .into_iter()
+            .map(|(status, body)| (status, body.to_string()))
+            .collect();
+        Self {
+            responses: Arc::new(Mutex::new(responses)),
+            requests: Arc::default(),
+        }
+    }
+
+    pub(crate) fn requests(&self) -> Vec<FakeRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn respond(
+        &self,
+        method: &reqwest::Method,
+        url: &reqwest::Url,
+        headers: &HeaderMap,
+        body: Option<&[u8]>,
+    ) -> http::Response<String> {
+        self.requests.lock().unwrap().push(FakeRequest {
+            method: method.to_string(),
+            url: url.to_string(),
+            headers: headers.clone(),
+            body: String::from_utf8_lossy(body.unwrap_or_default()).to_string(),
+        });
+        let (status, body) = self
+            .responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .expect("no more fake responses");
+        http::Response::builder()
+            .status(status)
+            .header("content-type", "application/json")
+            .body(body)
+            .unwrap()
+    }
+}
+
+#[cfg(not(feature = "blocking"))]
+impl Transport for FakeTransport {
+    fn execute(&self, request: reqwest::Request) -> TransportFuture<'_> {
+        let response = self.respond(
+            request.method(),
+            request.url(),
+            request.headers(),
+            request.body().and_then(|body| body.as_bytes()),
+        );
+        Box::pin(async move { Ok(response.into()) })
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl Transport for FakeTransport {
+    fn execute(
+        &self,
+        request: reqwest::blocking::Request,
+    ) -> Result<reqwest::blocking::Response, PayError> {
+        let response = self.respond(
+            request.method(),
+            request.url(),
+            request.headers(),
+            request.body().and_then(|body| body.as_bytes()),
+        );
+        Ok(response.into())
+    }
+}
+
+/// 模拟微信用APIv3密钥加密数据（AEAD_AES_256_GCM）
+pub(crate) fn encrypt(plaintext: &str, nonce: &str, associated_data: &str) -> String {
+    let cipher = Aes256Gcm::new(TEST_V3_KEY.as_bytes().into());
+    let payload = Payload {
+        msg: plaintext.as_bytes(),
+        aad: associated_data.as_bytes(),
+    };
+    let ciphertext = cipher.encrypt(nonce.as_bytes().into(), payload).unwrap();
+    util::base64_encode(ciphertext)
+}
+
+/// 模拟`/v3/certificates`的响应，证书用APIv3密钥加密
+pub(crate) fn certificates_json() -> String {
+    let nonce = "034246e50ad4";
+    let ciphertext = encrypt(TEST_PLATFORM_CERT, nonce, "certificate");
+    format!(
+        r#"{{"data":[{{"serial_no":"{}","effective_time":"2024-01-01T08:00:00+08:00","expire_time":"2099-01-01T08:00:00+08:00","encrypt_certificate":{{"algorithm":"AEAD_AES_256_GCM","nonce":"{}","associated_data":"certificate","ciphertext":"{}"}}}}]}}"#,
+        TEST_PLATFORM_SERIAL, nonce, ciphertext
+    )
+}
+
+/// 测试用的平台证书缓存项，弃用时间设在很远的将来，加密、验签测试不会随当前时间过期
+pub(crate) fn test_platform_certificate() -> PlatformCertificate {
+    PlatformCertificate {
+        serial_no: TEST_PLATFORM_SERIAL.to_string(),
+        effective_time: "2024-01-01T08:00:00+08:00".to_string(),
+        expire_time: "2099-01-01T08:00:00+08:00".to_string(),
+        certificate: TEST_PLATFORM_CERT.to_string(),
+    }
+}
+
+/// 模拟微信用平台私钥对回调签名
+pub(crate) fn sign_notification(timestamp: &str, nonce: &str, body: &str) -> String {
+    let message = format!("{}\n{}\n{}\n", timestamp, nonce, body);
+    let private_key = RsaPrivateKey::from_pkcs8_pem(TEST_PLATFORM_KEY).unwrap();
+    let hashed = Sha256::new().chain_update(message).finalize();
+    let signature = private_key
+        .sign(Pkcs1v15Sign::new::<Sha256>(), &hashed)
+        .unwrap();
+    util::base64_encode(signature)
+}
+
+/// 模拟微信的回调请求体，resource用APIv3密钥加密
+pub(crate) fn notification_json(event_type: &str, original_type: &str, plaintext: &str) -> String {
+    let nonce = "fdasflkja484";
+    let ciphertext = encrypt(plaintext, nonce, original_type);
+    format!(
+        r#"{{"id":"EV-2018022511223320873","create_time":"2015-05-20T13:29:35+08:00","resource_type":"encrypt-resource","event_type":"{}","summary":"支付成功","resource":{{"original_type":"{}","algorithm":"AEAD_AES_256_GCM","ciphertext":"{}","associated_data":"{}","nonce":"{}"}}}}"#,
+        event_type, original_type, ciphertext, original_type, nonce
+    )
+}
+
+pub(crate) struct MockServer {
+    pub(crate) base_url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    /// 每个连接返回一个响应，响应用完后关闭监听
+    pub(crate) fn start<R: Into<Vec<u8>> + Send + 'static>(responses: Vec<R>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        std::thread::spawn(move || {
+            for response in responses {
+                let Ok((mut stream, _)) = listener.accept() else {
+                    return;
+                };
+                let request = read_request(&mut stream);
+                recorded.lock().unwrap().push(request);
+                let _ = stream.write_all(&response.into());
+                let _ = stream.flush();
+            }
+        });
+        Self { base_url, requests }
+    }
+
+    /// 接受连接但永远不响应，用于超时测试
+    pub(crate) fn hang() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                std::thread::spawn(move || {
+                    let _stream = stream;
+                    std::thread::sleep(Duration::from_secs(30));
+                });
+            }
+        });
+        Self {
+            base_url,
+            requests: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub(crate) fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+pub(crate) fn response(status: u16, headers: &[(&str, &str)], body: &str) -> String {
+    let mut text = format!(
+        "HTTP/1.1 {} MOCK\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        body.len()
+    );
+    for (name, value) in headers {
+        text.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    text.push_str("\r\n");
+    text.push_str(body);
+    text
+}
+
+/// 二进制body的响应，如账单文件
+pub(crate) fn bytes_response(status: u16, body: &[u8]) -> Vec<u8> {
+    let mut data = format!(
+        "HTTP/1.1 {} MOCK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        body.len()
+    )
+    .into_bytes();
+    data.extend_from_slice(body);
+    data
+}
+
+pub(crate) fn json_response(status: u16, body: &str) -> String {
+    response(status, &[("Content-Type", "application/json")], body)
+}
+
+/// 从原始请求中读取header（忽略大小写）
+pub(crate) fn header(request: &str, name: &str) -> Option<String> {
+    request
+        .split("\r\n\r\n")
+        .next()?
+        .lines()
+        .skip(1)
+        .find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.eq_ignore_ascii_case(name)
+                .then(|| value.trim().to_string())
+        })
+}
+
+/// 取出原始请求中的body
+pub(crate) fn body(request: &str) -> String {
+    request
+        .split_once("\r\n\r\n")
+        .map(|(_, body)| body.to_string())
+        .unwrap_or_default()
+}
+
+/// 用测试私钥对应的公钥验证Authorization签名，签名串为`method\nurl\ntimestamp\nnonce_str\nbody\n`
+pub(crate) fn verify_authorization(authorization: &str, method: &str, url: &str, body: &str) {
+    let field = |name: &str| {
+        authorization
+            .split([' ', ','])
+            .find_map(|part| part.strip_prefix(&format!("{}=", name)))
+            .map(|value| value.trim_matches('"').to_string())
+            .unwrap_or_else(|| panic!("{} not found in {}", name, authorization))
+    };
+    let message = format!(
+        "{}\n{}\n{}\n{}\n{}\n",
+        method,
+        url,
+        field("timestamp"),
+        field("nonce_str"),
+        body
+    );
+    let private_key = RsaPrivateKey::from_pkcs8_pem(TEST_PRIVATE_KEY).unwrap();
+    let public_key = RsaPublicKey::from(&private_key);
+    let hashed = Sha256::new().chain_update(message).finalize();
+    let signature = util::base64_decode(field("signature")).unwrap();
+    public_key
+        .verify(Pkcs1v15Sign::new::<Sha256>(), &hashed, &signature)
+        .expect("authorization signature mismatch");
+}
+
+fn read_request(stream: &mut TcpStream) -> String {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = stream.read(&mut buf).unwrap_or(0);
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&data);
+        if let Some(pos) = text.find("\r\n\r\n") {
+            let content_length = text[..pos]
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().ok())
+                        .flatten()
+                })
+                .unwrap_or(0);
+            if data.len() >= pos + 4 + content_length {
+                break;
+            }
+        }
+    }
+    String::from_utf8_lossy(&data).to_string()
+}
@@ -1,42 +1,246 @@
+use crate::error::PayError;
+use crate::response::RefundStatus;
+use crate::util;
+use chrono::{DateTime, FixedOffset};
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 use std::fmt::{Display, Formatter};
+use std::iter::Sum;
+use std::ops::Add;
+use std::str::FromStr;
+use std::time::Duration;
 
 pub trait ParamsTrait {
     fn to_json(&self) -> String;
+    /// 请求体中需要从全局配置注入的字段，默认注入appid、mchid、notify_url
+    fn inject_fields(&self) -> InjectFields {
+        InjectFields::ALL
+    }
+    /// 发送请求前在本地校验参数，例如商户订单号的格式
+    fn validate(&self) -> Result<(), PayError> {
+        Ok(())
+    }
 }
 
-#[derive(Serialize, Debug, Clone)]
+/// 请求体中由[WechatPay](crate::pay::WechatPay)全局配置注入的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InjectFields {
+    pub appid: bool,
+    pub mchid: bool,
+    pub notify_url: bool,
+}
+
+impl InjectFields {
+    /// 注入appid、mchid、notify_url，下单接口使用
+    pub const ALL: Self = Self {
+        appid: true,
+        mchid: true,
+        notify_url: true,
+    };
+    /// 不注入任何字段，退款等非交易接口使用
+    pub const NONE: Self = Self {
+        appid: false,
+        mchid: false,
+        notify_url: false,
+    };
+}
+
+/// 币种，符合ISO 4217标准的三位字母代码，境内商户号仅支持人民币
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
 pub enum Currency {
+    #[default]
     CNY,
+    /// 其他币种，如跨境商户使用的USD、HKD，原样保留
+    Other(String),
+}
+
+impl From<String> for Currency {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "CNY" => Currency::CNY,
+            _ => Currency::Other(value),
+        }
+    }
+}
+
+impl From<&str> for Currency {
+    fn from(value: &str) -> Self {
+        value.to_string().into()
+    }
+}
+
+impl From<Currency> for String {
+    fn from(value: Currency) -> Self {
+        value.to_string()
+    }
 }
 
 impl Display for Currency {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Currency::CNY => write!(f, "CNY"),
+            Currency::Other(currency) => f.write_str(currency),
+        }
+    }
+}
+
+/// 账单类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BillType {
+    /// 返回当日所有订单信息（不含充值退款订单）
+    All,
+    /// 返回当日成功支付的订单（不含充值退款订单）
+    Success,
+    /// 返回当日退款订单（不含充值退款订单）
+    Refund,
+}
+
+impl Display for BillType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BillType::All => write!(f, "ALL"),
+            BillType::Success => write!(f, "SUCCESS"),
+            BillType::Refund => write!(f, "REFUND"),
+        }
+    }
+}
+
+/// 资金账户类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountType {
+    /// 基本账户
+    Basic,
+    /// 运营账户
+    Operation,
+    /// 手续费账户
+    Fees,
+}
+
+impl Display for AccountType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccountType::Basic => write!(f, "BASIC"),
+            AccountType::Operation => write!(f, "OPERATION"),
+            AccountType::Fees => write!(f, "FEES"),
+        }
+    }
+}
+
+/// 账单压缩类型，不填则默认是数据流
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TarType {
+    /// 返回格式为.gzip的压缩包账单
+    Gzip,
+}
+
+impl Display for TarType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TarType::Gzip => write!(f, "GZIP"),
         }
     }
 }
 
-unsafe impl Send for Currency {}
+/// 金额，单位为分。整数默认按分转换，`1.into()`表示1分，按元传入时使用[Fen::from_yuan]
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(transparent)]
+pub struct Fen(pub i64);
+
+impl Fen {
+    /// 元转分，四舍五入到分。先按1e-6精度取整，消除0.1+0.2、1.005这类浮点误差
+    pub fn from_yuan(yuan: f64) -> Self {
+        let fen = (yuan * 100.0 * 1e6).round() / 1e6;
+        Self(fen.round() as i64)
+    }
+    /// 分转元
+    pub fn to_yuan(self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+    /// 金额（分）
+    pub fn fen(self) -> i64 {
+        self.0
+    }
+}
+
+impl From<i64> for Fen {
+    fn from(value: i64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<i32> for Fen {
+    fn from(value: i32) -> Self {
+        Self(value as i64)
+    }
+}
+
+impl From<Fen> for i64 {
+    fn from(value: Fen) -> Self {
+        value.0
+    }
+}
+
+impl Display for Fen {
+    /// 按元显示，保留两位小数
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let fen = self.0.unsigned_abs();
+        write!(f, "{}{}.{:02}", sign, fen / 100, fen % 100)
+    }
+}
+
+impl Add for Fen {
+    type Output = Fen;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
 
-unsafe impl Sync for Currency {}
+impl Sum for Fen {
+    fn sum<I: Iterator<Item = Fen>>(iter: I) -> Self {
+        Self(iter.map(|fen| fen.0).sum())
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AmountInfo {
     ///【标价金额】 订单总金额，单位为分。
-    pub total: i32,
+    pub total: Fen,
+    ///【标价币种】 不填时微信默认为CNY，跨境商户可指定其他币种
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<Currency>,
+}
+
+impl AmountInfo {
+    pub fn with_currency(mut self, currency: impl Into<Currency>) -> Self {
+        self.currency = Some(currency.into());
+        self
+    }
+
+    /// 标价币种，未指定时为CNY
+    pub fn currency(&self) -> Currency {
+        self.currency.clone().unwrap_or_default()
+    }
 }
 
 impl From<i32> for AmountInfo {
     fn from(value: i32) -> Self {
-        Self { total: value }
+        Fen::from(value).into()
     }
 }
 
-unsafe impl Send for AmountInfo {}
-
-unsafe impl Sync for AmountInfo {}
+impl From<Fen> for AmountInfo {
+    fn from(value: Fen) -> Self {
+        Self {
+            total: value,
+            currency: None,
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PayerInfo {
@@ -52,9 +256,43 @@ impl From<&str> for PayerInfo {
     }
 }
 
-unsafe impl Send for PayerInfo {}
+/// JSAPI、小程序下单时openid所属的appid，决定请求体中`payer`下的字段名
+///
+/// 直连商户使用`openid`；服务商模式下，用户在服务商appid（sp_appid）下的openid使用`sp_openid`，
+/// 在子商户appid（sub_appid）下的openid使用`sub_openid`，二者选一，选错会导致下单失败
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OpenidKind {
+    ///【openid】 直连商户，用户在直连商户appid下的唯一标识
+    #[default]
+    Direct,
+    ///【sp_openid】 服务商模式，用户在服务商appid下的唯一标识
+    SpOpenid,
+    ///【sub_openid】 服务商模式，用户在子商户appid下的唯一标识，下单时需要传入sub_appid
+    SubOpenid,
+}
+
+impl OpenidKind {
+    /// 请求体中`payer`下的字段名
+    pub fn field_name(&self) -> &'static str {
+        match self {
+            OpenidKind::Direct => "openid",
+            OpenidKind::SpOpenid => "sp_openid",
+            OpenidKind::SubOpenid => "sub_openid",
+        }
+    }
 
-unsafe impl Sync for PayerInfo {}
+    /// 序列化后的下单参数中，把`payer.openid`改为对应的字段名
+    fn rename_payer(&self, mut value: serde_json::Value) -> String {
+        if *self != OpenidKind::Direct {
+            if let Some(payer) = value["payer"].as_object_mut() {
+                if let Some(openid) = payer.remove("openid") {
+                    payer.insert(self.field_name().to_string(), openid);
+                }
+            }
+        }
+        value.to_string()
+    }
+}
 
 #[derive(Serialize, Debug, Clone)]
 pub struct GoodsDetail {
@@ -63,7 +301,7 @@ pub struct GoodsDetail {
     ///【商品数量】 用户购买的数量
     pub quantity: i32,
     ///【商品单价】 单位为：分。如果商户有优惠，需传输商户优惠后的单价(例如：用户对一笔100元的订单使用了商场发的纸质优惠券100-50，则活动商品的单价应为原单价-50)
-    pub unit_price: i32,
+    pub unit_price: Fen,
     ///【微信支付商品编码】 微信支付定义的统一商品编号（没有可不传）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub wechatpay_goods_id: Option<String>,
@@ -72,9 +310,33 @@ pub struct GoodsDetail {
     pub goods_name: Option<String>,
 }
 
-unsafe impl Send for GoodsDetail {}
+impl GoodsDetail {
+    pub fn new<S: AsRef<str>>(
+        merchant_goods_id: S,
+        quantity: i32,
+        unit_price: impl Into<Fen>,
+    ) -> Self {
+        Self {
+            merchant_goods_id: merchant_goods_id.as_ref().to_string(),
+            quantity,
+            unit_price: unit_price.into(),
+            wechatpay_goods_id: None,
+            goods_name: None,
+        }
+    }
+
+    ///【商品名称】 商品的实际名称
+    pub fn with_goods_name<S: AsRef<str>>(mut self, goods_name: S) -> Self {
+        self.goods_name = Some(goods_name.as_ref().to_string());
+        self
+    }
 
-unsafe impl Sync for GoodsDetail {}
+    ///【微信支付商品编码】 微信支付定义的统一商品编号
+    pub fn with_wechatpay_goods_id<S: AsRef<str>>(mut self, wechatpay_goods_id: S) -> Self {
+        self.wechatpay_goods_id = Some(wechatpay_goods_id.as_ref().to_string());
+        self
+    }
+}
 
 #[derive(Serialize, Debug, Clone)]
 pub struct OrderDetail {
@@ -83,7 +345,7 @@ pub struct OrderDetail {
     /// 2、当订单原价与支付金额不相等，则不享受优惠。
     /// 3、该字段主要用于防止同一张小票分多次支付，以享受多次优惠的情况，正常支付订单不必上传此参数。
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub cost_price: Option<i32>,
+    pub cost_price: Option<Fen>,
     ///【商品小票ID】 商家小票ID
     #[serde(skip_serializing_if = "Option::is_none")]
     pub invoice_id: Option<String>,
@@ -91,9 +353,37 @@ pub struct OrderDetail {
     pub goods_detail: Vec<GoodsDetail>,
 }
 
-unsafe impl Send for OrderDetail {}
+impl OrderDetail {
+    /// 单品优惠需要上报商品明细
+    pub fn new(goods_detail: Vec<GoodsDetail>) -> Self {
+        Self {
+            cost_price: None,
+            invoice_id: None,
+            goods_detail,
+        }
+    }
+
+    ///【订单原价】 整张小票的交易金额
+    pub fn with_cost_price(mut self, cost_price: impl Into<Fen>) -> Self {
+        self.cost_price = Some(cost_price.into());
+        self
+    }
+
+    ///【商品小票ID】 商家小票ID
+    pub fn with_invoice_id<S: AsRef<str>>(mut self, invoice_id: S) -> Self {
+        self.invoice_id = Some(invoice_id.as_ref().to_string());
+        self
+    }
 
-unsafe impl Sync for OrderDetail {}
+    /// 没有任何字段时请求体中不输出detail
+    pub fn is_empty(&self) -> bool {
+        self.cost_price.is_none() && self.invoice_id.is_none() && self.goods_detail.is_empty()
+    }
+
+    fn is_omitted(detail: &Option<OrderDetail>) -> bool {
+        detail.as_ref().is_none_or(OrderDetail::is_empty)
+    }
+}
 
 #[derive(Serialize, Debug, Clone)]
 pub struct StoreInfo {
@@ -120,6 +410,49 @@ pub struct SceneInfo {
     ///【商户门店信息】 商户门店信息
     #[serde(skip_serializing_if = "Option::is_none")]
     pub store_info: Option<StoreInfo>,
+    ///【H5场景信息】 H5支付必填
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub h5_info: Option<H5Info>,
+}
+
+impl SceneInfo {
+    pub fn new<S: AsRef<str>>(payer_client_ip: S) -> Self {
+        Self {
+            payer_client_ip: payer_client_ip.as_ref().to_string(),
+            device_id: None,
+            store_info: None,
+            h5_info: None,
+        }
+    }
+
+    ///【H5场景信息】 H5场景信息
+    pub fn with_h5_info(mut self, h5_info: H5Info) -> Self {
+        self.h5_info = Some(h5_info);
+        self
+    }
+}
+
+impl From<&str> for SceneInfo {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<String> for SceneInfo {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<H5SceneInfo> for SceneInfo {
+    fn from(value: H5SceneInfo) -> Self {
+        Self {
+            payer_client_ip: value.payer_client_ip,
+            device_id: value.device_id,
+            store_info: value.store_info,
+            h5_info: Some(value.h5_info),
+        }
+    }
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -158,6 +491,18 @@ pub struct H5Info {
     pub package_name: Option<String>,
 }
 
+impl From<H5Type> for H5Info {
+    fn from(value: H5Type) -> Self {
+        Self {
+            h5_type: value.to_string(),
+            app_name: None,
+            app_url: None,
+            bundle_id: None,
+            package_name: None,
+        }
+    }
+}
+
 #[derive(Serialize, Debug, Clone)]
 pub struct H5SceneInfo {
     ///【用户终端IP】 用户的客户端IP，支持IPv4和IPv6两种格式的IP地址。
@@ -189,10 +534,6 @@ impl H5SceneInfo {
     }
 }
 
-unsafe impl Send for SceneInfo {}
-
-unsafe impl Sync for SceneInfo {}
-
 impl ParamsTrait for SceneInfo {
     fn to_json(&self) -> String {
         serde_json::to_string(self).unwrap()
@@ -203,6 +544,9 @@ impl ParamsTrait for SceneInfo {
 pub struct JsapiParams {
     ///【商品描述】 商品描述
     pub description: String,
+    ///【通知地址】 异步接收微信支付结果通知的回调地址，不设置时使用全局配置的notify_url
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify_url: Option<String>,
     ///【商户订单号】 商户系统内部订单号，只能是数字、大小写字母_-*且在同一个商户号下唯一。
     pub out_trade_no: String,
     ///【订单金额】 订单金额信息
@@ -213,26 +557,41 @@ pub struct JsapiParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub attach: Option<String>,
     ///【优惠功能】 优惠功能
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "OrderDetail::is_omitted")]
     pub detail: Option<OrderDetail>,
+    ///【订单优惠标记】 商品标记，代金券或立减优惠功能的参数。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goods_tag: Option<String>,
     ///【交易结束时间】 订单失效时间，遵循rfc3339标准格式，格式为yyyy-MM-DDTHH:mm:ss+TIMEZONE，yyyy-MM-DD表示年月日，T出现在字符串中，表示time元素的开头，HH:mm:ss表示时分秒，TIMEZONE表示时区（+08:00表示东八区时间，领先UTC8小时，即北京时间）。例如：2015-05-20T13:29:35+08:00表示，北京时间2015年5月20日13点29分35秒。
     #[serde(skip_serializing_if = "Option::is_none")]
     pub time_expire: Option<String>,
     ///【场景信息】 支付场景描述
     #[serde(skip_serializing_if = "Option::is_none")]
     pub scene_info: Option<SceneInfo>,
+    /// `payer.openid`序列化时使用的字段名，服务商模式下使用sp_openid或sub_openid
+    #[serde(skip)]
+    pub openid_kind: OpenidKind,
 }
 
 impl ParamsTrait for JsapiParams {
     fn to_json(&self) -> String {
-        serde_json::to_string(self).unwrap()
+        self.openid_kind
+            .rename_payer(serde_json::to_value(self).unwrap())
+    }
+    fn validate(&self) -> Result<(), PayError> {
+        util::validate_out_trade_no(&self.out_trade_no)?;
+        util::validate_openid(&self.payer.openid)
     }
 }
 
+/// 小程序支付参数，小程序下单使用JSAPI下单接口，需要支付者的openid
 #[derive(Serialize, Debug, Clone)]
 pub struct MicroParams {
     ///【商品描述】 商品描述
     pub description: String,
+    ///【通知地址】 异步接收微信支付结果通知的回调地址，不设置时使用全局配置的notify_url
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify_url: Option<String>,
     ///【商户订单号】 商户系统内部订单号，只能是数字、大小写字母_-*且在同一个商户号下唯一。
     pub out_trade_no: String,
     ///【订单金额】 订单金额信息
@@ -243,23 +602,35 @@ pub struct MicroParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub attach: Option<String>,
     ///【优惠功能】 优惠功能
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "OrderDetail::is_omitted")]
     pub detail: Option<OrderDetail>,
+    ///【订单优惠标记】 商品标记，代金券或立减优惠功能的参数。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goods_tag: Option<String>,
     ///【交易结束时间】 订单失效时间，遵循rfc3339标准格式，格式为yyyy-MM-DDTHH:mm:ss+TIMEZONE，yyyy-MM-DD表示年月日，T出现在字符串中，表示time元素的开头，HH:mm:ss表示时分秒，TIMEZONE表示时区（+08:00表示东八区时间，领先UTC8小时，即北京时间）。例如：2015-05-20T13:29:35+08:00表示，北京时间2015年5月20日13点29分35秒。
     #[serde(skip_serializing_if = "Option::is_none")]
     pub time_expire: Option<String>,
     ///【场景信息】 支付场景描述
     #[serde(skip_serializing_if = "Option::is_none")]
     pub scene_info: Option<SceneInfo>,
+    /// `payer.openid`序列化时使用的字段名，服务商模式下使用sp_openid或sub_openid
+    #[serde(skip)]
+    pub openid_kind: OpenidKind,
 }
 
 impl ParamsTrait for MicroParams {
     fn to_json(&self) -> String {
-        serde_json::to_string(self).unwrap()
+        self.openid_kind
+            .rename_payer(serde_json::to_value(self).unwrap())
+    }
+    fn validate(&self) -> Result<(), PayError> {
+        util::validate_out_trade_no(&self.out_trade_no)?;
+        util::validate_openid(&self.payer.openid)
     }
 }
 
 impl MicroParams {
+    /// `payer`可以直接传入openid，例如`"oUpF8uMuAJO_M2pxb1Q9zNjWeS6o".into()`
     pub fn new<S: AsRef<str>>(
         description: S,
         out_trade_no: S,
@@ -267,6 +638,7 @@ impl MicroParams {
         payer: PayerInfo,
     ) -> Self {
         Self {
+            notify_url: None,
             description: description.as_ref().to_string(),
             out_trade_no: out_trade_no.as_ref().to_string(),
             amount,
@@ -274,12 +646,69 @@ impl MicroParams {
             time_expire: None,
             attach: None,
             detail: None,
+            goods_tag: None,
             scene_info: None,
+            openid_kind: OpenidKind::Direct,
         }
     }
+
+    ///【通知地址】 本次请求使用的回调地址，覆盖全局配置的notify_url
+    pub fn with_notify_url<S: AsRef<str>>(mut self, notify_url: S) -> Self {
+        self.notify_url = Some(notify_url.as_ref().to_string());
+        self
+    }
+
+    ///【交易结束时间】 订单失效时间，遵循rfc3339标准格式，例如：2015-05-20T13:29:35+08:00
+    pub fn with_time_expire<S: AsRef<str>>(mut self, time_expire: S) -> Self {
+        self.time_expire = Some(time_expire.as_ref().to_string());
+        self
+    }
+
+    ///【交易结束时间】 订单失效时间，格式化为`2018-06-08T10:34:56+08:00`，不含毫秒
+    pub fn with_expire_at(mut self, expire_at: DateTime<FixedOffset>) -> Self {
+        self.time_expire = Some(util::format_rfc3339(&expire_at));
+        self
+    }
+
+    ///【交易结束时间】 从当前时间起duration后失效，使用北京时间（+08:00）
+    pub fn with_expire_in(mut self, duration: Duration) -> Self {
+        self.time_expire = Some(util::format_rfc3339(&util::beijing_after(duration)));
+        self
+    }
+
+    ///【附加数据】 附加数据，在查询API和支付通知中原样返回
+    pub fn with_attach<S: AsRef<str>>(mut self, attach: S) -> Self {
+        self.attach = Some(attach.as_ref().to_string());
+        self
+    }
+
+    ///【订单优惠标记】 商品标记，代金券或立减优惠功能的参数
+    pub fn with_goods_tag<S: AsRef<str>>(mut self, goods_tag: S) -> Self {
+        self.goods_tag = Some(goods_tag.as_ref().to_string());
+        self
+    }
+
+    ///【优惠功能】 优惠功能
+    pub fn with_detail(mut self, detail: OrderDetail) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    ///【场景信息】 支付场景描述
+    pub fn with_scene_info(mut self, scene_info: SceneInfo) -> Self {
+        self.scene_info = Some(scene_info);
+        self
+    }
+
+    /// 服务商模式下openid所属的appid，见[OpenidKind]
+    pub fn with_openid_kind(mut self, openid_kind: OpenidKind) -> Self {
+        self.openid_kind = openid_kind;
+        self
+    }
 }
 
 impl JsapiParams {
+    /// `payer`可以直接传入openid，例如`"oUpF8uMuAJO_M2pxb1Q9zNjWeS6o".into()`
     pub fn new<S: AsRef<str>>(
         description: S,
         out_trade_no: S,
@@ -287,6 +716,7 @@ impl JsapiParams {
         payer: PayerInfo,
     ) -> Self {
         Self {
+            notify_url: None,
             description: description.as_ref().to_string(),
             out_trade_no: out_trade_no.as_ref().to_string(),
             amount,
@@ -294,100 +724,274 @@ impl JsapiParams {
             time_expire: None,
             attach: None,
             detail: None,
+            goods_tag: None,
             scene_info: None,
+            openid_kind: OpenidKind::Direct,
         }
     }
-}
 
-#[derive(Serialize, Debug, Clone)]
-pub struct SettleInfo {
-    ///【是否指定分账】 是否指定分账，
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub profit_sharing: Option<bool>,
+    ///【通知地址】 本次请求使用的回调地址，覆盖全局配置的notify_url
+    pub fn with_notify_url<S: AsRef<str>>(mut self, notify_url: S) -> Self {
+        self.notify_url = Some(notify_url.as_ref().to_string());
+        self
+    }
+
+    ///【交易结束时间】 订单失效时间，遵循rfc3339标准格式，例如：2015-05-20T13:29:35+08:00
+    pub fn with_time_expire<S: AsRef<str>>(mut self, time_expire: S) -> Self {
+        self.time_expire = Some(time_expire.as_ref().to_string());
+        self
+    }
+
+    ///【交易结束时间】 订单失效时间，格式化为`2018-06-08T10:34:56+08:00`，不含毫秒
+    pub fn with_expire_at(mut self, expire_at: DateTime<FixedOffset>) -> Self {
+        self.time_expire = Some(util::format_rfc3339(&expire_at));
+        self
+    }
+
+    ///【交易结束时间】 从当前时间起duration后失效，使用北京时间（+08:00）
+    pub fn with_expire_in(mut self, duration: Duration) -> Self {
+        self.time_expire = Some(util::format_rfc3339(&util::beijing_after(duration)));
+        self
+    }
+
+    ///【附加数据】 附加数据，在查询API和支付通知中原样返回
+    pub fn with_attach<S: AsRef<str>>(mut self, attach: S) -> Self {
+        self.attach = Some(attach.as_ref().to_string());
+        self
+    }
+
+    ///【订单优惠标记】 商品标记，代金券或立减优惠功能的参数
+    pub fn with_goods_tag<S: AsRef<str>>(mut self, goods_tag: S) -> Self {
+        self.goods_tag = Some(goods_tag.as_ref().to_string());
+        self
+    }
+
+    ///【优惠功能】 优惠功能
+    pub fn with_detail(mut self, detail: OrderDetail) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    ///【场景信息】 支付场景描述
+    pub fn with_scene_info(mut self, scene_info: SceneInfo) -> Self {
+        self.scene_info = Some(scene_info);
+        self
+    }
+
+    /// 服务商模式下openid所属的appid，见[OpenidKind]
+    pub fn with_openid_kind(mut self, openid_kind: OpenidKind) -> Self {
+        self.openid_kind = openid_kind;
+        self
+    }
 }
 
-unsafe impl Send for SettleInfo {}
+/// 小程序支付参数，同[MicroParams]
+pub type MiniProgramParams = MicroParams;
 
-unsafe impl Sync for SettleInfo {}
+#[derive(Serialize, Debug, Clone)]
+pub struct CodepayPayer {
+    ///【付款码】 用户付款码，扫码枪或摄像头扫描用户微信中的付款码获得
+    pub auth_code: String,
+}
 
 #[derive(Serialize, Debug, Clone)]
-pub struct NativeParams {
-    ///【商品描述】 商品描述
-    pub description: String,
-    ///【通知地址】 异步接收微信支付结果通知的回调地址，通知URL必须为外网可访问的URL，不能携带参数。 公网域名必须为HTTPS，如果是走专线接入，使用专线NAT IP或者私有回调域名可使用HTTP
-    /// pub notify_url: String,
-    ///【商户订单号】 商户系统内部订单号，只能是数字、大小写字母_-*且在同一个商户号下唯一。
-    pub out_trade_no: String,
-    ///【订单金额】 订单金额信息
-    pub amount: AmountInfo,
-    ///【交易结束时间】 订单失效时间，遵循rfc3339标准格式，格式为yyyy-MM-DDTHH:mm:ss+TIMEZONE，yyyy-MM-DD表示年月日，T出现在字符串中，表示time元素的开头，HH:mm:ss表示时分秒，TIMEZONE表示时区（+08:00表示东八区时间，领先UTC8小时，即北京时间）。例如：2015-05-20T13:29:35+08:00表示，北京时间2015年5月20日13点29分35秒。
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub time_expire: Option<String>,
-    ///【附加数据】 附加数据，在查询API和支付通知中原样返回，可作为自定义参数使用，实际情况下只有支付完成状态才会返回该字段。
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub attach: Option<String>,
-    ///【订单优惠标记】 商品标记，代金券或立减优惠功能的参数。
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub goods_tag: Option<String>,
-    ///【电子发票入口开放标识】 传入true时，支付成功消息和支付详情页将出现开票入口。需要在微信支付商户平台或微信公众平台开通电子发票功能，传此字段才可生效。
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub support_fapiao: Option<bool>,
-    ///【场景信息】 支付场景描述
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub scene_info: Option<SceneInfo>,
-    ///【结算信息】 结算信息
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub settle_info: Option<SettleInfo>,
+pub struct CodepayStoreInfo {
+    ///【商户侧门店编号】 商户侧门店编号
+    pub out_id: String,
 }
 
-impl ParamsTrait for NativeParams {
-    fn to_json(&self) -> String {
-        serde_json::to_string(self).unwrap()
-    }
+#[derive(Serialize, Debug, Clone)]
+pub struct CodepaySceneInfo {
+    ///【商户门店信息】 商户门店信息
+    pub store_info: CodepayStoreInfo,
+    ///【商户端设备IP】 商户端设备IP
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_ip: Option<String>,
 }
 
+/// 付款码支付（商户扫用户付款码）参数，同步返回支付结果，没有prepay_id，也不需要notify_url
 #[derive(Serialize, Debug, Clone)]
-pub struct AppParams {
+pub struct CodepayParams {
     ///【商品描述】 商品描述
     pub description: String,
-    ///【通知地址】 异步接收微信支付结果通知的回调地址，通知URL必须为外网可访问的URL，不能携带参数。 公网域名必须为HTTPS，如果是走专线接入，使用专线NAT IP或者私有回调域名可使用HTTP
-    /// pub notify_url: String,
     ///【商户订单号】 商户系统内部订单号，只能是数字、大小写字母_-*且在同一个商户号下唯一。
     pub out_trade_no: String,
     ///【订单金额】 订单金额信息
     pub amount: AmountInfo,
-    ///【交易结束时间】 订单失效时间，遵循rfc3339标准格式，格式为yyyy-MM-DDTHH:mm:ss+TIMEZONE，yyyy-MM-DD表示年月日，T出现在字符串中，表示time元素的开头，HH:mm:ss表示时分秒，TIMEZONE表示时区（+08:00表示东八区时间，领先UTC8小时，即北京时间）。例如：2015-05-20T13:29:35+08:00表示，北京时间2015年5月20日13点29分35秒。
+    ///【支付者】 用户付款码
+    pub payer: CodepayPayer,
+    ///【场景信息】 支付场景描述
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub time_expire: Option<String>,
-    ///【附加数据】 附加数据，在查询API和支付通知中原样返回，可作为自定义参数使用，实际情况下只有支付完成状态才会返回该字段。
+    pub scene_info: Option<CodepaySceneInfo>,
+    ///【附加数据】 附加数据，在查询API和支付通知中原样返回
     #[serde(skip_serializing_if = "Option::is_none")]
     pub attach: Option<String>,
     ///【订单优惠标记】 商品标记，代金券或立减优惠功能的参数。
     #[serde(skip_serializing_if = "Option::is_none")]
     pub goods_tag: Option<String>,
-    ///【电子发票入口开放标识】 传入true时，支付成功消息和支付详情页将出现开票入口。需要在微信支付商户平台或微信公众平台开通电子发票功能，传此字段才可生效。
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub support_fapiao: Option<bool>,
     ///【优惠功能】 优惠功能
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "OrderDetail::is_omitted")]
     pub detail: Option<OrderDetail>,
-    ///【场景信息】 支付场景描述
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub scene_info: Option<SceneInfo>,
     ///【结算信息】 结算信息
     #[serde(skip_serializing_if = "Option::is_none")]
     pub settle_info: Option<SettleInfo>,
 }
 
-impl ParamsTrait for AppParams {
+impl ParamsTrait for CodepayParams {
     fn to_json(&self) -> String {
         serde_json::to_string(self).unwrap()
     }
+    fn validate(&self) -> Result<(), PayError> {
+        util::validate_out_trade_no(&self.out_trade_no)?;
+        util::validate_auth_code(&self.payer.auth_code)
+    }
+    fn inject_fields(&self) -> InjectFields {
+        InjectFields {
+            appid: true,
+            mchid: true,
+            notify_url: false,
+        }
+    }
 }
 
-impl AppParams {
-    pub fn new<S: AsRef<str>>(description: S, out_trade_no: S, amount: AmountInfo) -> Self {
+impl CodepayParams {
+    pub fn new<S: AsRef<str>>(
+        description: S,
+        out_trade_no: S,
+        amount: AmountInfo,
+        auth_code: S,
+    ) -> Self {
         Self {
+            description: description.as_ref().to_string(),
+            out_trade_no: out_trade_no.as_ref().to_string(),
+            amount,
+            payer: CodepayPayer {
+                auth_code: auth_code.as_ref().to_string(),
+            },
+            scene_info: None,
+            attach: None,
+            goods_tag: None,
+            detail: None,
+            settle_info: None,
+        }
+    }
+
+    ///【场景信息】 门店编号及设备IP
+    pub fn with_scene_info(mut self, scene_info: CodepaySceneInfo) -> Self {
+        self.scene_info = Some(scene_info);
+        self
+    }
+
+    ///【优惠功能】 优惠功能
+    pub fn with_detail(mut self, detail: OrderDetail) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    ///【附加数据】 附加数据，在查询API和支付通知中原样返回
+    pub fn with_attach<S: AsRef<str>>(mut self, attach: S) -> Self {
+        self.attach = Some(attach.as_ref().to_string());
+        self
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SettleInfo {
+    ///【是否指定分账】 是否指定分账，
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profit_sharing: Option<bool>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct NativeParams {
+    ///【商品描述】 商品描述
+    pub description: String,
+    ///【通知地址】 异步接收微信支付结果通知的回调地址，通知URL必须为外网可访问的URL，不能携带参数，不设置时使用全局配置的notify_url
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify_url: Option<String>,
+    ///【商户订单号】 商户系统内部订单号，只能是数字、大小写字母_-*且在同一个商户号下唯一。
+    pub out_trade_no: String,
+    ///【订单金额】 订单金额信息
+    pub amount: AmountInfo,
+    ///【交易结束时间】 订单失效时间，遵循rfc3339标准格式，格式为yyyy-MM-DDTHH:mm:ss+TIMEZONE，yyyy-MM-DD表示年月日，T出现在字符串中，表示time元素的开头，HH:mm:ss表示时分秒，TIMEZONE表示时区（+08:00表示东八区时间，领先UTC8小时，即北京时间）。例如：2015-05-20T13:29:35+08:00表示，北京时间2015年5月20日13点29分35秒。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_expire: Option<String>,
+    ///【附加数据】 附加数据，在查询API和支付通知中原样返回，可作为自定义参数使用，实际情况下只有支付完成状态才会返回该字段。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attach: Option<String>,
+    ///【订单优惠标记】 商品标记，代金券或立减优惠功能的参数。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goods_tag: Option<String>,
+    ///【电子发票入口开放标识】 传入true时，支付成功消息和支付详情页将出现开票入口。需要在微信支付商户平台或微信公众平台开通电子发票功能，传此字段才可生效。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub support_fapiao: Option<bool>,
+    ///【优惠功能】 优惠功能
+    #[serde(skip_serializing_if = "OrderDetail::is_omitted")]
+    pub detail: Option<OrderDetail>,
+    ///【场景信息】 支付场景描述
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scene_info: Option<SceneInfo>,
+    ///【结算信息】 结算信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settle_info: Option<SettleInfo>,
+}
+
+impl ParamsTrait for NativeParams {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    fn validate(&self) -> Result<(), PayError> {
+        util::validate_out_trade_no(&self.out_trade_no)
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AppParams {
+    ///【商品描述】 商品描述
+    pub description: String,
+    ///【通知地址】 异步接收微信支付结果通知的回调地址，通知URL必须为外网可访问的URL，不能携带参数，不设置时使用全局配置的notify_url
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify_url: Option<String>,
+    ///【商户订单号】 商户系统内部订单号，只能是数字、大小写字母_-*且在同一个商户号下唯一。
+    pub out_trade_no: String,
+    ///【订单金额】 订单金额信息
+    pub amount: AmountInfo,
+    ///【交易结束时间】 订单失效时间，遵循rfc3339标准格式，格式为yyyy-MM-DDTHH:mm:ss+TIMEZONE，yyyy-MM-DD表示年月日，T出现在字符串中，表示time元素的开头，HH:mm:ss表示时分秒，TIMEZONE表示时区（+08:00表示东八区时间，领先UTC8小时，即北京时间）。例如：2015-05-20T13:29:35+08:00表示，北京时间2015年5月20日13点29分35秒。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_expire: Option<String>,
+    ///【附加数据】 附加数据，在查询API和支付通知中原样返回，可作为自定义参数使用，实际情况下只有支付完成状态才会返回该字段。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attach: Option<String>,
+    ///【订单优惠标记】 商品标记，代金券或立减优惠功能的参数。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goods_tag: Option<String>,
+    ///【电子发票入口开放标识】 传入true时，支付成功消息和支付详情页将出现开票入口。需要在微信支付商户平台或微信公众平台开通电子发票功能，传此字段才可生效。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub support_fapiao: Option<bool>,
+    ///【优惠功能】 优惠功能
+    #[serde(skip_serializing_if = "OrderDetail::is_omitted")]
+    pub detail: Option<OrderDetail>,
+    ///【场景信息】 支付场景描述
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scene_info: Option<SceneInfo>,
+    ///【结算信息】 结算信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settle_info: Option<SettleInfo>,
+}
+
+impl ParamsTrait for AppParams {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    fn validate(&self) -> Result<(), PayError> {
+        util::validate_out_trade_no(&self.out_trade_no)
+    }
+}
+
+impl AppParams {
+    pub fn new<S: AsRef<str>>(description: S, out_trade_no: S, amount: AmountInfo) -> Self {
+        Self {
+            notify_url: None,
             description: description.as_ref().to_string(),
             out_trade_no: out_trade_no.as_ref().to_string(),
             amount,
@@ -400,14 +1004,63 @@ impl AppParams {
             settle_info: None,
         }
     }
+
+    ///【通知地址】 本次请求使用的回调地址，覆盖全局配置的notify_url
+    pub fn with_notify_url<S: AsRef<str>>(mut self, notify_url: S) -> Self {
+        self.notify_url = Some(notify_url.as_ref().to_string());
+        self
+    }
+
+    ///【交易结束时间】 订单失效时间，遵循rfc3339标准格式，例如：2015-05-20T13:29:35+08:00
+    pub fn with_time_expire<S: AsRef<str>>(mut self, time_expire: S) -> Self {
+        self.time_expire = Some(time_expire.as_ref().to_string());
+        self
+    }
+
+    ///【交易结束时间】 订单失效时间，格式化为`2018-06-08T10:34:56+08:00`，不含毫秒
+    pub fn with_expire_at(mut self, expire_at: DateTime<FixedOffset>) -> Self {
+        self.time_expire = Some(util::format_rfc3339(&expire_at));
+        self
+    }
+
+    ///【交易结束时间】 从当前时间起duration后失效，使用北京时间（+08:00）
+    pub fn with_expire_in(mut self, duration: Duration) -> Self {
+        self.time_expire = Some(util::format_rfc3339(&util::beijing_after(duration)));
+        self
+    }
+
+    ///【附加数据】 附加数据，在查询API和支付通知中原样返回
+    pub fn with_attach<S: AsRef<str>>(mut self, attach: S) -> Self {
+        self.attach = Some(attach.as_ref().to_string());
+        self
+    }
+
+    ///【订单优惠标记】 商品标记，代金券或立减优惠功能的参数
+    pub fn with_goods_tag<S: AsRef<str>>(mut self, goods_tag: S) -> Self {
+        self.goods_tag = Some(goods_tag.as_ref().to_string());
+        self
+    }
+
+    ///【优惠功能】 优惠功能
+    pub fn with_detail(mut self, detail: OrderDetail) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    ///【场景信息】 支付场景描述
+    pub fn with_scene_info(mut self, scene_info: SceneInfo) -> Self {
+        self.scene_info = Some(scene_info);
+        self
+    }
 }
 
 #[derive(Serialize, Debug, Clone)]
 pub struct H5Params {
     ///【商品描述】 商品描述
     pub description: String,
-    ///【通知地址】 异步接收微信支付结果通知的回调地址，通知URL必须为外网可访问的URL，不能携带参数。 公网域名必须为HTTPS，如果是走专线接入，使用专线NAT IP或者私有回调域名可使用HTTP
-    /// pub notify_url: String,
+    ///【通知地址】 异步接收微信支付结果通知的回调地址，通知URL必须为外网可访问的URL，不能携带参数，不设置时使用全局配置的notify_url
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify_url: Option<String>,
     ///【商户订单号】 商户系统内部订单号，只能是数字、大小写字母_-*且在同一个商户号下唯一。
     pub out_trade_no: String,
     ///【订单金额】 订单金额信息
@@ -424,8 +1077,11 @@ pub struct H5Params {
     ///【电子发票入口开放标识】 传入true时，支付成功消息和支付详情页将出现开票入口。需要在微信支付商户平台或微信公众平台开通电子发票功能，传此字段才可生效。
     #[serde(skip_serializing_if = "Option::is_none")]
     pub support_fapiao: Option<bool>,
-    ///【场景信息】 支付场景描述
-    pub scene_info: H5SceneInfo,
+    ///【优惠功能】 优惠功能
+    #[serde(skip_serializing_if = "OrderDetail::is_omitted")]
+    pub detail: Option<OrderDetail>,
+    ///【场景信息】 支付场景描述，H5支付必须包含payer_client_ip和h5_info
+    pub scene_info: SceneInfo,
     ///【结算信息】 结算信息
     #[serde(skip_serializing_if = "Option::is_none")]
     pub settle_info: Option<SettleInfo>,
@@ -435,16 +1091,22 @@ impl ParamsTrait for H5Params {
     fn to_json(&self) -> String {
         serde_json::to_string(self).unwrap()
     }
+    fn validate(&self) -> Result<(), PayError> {
+        util::validate_out_trade_no(&self.out_trade_no)
+    }
 }
 
 impl H5Params {
-    pub fn new<S: AsRef<str>>(
+    /// scene_info可以直接传用户终端IP，也可以传[SceneInfo]或[H5SceneInfo]，
+    /// 未设置h5_info时默认使用Wap场景
+    pub fn new<S: AsRef<str>, T: Into<SceneInfo>>(
         description: S,
         out_trade_no: S,
         amount: AmountInfo,
-        scene_info: H5SceneInfo,
+        scene_info: T,
     ) -> Self {
         Self {
+            notify_url: None,
             description: description.as_ref().to_string(),
             out_trade_no: out_trade_no.as_ref().to_string(),
             amount,
@@ -452,15 +1114,72 @@ impl H5Params {
             attach: None,
             goods_tag: None,
             support_fapiao: None,
-            scene_info,
+            detail: None,
+            scene_info: Self::h5_scene_info(scene_info.into()),
             settle_info: None,
         }
     }
+
+    ///【通知地址】 本次请求使用的回调地址，覆盖全局配置的notify_url
+    pub fn with_notify_url<S: AsRef<str>>(mut self, notify_url: S) -> Self {
+        self.notify_url = Some(notify_url.as_ref().to_string());
+        self
+    }
+
+    ///【交易结束时间】 订单失效时间，遵循rfc3339标准格式，例如：2015-05-20T13:29:35+08:00
+    pub fn with_time_expire<S: AsRef<str>>(mut self, time_expire: S) -> Self {
+        self.time_expire = Some(time_expire.as_ref().to_string());
+        self
+    }
+
+    ///【交易结束时间】 订单失效时间，格式化为`2018-06-08T10:34:56+08:00`，不含毫秒
+    pub fn with_expire_at(mut self, expire_at: DateTime<FixedOffset>) -> Self {
+        self.time_expire = Some(util::format_rfc3339(&expire_at));
+        self
+    }
+
+    ///【交易结束时间】 从当前时间起duration后失效，使用北京时间（+08:00）
+    pub fn with_expire_in(mut self, duration: Duration) -> Self {
+        self.time_expire = Some(util::format_rfc3339(&util::beijing_after(duration)));
+        self
+    }
+
+    ///【附加数据】 附加数据，在查询API和支付通知中原样返回
+    pub fn with_attach<S: AsRef<str>>(mut self, attach: S) -> Self {
+        self.attach = Some(attach.as_ref().to_string());
+        self
+    }
+
+    ///【订单优惠标记】 商品标记，代金券或立减优惠功能的参数
+    pub fn with_goods_tag<S: AsRef<str>>(mut self, goods_tag: S) -> Self {
+        self.goods_tag = Some(goods_tag.as_ref().to_string());
+        self
+    }
+
+    ///【优惠功能】 优惠功能
+    pub fn with_detail(mut self, detail: OrderDetail) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    ///【场景信息】 支付场景描述
+    pub fn with_scene_info<T: Into<SceneInfo>>(mut self, scene_info: T) -> Self {
+        self.scene_info = Self::h5_scene_info(scene_info.into());
+        self
+    }
+
+    fn h5_scene_info(mut scene_info: SceneInfo) -> SceneInfo {
+        if scene_info.h5_info.is_none() {
+            scene_info.h5_info = Some(H5Type::Wap.into());
+        }
+        scene_info
+    }
 }
 
 impl NativeParams {
     pub fn new<S: AsRef<str>>(description: S, out_trade_no: S, amount: AmountInfo) -> Self {
         Self {
+            notify_url: None,
             description: description.as_ref().to_string(),
             out_trade_no: out_trade_no.as_ref().to_string(),
             amount,
@@ -468,19 +1187,60 @@ impl NativeParams {
             attach: None,
             goods_tag: None,
             support_fapiao: None,
+            detail: None,
             scene_info: None,
             settle_info: None,
         }
     }
-}
 
-unsafe impl Send for NativeParams {}
+    ///【通知地址】 本次请求使用的回调地址，覆盖全局配置的notify_url
+    pub fn with_notify_url<S: AsRef<str>>(mut self, notify_url: S) -> Self {
+        self.notify_url = Some(notify_url.as_ref().to_string());
+        self
+    }
+
+    ///【交易结束时间】 订单失效时间，遵循rfc3339标准格式，例如：2015-05-20T13:29:35+08:00
+    pub fn with_time_expire<S: AsRef<str>>(mut self, time_expire: S) -> Self {
+        self.time_expire = Some(time_expire.as_ref().to_string());
+        self
+    }
+
+    ///【交易结束时间】 订单失效时间，格式化为`2018-06-08T10:34:56+08:00`，不含毫秒
+    pub fn with_expire_at(mut self, expire_at: DateTime<FixedOffset>) -> Self {
+        self.time_expire = Some(util::format_rfc3339(&expire_at));
+        self
+    }
+
+    ///【交易结束时间】 从当前时间起duration后失效，使用北京时间（+08:00）
+    pub fn with_expire_in(mut self, duration: Duration) -> Self {
+        self.time_expire = Some(util::format_rfc3339(&util::beijing_after(duration)));
+        self
+    }
+
+    ///【附加数据】 附加数据，在查询API和支付通知中原样返回
+    pub fn with_attach<S: AsRef<str>>(mut self, attach: S) -> Self {
+        self.attach = Some(attach.as_ref().to_string());
+        self
+    }
 
-unsafe impl Sync for NativeParams {}
+    ///【订单优惠标记】 商品标记，代金券或立减优惠功能的参数
+    pub fn with_goods_tag<S: AsRef<str>>(mut self, goods_tag: S) -> Self {
+        self.goods_tag = Some(goods_tag.as_ref().to_string());
+        self
+    }
 
-unsafe impl Send for JsapiParams {}
+    ///【优惠功能】 优惠功能
+    pub fn with_detail(mut self, detail: OrderDetail) -> Self {
+        self.detail = Some(detail);
+        self
+    }
 
-unsafe impl Sync for JsapiParams {}
+    ///【场景信息】 支付场景描述
+    pub fn with_scene_info(mut self, scene_info: SceneInfo) -> Self {
+        self.scene_info = Some(scene_info);
+        self
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct WechatPayNotifySource {
@@ -491,28 +1251,1876 @@ pub struct WechatPayNotifySource {
     pub nonce: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct WechatPayNotify {
-    pub id: String,
-    pub create_time: String,
-    pub event_type: String,
-    pub resource_type: String,
-    pub resource: WechatPayNotifySource,
-    pub summary: String,
+#[derive(Serialize, Debug, Clone)]
+pub struct RefundAmount {
+    ///【退款金额】 退款金额，单位为分，只能为整数，不能超过原订单支付金额。
+    pub refund: Fen,
+    ///【原订单金额】 原支付交易的订单总金额，单位为分，只能为整数。
+    pub total: Fen,
+    ///【退款币种】 符合ISO 4217标准的三位字母代码，目前只支持人民币：CNY。
+    pub currency: Currency,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct WechatPayDecodeData {
-    pub mchid: String,
-    pub appid: String,
-    pub out_trade_no: String,
-    pub transaction_id: String,
-    pub trade_type: String,
-    pub trade_state: String,
-    pub trade_state_desc: String,
-    pub bank_type: String,
-    pub attach: String,
-    pub success_time: String,
-    pub payer: PayerInfo,
-    pub amount: AmountInfo,
+impl RefundAmount {
+    pub fn new(refund: impl Into<Fen>, total: impl Into<Fen>) -> Self {
+        Self {
+            refund: refund.into(),
+            total: total.into(),
+            currency: Currency::CNY,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RefundParams {
+    ///【微信支付订单号】 原支付交易对应的微信订单号，与out_trade_no二选一
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_id: Option<String>,
+    ///【商户订单号】 原支付交易对应的商户订单号，与transaction_id二选一
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub out_trade_no: Option<String>,
+    ///【商户退款单号】 商户系统内部的退款单号，商户系统内部唯一，只能是数字、大小写字母_-|*@ ，同一退款单号多次请求只退一笔。
+    pub out_refund_no: String,
+    ///【退款原因】 若商户传入，会在下发给用户的退款消息中体现退款原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    ///【退款结果回调url】 异步接收微信支付退款结果通知的回调地址，通知url必须为外网可访问的url，不能携带参数。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify_url: Option<String>,
+    ///【金额信息】 订单金额信息
+    pub amount: RefundAmount,
+}
+
+impl ParamsTrait for RefundParams {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    fn validate(&self) -> Result<(), PayError> {
+        if let Some(out_trade_no) = &self.out_trade_no {
+            util::validate_out_trade_no(out_trade_no)?;
+        }
+        util::validate_out_refund_no(&self.out_refund_no)
+    }
+    fn inject_fields(&self) -> InjectFields {
+        InjectFields::NONE
+    }
+}
+
+impl RefundParams {
+    /// 按商户订单号退款
+    pub fn new<S: AsRef<str>>(out_trade_no: S, out_refund_no: S, amount: RefundAmount) -> Self {
+        Self {
+            transaction_id: None,
+            out_trade_no: Some(out_trade_no.as_ref().to_string()),
+            out_refund_no: out_refund_no.as_ref().to_string(),
+            reason: None,
+            notify_url: None,
+            amount,
+        }
+    }
+
+    /// 按微信支付订单号退款
+    pub fn with_transaction_id<S: AsRef<str>>(
+        transaction_id: S,
+        out_refund_no: S,
+        amount: RefundAmount,
+    ) -> Self {
+        Self {
+            transaction_id: Some(transaction_id.as_ref().to_string()),
+            out_trade_no: None,
+            out_refund_no: out_refund_no.as_ref().to_string(),
+            reason: None,
+            notify_url: None,
+            amount,
+        }
+    }
+}
+
+/// 分账接收方类型
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReceiverType {
+    /// 商户号
+    MerchantId,
+    /// 个人openid（由父商户APPID转换得到）
+    PersonalOpenid,
+    /// 个人sub_openid（由子商户APPID转换得到），服务商模式使用
+    PersonalSubOpenid,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ProfitSharingReceiver {
+    ///【分账接收方类型】 MERCHANT_ID：商户号 PERSONAL_OPENID：个人openid
+    #[serde(rename = "type")]
+    pub receiver_type: ReceiverType,
+    ///【分账接收方账号】 类型是MERCHANT_ID时，是商户号；类型是PERSONAL_OPENID时，是个人openid
+    pub account: String,
+    ///【分账个人接收方姓名】 在接收方类型为个人的时可选填，若有值，会检查与name是否实名匹配，需使用微信支付平台证书加密
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    ///【分账金额】 分账金额，单位为分，只能为整数，不能超过原订单支付金额及最大分账比例金额
+    pub amount: Fen,
+    ///【分账描述】 分账的原因描述，分账账单中需要体现
+    pub description: String,
+}
+
+impl ProfitSharingReceiver {
+    pub fn new<S: AsRef<str>>(
+        receiver_type: ReceiverType,
+        account: S,
+        amount: impl Into<Fen>,
+        description: S,
+    ) -> Self {
+        Self {
+            receiver_type,
+            account: account.as_ref().to_string(),
+            name: None,
+            amount: amount.into(),
+            description: description.as_ref().to_string(),
+        }
+    }
+}
+
+/// 代金券批次发放规则
+#[derive(Serialize, Debug, Clone)]
+pub struct CouponStockUseRule {
+    ///【发放总上限】 最大发券数
+    pub max_coupons: u64,
+    ///【总预算】 最大使用预算，单位为分，为面额×发放总上限
+    pub max_amount: Fen,
+    ///【单天预算发放上限】 单天最大使用预算，单位为分
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_amount_by_day: Option<Fen>,
+    ///【单个用户可领个数】 单个用户最多可领个数，最多60张
+    pub max_coupons_per_user: u32,
+    ///【是否开启自然人限制】 开启后同一个自然人（如同一身份证）只能领取一次
+    pub natural_person_limit: bool,
+    ///【是否开启防刷拦截】 开启后会拦截刷券的风险用户
+    pub prevent_api_abuse: bool,
+}
+
+/// 固定面额满减券
+#[derive(Serialize, Debug, Clone)]
+pub struct FixedNormalCoupon {
+    ///【面额】 面额，单位为分
+    pub coupon_amount: Fen,
+    ///【门槛】 使用券金额门槛，单位为分
+    pub transaction_minimum: Fen,
+}
+
+/// 代金券核销规则
+#[derive(Serialize, Debug, Clone)]
+pub struct CouponUseRule {
+    ///【固定面额满减券使用规则】 固定面额满减券使用规则
+    pub fixed_normal_coupon: FixedNormalCoupon,
+    ///【订单优惠标记】 订单优惠标记，下单时传入goods_tag才能使用该券
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goods_tag: Option<Vec<String>>,
+    ///【可核销商户号】 可以核销代金券的商户号，至少包含一个
+    pub available_merchants: Vec<String>,
+}
+
+/// 创建代金券批次参数
+#[derive(Serialize, Debug, Clone)]
+pub struct CouponStockParams {
+    ///【批次名称】 批次名称，最多9个中文汉字
+    pub stock_name: String,
+    ///【批次备注】 仅制券商户可见，最多20个中文汉字
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    ///【归属商户号】 批次归属商户号，不填时使用当前商户号
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub belong_merchant: Option<String>,
+    ///【可用时间-开始时间】 遵循rfc3339标准格式，例如：2015-05-20T13:29:35+08:00
+    pub available_begin_time: String,
+    ///【可用时间-结束时间】 遵循rfc3339标准格式，例如：2015-05-20T13:29:35+08:00
+    pub available_end_time: String,
+    ///【发放规则】 批次发放规则
+    pub stock_use_rule: CouponStockUseRule,
+    ///【核销规则】 代金券核销规则
+    pub coupon_use_rule: CouponUseRule,
+    ///【营销经费】 false：预充值，true：免充值
+    pub no_cash: bool,
+    ///【批次类型】 固定填写NORMAL
+    pub stock_type: String,
+    ///【商户单据号】 商户创建批次凭据号，商户侧需保持唯一性
+    pub out_request_no: String,
+}
+
+impl ParamsTrait for CouponStockParams {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    fn inject_fields(&self) -> InjectFields {
+        InjectFields::NONE
+    }
+}
+
+impl CouponStockParams {
+    pub fn new<S: AsRef<str>>(
+        stock_name: S,
+        available_begin_time: S,
+        available_end_time: S,
+        stock_use_rule: CouponStockUseRule,
+        coupon_use_rule: CouponUseRule,
+        out_request_no: S,
+    ) -> Self {
+        Self {
+            stock_name: stock_name.as_ref().to_string(),
+            comment: None,
+            belong_merchant: None,
+            available_begin_time: available_begin_time.as_ref().to_string(),
+            available_end_time: available_end_time.as_ref().to_string(),
+            stock_use_rule,
+            coupon_use_rule,
+            no_cash: false,
+            stock_type: "NORMAL".to_string(),
+            out_request_no: out_request_no.as_ref().to_string(),
+        }
+    }
+
+    ///【批次备注】 仅制券商户可见
+    pub fn with_comment<S: AsRef<str>>(mut self, comment: S) -> Self {
+        self.comment = Some(comment.as_ref().to_string());
+        self
+    }
+}
+
+/// 回复用户投诉参数
+#[derive(Serialize, Debug, Clone)]
+pub struct ComplaintResponseParams {
+    ///【被诉商户号】 投诉单对应的被诉商户号
+    pub complainted_mchid: String,
+    ///【回复内容】 具体的投诉处理方案，限制200个字符以内
+    pub response_content: String,
+    ///【回复图片】 图片上传接口返回的media_id，最多上传4张
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_images: Option<Vec<String>>,
+    ///【跳转链接】 商户可在回复中附加跳转链接，引导用户跳转至商户客诉处理页面
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jump_url: Option<String>,
+    ///【跳转链接文案】 实际展示给用户的文案，附加跳转链接时必填
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jump_url_text: Option<String>,
+}
+
+impl ParamsTrait for ComplaintResponseParams {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    fn inject_fields(&self) -> InjectFields {
+        InjectFields::NONE
+    }
+}
+
+impl ComplaintResponseParams {
+    pub fn new<S: AsRef<str>>(complainted_mchid: S, response_content: S) -> Self {
+        Self {
+            complainted_mchid: complainted_mchid.as_ref().to_string(),
+            response_content: response_content.as_ref().to_string(),
+            response_images: None,
+            jump_url: None,
+            jump_url_text: None,
+        }
+    }
+
+    ///【回复图片】 图片上传接口返回的media_id
+    pub fn with_response_images(mut self, response_images: Vec<String>) -> Self {
+        self.response_images = Some(response_images);
+        self
+    }
+
+    ///【跳转链接】 跳转链接及展示给用户的文案
+    pub fn with_jump_url<S: AsRef<str>>(mut self, jump_url: S, jump_url_text: S) -> Self {
+        self.jump_url = Some(jump_url.as_ref().to_string());
+        self.jump_url_text = Some(jump_url_text.as_ref().to_string());
+        self
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ProfitSharingParams {
+    ///【微信订单号】 微信支付订单号
+    pub transaction_id: String,
+    ///【商户分账单号】 商户系统内部的分账单号，在商户系统内部唯一，同一分账单号多次请求等同一次。只能是数字、大小写字母_-|*@
+    pub out_order_no: String,
+    ///【分账接收方列表】 分账接收方列表，可以设置出资商户作为分账接受方，最多可有50个分账接收方
+    pub receivers: Vec<ProfitSharingReceiver>,
+    ///【是否解冻剩余未分资金】 true：是，解冻剩余未分资金 false：否，不解冻剩余未分资金
+    pub unfreeze_unsplit: bool,
+}
+
+impl ParamsTrait for ProfitSharingParams {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    fn inject_fields(&self) -> InjectFields {
+        InjectFields::NONE
+    }
+}
+
+impl ProfitSharingParams {
+    pub fn new<S: AsRef<str>>(
+        transaction_id: S,
+        out_order_no: S,
+        receivers: Vec<ProfitSharingReceiver>,
+        unfreeze_unsplit: bool,
+    ) -> Self {
+        Self {
+            transaction_id: transaction_id.as_ref().to_string(),
+            out_order_no: out_order_no.as_ref().to_string(),
+            receivers,
+            unfreeze_unsplit,
+        }
+    }
+}
+
+/// 分账接收方与分账方的关系类型
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RelationType {
+    /// 服务商
+    ServiceProvider,
+    /// 门店
+    Store,
+    /// 员工
+    Staff,
+    /// 店主
+    StoreOwner,
+    /// 合作伙伴
+    Partner,
+    /// 总部
+    Headquarter,
+    /// 品牌方
+    Brand,
+    /// 分销商
+    Distributor,
+    /// 用户
+    User,
+    /// 供应商
+    Supplier,
+    /// 自定义，需要填写custom_relation
+    Custom,
+}
+
+/// 添加分账接收方参数
+#[derive(Serialize, Debug, Clone)]
+pub struct ProfitSharingReceiverAddParams {
+    ///【分账接收方类型】 MERCHANT_ID：商户号 PERSONAL_OPENID：个人openid PERSONAL_SUB_OPENID：个人sub_openid
+    #[serde(rename = "type")]
+    pub receiver_type: ReceiverType,
+    ///【分账接收方账号】 类型是MERCHANT_ID时，是商户号；类型是PERSONAL_OPENID时，是个人openid
+    pub account: String,
+    ///【分账个人接收方姓名】 接收方类型为MERCHANT_ID时必填商户全称，个人时选填，请求时会自动使用微信支付平台证书加密
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    ///【与分账方的关系类型】 子商户与接收方的关系
+    pub relation_type: RelationType,
+    ///【自定义的分账关系】 relation_type为CUSTOM时必填，不超过10个字
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_relation: Option<String>,
+}
+
+impl ParamsTrait for ProfitSharingReceiverAddParams {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    fn inject_fields(&self) -> InjectFields {
+        InjectFields::NONE
+    }
+}
+
+impl ProfitSharingReceiverAddParams {
+    pub fn new<S: AsRef<str>>(
+        receiver_type: ReceiverType,
+        account: S,
+        relation_type: RelationType,
+    ) -> Self {
+        Self {
+            receiver_type,
+            account: account.as_ref().to_string(),
+            name: None,
+            relation_type,
+            custom_relation: None,
+        }
+    }
+
+    ///【分账接收方全称或姓名】 请求时会自动使用微信支付平台证书加密
+    pub fn with_name<S: AsRef<str>>(mut self, name: S) -> Self {
+        self.name = Some(name.as_ref().to_string());
+        self
+    }
+
+    ///【自定义的分账关系】 relation_type为CUSTOM时使用
+    pub fn with_custom_relation<S: AsRef<str>>(mut self, custom_relation: S) -> Self {
+        self.relation_type = RelationType::Custom;
+        self.custom_relation = Some(custom_relation.as_ref().to_string());
+        self
+    }
+}
+
+/// 删除分账接收方参数
+#[derive(Serialize, Debug, Clone)]
+pub struct ProfitSharingReceiverDeleteParams {
+    ///【分账接收方类型】 MERCHANT_ID：商户号 PERSONAL_OPENID：个人openid PERSONAL_SUB_OPENID：个人sub_openid
+    #[serde(rename = "type")]
+    pub receiver_type: ReceiverType,
+    ///【分账接收方账号】 类型是MERCHANT_ID时，是商户号；类型是PERSONAL_OPENID时，是个人openid
+    pub account: String,
+}
+
+impl ParamsTrait for ProfitSharingReceiverDeleteParams {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    fn inject_fields(&self) -> InjectFields {
+        InjectFields::NONE
+    }
+}
+
+impl ProfitSharingReceiverDeleteParams {
+    pub fn new<S: AsRef<str>>(receiver_type: ReceiverType, account: S) -> Self {
+        Self {
+            receiver_type,
+            account: account.as_ref().to_string(),
+        }
+    }
+}
+
+/// 解冻剩余资金参数，不需要分账的订单可以直接解冻，把剩余资金全部解冻给本商户
+#[derive(Serialize, Debug, Clone)]
+pub struct ProfitSharingUnfreezeParams {
+    ///【微信订单号】 微信支付订单号
+    pub transaction_id: String,
+    ///【商户分账单号】 商户系统内部的分账单号，在商户系统内部唯一。只能是数字、大小写字母_-|*@
+    pub out_order_no: String,
+    ///【分账描述】 分账的原因描述，分账账单中需要体现
+    pub description: String,
+}
+
+impl ParamsTrait for ProfitSharingUnfreezeParams {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    fn inject_fields(&self) -> InjectFields {
+        InjectFields::NONE
+    }
+}
+
+impl ProfitSharingUnfreezeParams {
+    pub fn new<S: AsRef<str>>(transaction_id: S, out_order_no: S, description: S) -> Self {
+        Self {
+            transaction_id: transaction_id.as_ref().to_string(),
+            out_order_no: out_order_no.as_ref().to_string(),
+            description: description.as_ref().to_string(),
+        }
+    }
+}
+
+/// 分账回退参数，order_id和out_order_no二选一
+#[derive(Serialize, Debug, Clone)]
+pub struct ProfitSharingReturnParams {
+    ///【微信分账单号】 微信分账单号，微信系统返回的唯一标识
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_id: Option<String>,
+    ///【商户分账单号】 商户系统内部的分账单号
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub out_order_no: Option<String>,
+    ///【商户回退单号】 商户系统内部的回退单号，商户系统内部唯一，同一回退单号多次请求等同一次。只能是数字、大小写字母_-|*@
+    pub out_return_no: String,
+    ///【回退商户号】 分账回退的出资商户，只能对原分账请求中成功分给商户接收方进行回退
+    pub return_mchid: String,
+    ///【回退金额】 需要从分账接收方回退的金额，单位为分，只能为整数，不能超过原始分账单分出给该接收方的金额
+    pub amount: Fen,
+    ///【回退描述】 分账回退的原因描述
+    pub description: String,
+}
+
+impl ParamsTrait for ProfitSharingReturnParams {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    fn inject_fields(&self) -> InjectFields {
+        InjectFields::NONE
+    }
+    fn validate(&self) -> Result<(), PayError> {
+        if self.order_id.is_none() && self.out_order_no.is_none() {
+            return Err(PayError::InvalidParam {
+                field: "out_order_no",
+                reason: "order_id or out_order_no is required".to_string(),
+            });
+        }
+        util::validate_out_return_no(&self.out_return_no)
+    }
+}
+
+impl ProfitSharingReturnParams {
+    pub fn new<S: AsRef<str>>(
+        out_order_no: S,
+        out_return_no: S,
+        return_mchid: S,
+        amount: impl Into<Fen>,
+        description: S,
+    ) -> Self {
+        Self {
+            order_id: None,
+            out_order_no: Some(out_order_no.as_ref().to_string()),
+            out_return_no: out_return_no.as_ref().to_string(),
+            return_mchid: return_mchid.as_ref().to_string(),
+            amount: amount.into(),
+            description: description.as_ref().to_string(),
+        }
+    }
+
+    ///【微信分账单号】 使用微信分账单号代替商户分账单号
+    pub fn with_order_id<S: AsRef<str>>(mut self, order_id: S) -> Self {
+        self.order_id = Some(order_id.as_ref().to_string());
+        self.out_order_no = None;
+        self
+    }
+}
+
+/// 支付分服务时间范围
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PayScoreTimeRange {
+    ///【服务开始时间】 支持两种格式：yyyyMMddHHmmss、yyyyMMdd，传入OnAccept表示用户确认订单成功时间为服务开始时间
+    pub start_time: String,
+    ///【服务开始时间备注】 服务开始时间备注说明，服务开始时间有填写时才能填写
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time_remark: Option<String>,
+    ///【预计服务结束时间】 格式为yyyyMMddHHmmss或yyyyMMdd
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<String>,
+    ///【预计服务结束时间备注】 预计服务结束时间备注说明
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time_remark: Option<String>,
+}
+
+impl PayScoreTimeRange {
+    pub fn new<S: AsRef<str>>(start_time: S) -> Self {
+        Self {
+            start_time: start_time.as_ref().to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// 支付分订单风险金
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PayScoreRiskFund {
+    ///【风险金名称】 DEPOSIT：押金 ADVANCE：预付款 CASH_DEPOSIT：保证金 ESTIMATE_ORDER_COST：预估订单费用
+    pub name: String,
+    ///【风险金额】 单位为分，不能超过服务ID配置的风险金额上限
+    pub amount: Fen,
+    ///【风险说明】 风险金说明，不超过30个字符
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl PayScoreRiskFund {
+    pub fn new<S: AsRef<str>>(name: S, amount: impl Into<Fen>) -> Self {
+        Self {
+            name: name.as_ref().to_string(),
+            amount: amount.into(),
+            description: None,
+        }
+    }
+}
+
+/// 支付分订单后付费项目
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PayScorePostPayment {
+    ///【付费项目名称】 付费项目名称
+    pub name: String,
+    ///【金额】 此付费项目总金额，单位为分
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Fen>,
+    ///【计费说明】 描述计费规则，不超过30个字符
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    ///【付费数量】 付费项目的数量
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u32>,
+}
+
+/// 支付分订单后付费商户优惠
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PayScorePostDiscount {
+    ///【优惠名称】 优惠名称说明
+    pub name: String,
+    ///【优惠说明】 优惠使用条件说明
+    pub description: String,
+    ///【优惠金额】 优惠金额，单位为分
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Fen>,
+    ///【优惠数量】 优惠的数量
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u32>,
+}
+
+/// 创建支付分订单参数，请求体会注入appid、notify_url
+#[derive(Serialize, Debug, Clone)]
+pub struct PayScoreParams {
+    ///【商户服务订单号】 商户系统内部服务订单号，只能是数字、大小写字母_-|*
+    pub out_order_no: String,
+    ///【服务ID】 该服务ID由微信支付分配
+    pub service_id: String,
+    ///【服务信息】 用于介绍本订单所提供的服务，不超过20个字符
+    pub service_introduction: String,
+    ///【后付费项目】 后付费项目列表，最多包含100条付费项目
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub post_payments: Vec<PayScorePostPayment>,
+    ///【后付费商户优惠】 后付费商户优惠列表，最多包含30条商户优惠
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub post_discounts: Vec<PayScorePostDiscount>,
+    ///【服务时间段】 服务时间范围
+    pub time_range: PayScoreTimeRange,
+    ///【订单风险金】 订单风险金信息
+    pub risk_fund: PayScoreRiskFund,
+    ///【商户数据包】 商户数据包，可存放本订单所需信息，需要先urlencode后传入
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attach: Option<String>,
+    ///【用户标识】 微信用户在商户对应appid下的唯一标识，need_user_confirm为false时必填
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub openid: Option<String>,
+    ///【通知地址】 接收用户确认订单和付款成功回调通知的地址，不设置时使用全局配置的notify_url
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify_url: Option<String>,
+    ///【是否需要用户确认】 true：需要用户确认，返回package用于小程序调起确认订单页 false：免确认订单，需要填写openid
+    pub need_user_confirm: bool,
+}
+
+impl ParamsTrait for PayScoreParams {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    fn inject_fields(&self) -> InjectFields {
+        InjectFields {
+            appid: true,
+            mchid: false,
+            notify_url: true,
+        }
+    }
+    fn validate(&self) -> Result<(), PayError> {
+        util::validate_out_order_no(&self.out_order_no)?;
+        match &self.openid {
+            Some(openid) => util::validate_openid(openid),
+            None if !self.need_user_confirm => Err(PayError::InvalidParam {
+                field: "openid",
+                reason: "required when need_user_confirm is false".to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl PayScoreParams {
+    pub fn new<S: AsRef<str>>(
+        out_order_no: S,
+        service_id: S,
+        service_introduction: S,
+        time_range: PayScoreTimeRange,
+        risk_fund: PayScoreRiskFund,
+    ) -> Self {
+        Self {
+            notify_url: None,
+            out_order_no: out_order_no.as_ref().to_string(),
+            service_id: service_id.as_ref().to_string(),
+            service_introduction: service_introduction.as_ref().to_string(),
+            post_payments: vec![],
+            post_discounts: vec![],
+            time_range,
+            risk_fund,
+            attach: None,
+            openid: None,
+            need_user_confirm: true,
+        }
+    }
+
+    ///【通知地址】 本次请求使用的回调地址，覆盖全局配置的notify_url
+    pub fn with_notify_url<S: AsRef<str>>(mut self, notify_url: S) -> Self {
+        self.notify_url = Some(notify_url.as_ref().to_string());
+        self
+    }
+
+    ///【免确认订单】 不需要用户确认订单，服务直接开始，需要用户的openid
+    pub fn without_user_confirm<S: AsRef<str>>(mut self, openid: S) -> Self {
+        self.openid = Some(openid.as_ref().to_string());
+        self.need_user_confirm = false;
+        self
+    }
+
+    ///【后付费项目】 添加后付费项目
+    pub fn with_post_payment(mut self, post_payment: PayScorePostPayment) -> Self {
+        self.post_payments.push(post_payment);
+        self
+    }
+
+    ///【商户数据包】 商户数据包，在查询订单和回调通知中原样返回
+    pub fn with_attach<S: AsRef<str>>(mut self, attach: S) -> Self {
+        self.attach = Some(attach.as_ref().to_string());
+        self
+    }
+}
+
+/// 修改支付分订单金额参数，请求体会注入appid
+#[derive(Serialize, Debug, Clone)]
+pub struct PayScoreModifyParams {
+    ///【服务ID】 该服务ID由微信支付分配
+    pub service_id: String,
+    ///【后付费项目】 后付费项目列表，最多包含100条付费项目
+    pub post_payments: Vec<PayScorePostPayment>,
+    ///【后付费商户优惠】 后付费商户优惠列表，最多包含30条商户优惠
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub post_discounts: Vec<PayScorePostDiscount>,
+    ///【总金额】 单位为分，等于后付费项目总金额减去后付费商户优惠总金额，不能超过创建订单时的风险金额
+    pub total_amount: Fen,
+    ///【修改原因】 订单金额修改原因，不超过50个字符
+    pub reason: String,
+}
+
+impl ParamsTrait for PayScoreModifyParams {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    fn inject_fields(&self) -> InjectFields {
+        InjectFields {
+            appid: true,
+            mchid: false,
+            notify_url: false,
+        }
+    }
+}
+
+impl PayScoreModifyParams {
+    pub fn new<S: AsRef<str>>(
+        service_id: S,
+        post_payments: Vec<PayScorePostPayment>,
+        total_amount: impl Into<Fen>,
+        reason: S,
+    ) -> Self {
+        Self {
+            service_id: service_id.as_ref().to_string(),
+            post_payments,
+            post_discounts: vec![],
+            total_amount: total_amount.into(),
+            reason: reason.as_ref().to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TransferDetail {
+    ///【商家明细单号】 商户系统内部区分转账批次单下不同转账明细单的唯一标识，要求此参数只能由数字、大小写字母组成
+    pub out_detail_no: String,
+    ///【转账金额】 转账金额单位为"分"
+    pub transfer_amount: Fen,
+    ///【转账备注】 单条转账备注（微信用户会收到该备注），UTF8编码，最多允许32个字符
+    pub transfer_remark: String,
+    ///【收款用户openid】 商户appid下，某用户的openid
+    pub openid: String,
+    ///【收款用户姓名】 收款方真实姓名，明细转账金额>=2000元时必填，请求时会自动使用微信支付平台证书加密
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_name: Option<String>,
+}
+
+impl TransferDetail {
+    pub fn new<S: AsRef<str>>(
+        out_detail_no: S,
+        transfer_amount: impl Into<Fen>,
+        transfer_remark: S,
+        openid: S,
+    ) -> Self {
+        Self {
+            out_detail_no: out_detail_no.as_ref().to_string(),
+            transfer_amount: transfer_amount.into(),
+            transfer_remark: transfer_remark.as_ref().to_string(),
+            openid: openid.as_ref().to_string(),
+            user_name: None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TransferBatchParams {
+    ///【商家批次单号】 商户系统内部的商家批次单号，要求此参数只能由数字、大小写字母组成，在商户系统内部唯一
+    pub out_batch_no: String,
+    ///【批次名称】 该笔批量转账的名称
+    pub batch_name: String,
+    ///【批次备注】 转账说明，UTF8编码，最多允许32个字符
+    pub batch_remark: String,
+    ///【转账总金额】 转账金额单位为"分"，转账总金额必须与批次内所有明细转账金额之和保持一致
+    pub total_amount: Fen,
+    ///【转账总笔数】 一个转账批次单最多发起一千笔转账，转账总笔数必须与批次内所有明细之和保持一致
+    pub total_num: i32,
+    ///【转账明细列表】 发起批量转账的明细列表，最多一千笔
+    pub transfer_detail_list: Vec<TransferDetail>,
+    ///【转账场景ID】 该批次转账使用的转账场景，如不填写则使用商家的默认场景
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transfer_scene_id: Option<String>,
+}
+
+impl ParamsTrait for TransferBatchParams {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    fn validate(&self) -> Result<(), PayError> {
+        util::validate_out_batch_no(&self.out_batch_no)
+    }
+    fn inject_fields(&self) -> InjectFields {
+        InjectFields {
+            appid: true,
+            mchid: false,
+            notify_url: false,
+        }
+    }
+}
+
+impl TransferBatchParams {
+    /// 按明细自动计算转账总金额和总笔数
+    pub fn new<S: AsRef<str>>(
+        out_batch_no: S,
+        batch_name: S,
+        batch_remark: S,
+        transfer_detail_list: Vec<TransferDetail>,
+    ) -> Self {
+        Self {
+            out_batch_no: out_batch_no.as_ref().to_string(),
+            batch_name: batch_name.as_ref().to_string(),
+            batch_remark: batch_remark.as_ref().to_string(),
+            total_amount: transfer_detail_list
+                .iter()
+                .map(|detail| detail.transfer_amount)
+                .sum(),
+            total_num: transfer_detail_list.len() as i32,
+            transfer_detail_list,
+            transfer_scene_id: None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CombineAmount {
+    ///【标价金额】 子单金额，单位为分
+    pub total_amount: Fen,
+    ///【标价币种】 符合ISO 4217标准的三位字母代码，人民币：CNY
+    pub currency: Currency,
+}
+
+impl From<i32> for CombineAmount {
+    fn from(value: i32) -> Self {
+        Fen::from(value).into()
+    }
+}
+
+impl From<Fen> for CombineAmount {
+    fn from(value: Fen) -> Self {
+        Self {
+            total_amount: value,
+            currency: Currency::CNY,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CombineSubOrder {
+    ///【子单商户号】 子单发起方商户号，必须与发起方appid有绑定关系
+    pub mchid: String,
+    ///【附加数据】 附加数据，在查询API和支付通知中原样返回
+    pub attach: String,
+    ///【订单金额】 订单金额信息
+    pub amount: CombineAmount,
+    ///【子单商户订单号】 商户系统内部订单号，要求32个字符内，只能是数字、大小写字母_-|*@ ，且在同一个商户号下唯一
+    pub out_trade_no: String,
+    ///【商品描述】 商品描述
+    pub description: String,
+    ///【二级商户号】 服务商模式下的二级商户号
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub_mchid: Option<String>,
+    ///【订单优惠标记】 订单优惠标记
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goods_tag: Option<String>,
+    ///【结算信息】 结算信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settle_info: Option<SettleInfo>,
+}
+
+impl CombineSubOrder {
+    pub fn new<S: AsRef<str>>(
+        mchid: S,
+        out_trade_no: S,
+        description: S,
+        amount: CombineAmount,
+    ) -> Self {
+        Self {
+            mchid: mchid.as_ref().to_string(),
+            attach: "".to_string(),
+            amount,
+            out_trade_no: out_trade_no.as_ref().to_string(),
+            description: description.as_ref().to_string(),
+            sub_mchid: None,
+            goods_tag: None,
+            settle_info: None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CombineSceneInfo {
+    ///【用户终端IP】 用户的客户端IP，支持IPv4和IPv6两种格式的IP地址。
+    pub payer_client_ip: String,
+    ///【商户端设备号】 商户端设备号（门店号或收银设备ID）。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+    ///【H5场景信息】 合单H5支付必填
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub h5_info: Option<H5Info>,
+}
+
+/// 合单支付参数，JSAPI、APP、H5、Native合单共用，
+/// 子单各自携带mchid，请求时只注入combine_appid、combine_mchid、notify_url
+#[derive(Serialize, Debug, Clone)]
+pub struct CombineParams {
+    ///【合单商户订单号】 合单支付总订单号，要求32个字符内，只能是数字、大小写字母_-|*@ ，且在同一个商户号下唯一
+    pub combine_out_trade_no: String,
+    ///【场景信息】 支付场景信息描述
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scene_info: Option<CombineSceneInfo>,
+    ///【子单信息】 最多支持子单条数：10
+    pub sub_orders: Vec<CombineSubOrder>,
+    ///【支付者】 支付者信息，合单JSAPI支付必填
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub combine_payer_info: Option<PayerInfo>,
+    ///【交易起始时间】 订单生成时间，遵循rfc3339标准格式
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_start: Option<String>,
+    ///【交易结束时间】 订单失效时间，遵循rfc3339标准格式
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_expire: Option<String>,
+}
+
+impl ParamsTrait for CombineParams {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    fn validate(&self) -> Result<(), PayError> {
+        util::validate_out_trade_no(&self.combine_out_trade_no)?;
+        self.sub_orders
+            .iter()
+            .try_for_each(|sub_order| util::validate_out_trade_no(&sub_order.out_trade_no))
+    }
+    fn inject_fields(&self) -> InjectFields {
+        InjectFields::NONE
+    }
+}
+
+impl CombineParams {
+    pub fn new<S: AsRef<str>>(combine_out_trade_no: S, sub_orders: Vec<CombineSubOrder>) -> Self {
+        Self {
+            combine_out_trade_no: combine_out_trade_no.as_ref().to_string(),
+            scene_info: None,
+            sub_orders,
+            combine_payer_info: None,
+            time_start: None,
+            time_expire: None,
+        }
+    }
+}
+
+/// 合单关单的子单信息
+#[derive(Serialize, Debug, Clone)]
+pub struct CombineCloseSubOrder {
+    ///【子单商户号】 子单发起方商户号
+    pub mchid: String,
+    ///【子单商户订单号】 商户系统内部订单号
+    pub out_trade_no: String,
+    ///【二级商户号】 服务商模式下的二级商户号
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub_mchid: Option<String>,
+}
+
+impl CombineCloseSubOrder {
+    pub fn new<S: AsRef<str>>(mchid: S, out_trade_no: S) -> Self {
+        Self {
+            mchid: mchid.as_ref().to_string(),
+            out_trade_no: out_trade_no.as_ref().to_string(),
+            sub_mchid: None,
+        }
+    }
+}
+
+impl From<&CombineSubOrder> for CombineCloseSubOrder {
+    fn from(sub_order: &CombineSubOrder) -> Self {
+        Self {
+            mchid: sub_order.mchid.clone(),
+            out_trade_no: sub_order.out_trade_no.clone(),
+            sub_mchid: sub_order.sub_mchid.clone(),
+        }
+    }
+}
+
+/// 电子发票开票场景
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FapiaoScene {
+    /// 微信支付场景，用户支付后在支付凭证中申请开票
+    #[default]
+    WithWechatpay,
+    /// 非微信支付场景
+    WithoutWechatpay,
+}
+
+impl FapiaoScene {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FapiaoScene::WithWechatpay => "WITH_WECHATPAY",
+            FapiaoScene::WithoutWechatpay => "WITHOUT_WECHATPAY",
+        }
+    }
+}
+
+/// 发票购买方类型
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FapiaoBuyerType {
+    /// 个人
+    Individual,
+    /// 单位
+    Organization,
+}
+
+/// 发票购买方信息，获取抬头接口返回的用户填写信息可直接用于开票
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FapiaoBuyerInformation {
+    ///【购买方类型】 INDIVIDUAL：个人，ORGANIZATION：单位
+    #[serde(rename = "type")]
+    pub buyer_type: FapiaoBuyerType,
+    ///【名称】 个人姓名或单位名称
+    pub name: String,
+    ///【纳税人识别号】 购买方为单位时必填
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub taxpayer_id: Option<String>,
+    ///【地址】
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    ///【电话】
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telephone: Option<String>,
+    ///【开户银行】
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank_name: Option<String>,
+    ///【银行账号】
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank_account: Option<String>,
+    ///【手机号】 开票时使用平台证书加密
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    ///【邮箱地址】 开票时使用平台证书加密
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+impl FapiaoBuyerInformation {
+    pub fn new<S: AsRef<str>>(buyer_type: FapiaoBuyerType, name: S) -> Self {
+        Self {
+            buyer_type,
+            name: name.as_ref().to_string(),
+            taxpayer_id: None,
+            address: None,
+            telephone: None,
+            bank_name: None,
+            bank_account: None,
+            phone: None,
+            email: None,
+        }
+    }
+}
+
+/// 发票行项目
+#[derive(Serialize, Debug, Clone)]
+pub struct FapiaoItem {
+    ///【税局侧规定的商品和服务分类编码】
+    pub tax_code: String,
+    ///【商品名称】
+    pub goods_name: String,
+    ///【规格型号】
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub specification: Option<String>,
+    ///【单位】
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    ///【数量】 实际数量乘以10的8次方，例如1件传100000000
+    pub quantity: i64,
+    ///【单行金额合计】 含税金额，单位为分
+    pub total_amount: Fen,
+    ///【税率】 单位为万分之一，例如13%传1300
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tax_rate: Option<i32>,
+    ///【税收优惠政策标识】
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tax_prefer_mark: Option<String>,
+    ///【是否折扣行】
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discount: Option<bool>,
+}
+
+impl FapiaoItem {
+    pub fn new<S: AsRef<str>>(
+        tax_code: S,
+        goods_name: S,
+        quantity: i64,
+        total_amount: Fen,
+    ) -> Self {
+        Self {
+            tax_code: tax_code.as_ref().to_string(),
+            goods_name: goods_name.as_ref().to_string(),
+            specification: None,
+            unit: None,
+            quantity,
+            total_amount,
+            tax_rate: None,
+            tax_prefer_mark: None,
+            discount: None,
+        }
+    }
+}
+
+/// 单张发票信息
+#[derive(Serialize, Debug, Clone)]
+pub struct FapiaoInformation {
+    ///【商户发票单号】 商户侧唯一标识一张发票
+    pub fapiao_id: String,
+    ///【总价税合计】 单位为分，等于各行项目金额之和
+    pub total_amount: Fen,
+    ///【是否以清单形式开具发票】
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub need_list: Option<bool>,
+    ///【发票备注】
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remark: Option<String>,
+    ///【发票行信息】
+    pub items: Vec<FapiaoItem>,
+}
+
+impl FapiaoInformation {
+    /// total_amount按items的金额求和
+    pub fn new<S: AsRef<str>>(fapiao_id: S, items: Vec<FapiaoItem>) -> Self {
+        Self {
+            fapiao_id: fapiao_id.as_ref().to_string(),
+            total_amount: items.iter().map(|item| item.total_amount).sum(),
+            need_list: None,
+            remark: None,
+            items,
+        }
+    }
+}
+
+/// 开具电子发票参数，请求体不注入appid、mchid
+#[derive(Serialize, Debug, Clone)]
+pub struct FapiaoParams {
+    ///【开票场景】
+    pub scene: FapiaoScene,
+    ///【发票申请单号】 与获取抬头时使用的申请单号一致
+    pub fapiao_apply_id: String,
+    ///【购买方信息】
+    pub buyer_information: FapiaoBuyerInformation,
+    ///【需要开具的发票信息】
+    pub fapiao_information: Vec<FapiaoInformation>,
+}
+
+impl ParamsTrait for FapiaoParams {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    fn inject_fields(&self) -> InjectFields {
+        InjectFields::NONE
+    }
+    fn validate(&self) -> Result<(), PayError> {
+        util::validate_fapiao_apply_id(&self.fapiao_apply_id)?;
+        if self.fapiao_information.is_empty() {
+            return Err(PayError::InvalidParam {
+                field: "fapiao_information",
+                reason: "must not be empty".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl FapiaoParams {
+    pub fn new<S: AsRef<str>>(
+        fapiao_apply_id: S,
+        buyer_information: FapiaoBuyerInformation,
+        fapiao_information: Vec<FapiaoInformation>,
+    ) -> Self {
+        Self {
+            scene: FapiaoScene::default(),
+            fapiao_apply_id: fapiao_apply_id.as_ref().to_string(),
+            buyer_information,
+            fapiao_information,
+        }
+    }
+
+    pub fn with_scene(mut self, scene: FapiaoScene) -> Self {
+        self.scene = scene;
+        self
+    }
+}
+
+/// 交易状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum TradeState {
+    /// 支付成功
+    Success,
+    /// 转入退款
+    Refund,
+    /// 未支付
+    NotPay,
+    /// 已关闭
+    Closed,
+    /// 已撤销（仅付款码支付会返回）
+    Revoked,
+    /// 用户支付中（仅付款码支付会返回）
+    UserPaying,
+    /// 支付失败（仅付款码支付会返回）
+    PayError,
+    /// 未知状态，原样保留
+    Other(String),
+}
+
+impl Display for TradeState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TradeState::Success => write!(f, "SUCCESS"),
+            TradeState::Refund => write!(f, "REFUND"),
+            TradeState::NotPay => write!(f, "NOTPAY"),
+            TradeState::Closed => write!(f, "CLOSED"),
+            TradeState::Revoked => write!(f, "REVOKED"),
+            TradeState::UserPaying => write!(f, "USERPAYING"),
+            TradeState::PayError => write!(f, "PAYERROR"),
+            TradeState::Other(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl TradeState {
+    /// 是否为终态（SUCCESS/REFUND/CLOSED/REVOKED/PAYERROR），终态后订单状态不再变化
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            TradeState::Success
+                | TradeState::Refund
+                | TradeState::Closed
+                | TradeState::Revoked
+                | TradeState::PayError
+        )
+    }
+}
+
+impl FromStr for TradeState {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "SUCCESS" => TradeState::Success,
+            "REFUND" => TradeState::Refund,
+            "NOTPAY" => TradeState::NotPay,
+            "CLOSED" => TradeState::Closed,
+            "REVOKED" => TradeState::Revoked,
+            "USERPAYING" => TradeState::UserPaying,
+            "PAYERROR" => TradeState::PayError,
+            _ => TradeState::Other(s.to_string()),
+        })
+    }
+}
+
+impl From<String> for TradeState {
+    fn from(value: String) -> Self {
+        match value.parse() {
+            Ok(TradeState::Other(_)) => TradeState::Other(value),
+            Ok(state) => state,
+            Err(never) => match never {},
+        }
+    }
+}
+
+impl From<TradeState> for String {
+    fn from(value: TradeState) -> Self {
+        value.to_string()
+    }
+}
+
+/// 查询订单、支付通知中返回的订单金额
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Amount {
+    ///【总金额】 订单总金额，单位为分。未支付订单的查询结果中可能不返回，此时为0
+    #[serde(default)]
+    pub total: Fen,
+    ///【用户支付金额】 用户支付金额，单位为分。
+    pub payer_total: Option<Fen>,
+    ///【货币类型】 CNY：人民币，境内商户号仅支持人民币。未返回时为CNY
+    #[serde(default)]
+    pub currency: Currency,
+    ///【用户支付币种】 用户支付币种
+    pub payer_currency: Option<Currency>,
+}
+
+/// 查询订单、支付通知中返回的优惠功能信息，使用代金券等优惠时返回
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Promotion {
+    ///【券ID】 券ID
+    pub coupon_id: String,
+    ///【优惠名称】 优惠名称
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    ///【优惠范围】 GLOBAL：全场代金券 SINGLE：单品优惠
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    ///【优惠类型】 CASH：充值型代金券 NOCASH：免充值型代金券
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub promotion_type: Option<String>,
+    ///【优惠券面额】 优惠券面额，单位为分
+    pub amount: Fen,
+    ///【活动ID】 活动ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stock_id: Option<String>,
+    ///【微信出资】 微信出资，单位为分
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wechatpay_contribute: Option<Fen>,
+    ///【商户出资】 商户出资，单位为分
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merchant_contribute: Option<Fen>,
+    ///【其他出资】 其他出资，单位为分
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub other_contribute: Option<Fen>,
+    ///【优惠币种】 CNY：人民币
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<Currency>,
+    ///【单品列表】 单品优惠时返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goods_detail: Option<Vec<PromotionGoodsDetail>>,
+}
+
+/// 优惠涉及的单品
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PromotionGoodsDetail {
+    ///【商品编码】 商品编码
+    pub goods_id: String,
+    ///【商品数量】 商品数量
+    pub quantity: i64,
+    ///【商品单价】 商品单价，单位为分
+    pub unit_price: Fen,
+    ///【商品优惠金额】 商品优惠金额，单位为分
+    pub discount_amount: Fen,
+    ///【商品备注】 商品备注
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goods_remark: Option<String>,
+}
+
+/// 回调通知中的加密数据
+pub type NotifyResource = WechatPayNotifySource;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WechatPayNotify {
+    pub id: String,
+    pub create_time: String,
+    pub event_type: String,
+    pub resource_type: String,
+    pub resource: WechatPayNotifySource,
+    pub summary: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WechatPayDecodeData {
+    pub mchid: String,
+    pub appid: String,
+    pub out_trade_no: String,
+    pub transaction_id: String,
+    pub trade_type: String,
+    pub trade_state: String,
+    pub trade_state_desc: String,
+    pub bank_type: String,
+    pub attach: String,
+    pub success_time: String,
+    pub payer: PayerInfo,
+    pub amount: AmountInfo,
+}
+
+/// 支付成功回调解密后的数据
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PaymentNotification {
+    ///【应用ID】 直连商户申请的公众号或移动应用AppID。
+    pub appid: Option<String>,
+    ///【商户号】 商户的商户号，由微信支付生成并下发。
+    pub mchid: Option<String>,
+    ///【商户订单号】 商户系统内部订单号
+    pub out_trade_no: String,
+    ///【微信支付订单号】 微信支付系统生成的订单号。
+    pub transaction_id: String,
+    ///【交易类型】 JSAPI、NATIVE、APP、MICROPAY、MWEB、FACEPAY
+    pub trade_type: Option<String>,
+    ///【交易状态】 SUCCESS：支付成功 REFUND：转入退款 NOTPAY：未支付 CLOSED：已关闭 REVOKED：已撤销 USERPAYING：用户支付中 PAYERROR：支付失败
+    pub trade_state: TradeState,
+    ///【交易状态描述】 交易状态描述
+    pub trade_state_desc: Option<String>,
+    ///【银行类型】 银行类型，采用字符串类型的银行标识。
+    pub bank_type: Option<String>,
+    ///【附加数据】 附加数据，在查询API和支付通知中原样返回
+    pub attach: Option<String>,
+    ///【支付完成时间】 支付完成时间，遵循rfc3339标准格式
+    pub success_time: Option<String>,
+    ///【支付者】 支付者信息
+    pub payer: Option<PayerInfo>,
+    ///【订单金额】 订单金额信息
+    pub amount: Amount,
+    ///【优惠功能】 使用代金券等优惠时返回
+    pub promotion_detail: Option<Vec<Promotion>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RefundNotifyAmount {
+    ///【订单金额】 订单总金额，单位为分
+    pub total: Fen,
+    ///【退款金额】 退款金额，单位为分
+    pub refund: Fen,
+    ///【用户支付金额】 用户实际支付金额，单位为分
+    pub payer_total: Fen,
+    ///【用户退款金额】 退款给用户的金额，单位为分
+    pub payer_refund: Fen,
+}
+
+/// 退款结果回调解密后的数据
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RefundNotification {
+    ///【直连商户号】 直连商户的商户号，由微信支付生成并下发。
+    pub mchid: String,
+    ///【商户订单号】 商户系统内部订单号
+    pub out_trade_no: String,
+    ///【微信支付订单号】 微信支付交易订单号
+    pub transaction_id: String,
+    ///【商户退款单号】 商户系统内部的退款单号
+    pub out_refund_no: String,
+    ///【微信支付退款单号】 微信支付退款单号
+    pub refund_id: String,
+    ///【退款状态】 SUCCESS：退款成功 CLOSED：退款关闭 ABNORMAL：退款异常
+    pub refund_status: RefundStatus,
+    ///【退款成功时间】 退款成功时间，当前退款成功时才有此返回值
+    pub success_time: Option<String>,
+    ///【退款入账账户】 取当前退款单的退款入账方
+    pub user_received_account: String,
+    ///【金额信息】 金额信息
+    pub amount: RefundNotifyAmount,
+}
+
+/// 回调通知请求头中验签需要的`Wechatpay-Serial`、`Wechatpay-Timestamp`、`Wechatpay-Nonce`、`Wechatpay-Signature`，
+/// 与web框架无关，任意框架的请求头都可以通过[from_pairs](NotificationHeaders::from_pairs)转换
+///
+/// ```
+/// use wechat_pay_rust_sdk::model::NotificationHeaders;
+///
+/// // 例如axum/actix的请求头：headers.iter().map(|(k, v)| (k.as_str(), v.to_str().unwrap_or_default()))
+/// let headers = NotificationHeaders::from_pairs([
+///     ("wechatpay-serial", "5157F09EFDC096DE15EBE81A47057A7232F1B8E1"),
+///     ("wechatpay-timestamp", "1705066785"),
+///     ("wechatpay-nonce", "Jh9oPZelCJIQeQ47kz4stzvDKpLEUhCX"),
+///     ("wechatpay-signature", "signature"),
+/// ])
+/// .unwrap();
+/// assert_eq!(headers.timestamp, "1705066785");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NotificationHeaders {
+    ///【Wechatpay-Serial】 平台证书序列号
+    pub serial: String,
+    ///【Wechatpay-Timestamp】 应答时间戳
+    pub timestamp: String,
+    ///【Wechatpay-Nonce】 应答随机串
+    pub nonce: String,
+    ///【Wechatpay-Signature】 应答签名
+    pub signature: String,
+}
+
+impl NotificationHeaders {
+    /// 从请求头的(名称, 值)中取出四个`Wechatpay-*`值，名称不区分大小写，缺少任意一个时返回`PayError::VerifyError`
+    pub fn from_pairs<K, V>(pairs: impl IntoIterator<Item = (K, V)>) -> Result<Self, PayError>
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let (mut serial, mut timestamp, mut nonce, mut signature) = (None, None, None, None);
+        for (name, value) in pairs {
+            let slot = match name.as_ref().to_ascii_lowercase().as_str() {
+                "wechatpay-serial" => &mut serial,
+                "wechatpay-timestamp" => &mut timestamp,
+                "wechatpay-nonce" => &mut nonce,
+                "wechatpay-signature" => &mut signature,
+                _ => continue,
+            };
+            *slot = Some(value.as_ref().to_string());
+        }
+        let required = |value: Option<String>, name: &str| {
+            value.ok_or_else(|| PayError::VerifyError(format!("missing header: {}", name)))
+        };
+        Ok(Self {
+            serial: required(serial, "Wechatpay-Serial")?,
+            timestamp: required(timestamp, "Wechatpay-Timestamp")?,
+            nonce: required(nonce, "Wechatpay-Nonce")?,
+            signature: required(signature, "Wechatpay-Signature")?,
+        })
+    }
+
+    /// 从reqwest/http的`HeaderMap`中取出，非UTF-8的值会被忽略
+    pub fn from_header_map(headers: &reqwest::header::HeaderMap) -> Result<Self, PayError> {
+        Self::from_pairs(
+            headers.iter().filter_map(|(name, value)| {
+                value.to_str().ok().map(|value| (name.as_str(), value))
+            }),
+        )
+    }
+}
+
+/// 解密后的回调通知，按event_type区分
+#[derive(Debug, Clone)]
+pub enum Notification {
+    /// 支付成功通知，event_type为`TRANSACTION.SUCCESS`
+    Transaction(PaymentNotification),
+    /// 退款结果通知，event_type为`REFUND.SUCCESS`、`REFUND.ABNORMAL`、`REFUND.CLOSED`
+    Refund(RefundNotification),
+    /// 其他通知，保留解密后的原始数据
+    Other {
+        event_type: String,
+        resource: serde_json::Value,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PayError;
+    use crate::model::{
+        Amount, AmountInfo, CodepayParams, CodepaySceneInfo, CodepayStoreInfo, Currency, Fen,
+        GoodsDetail, H5Params, H5SceneInfo, JsapiParams, MicroParams, NativeParams,
+        NotificationHeaders, OpenidKind, OrderDetail, ParamsTrait, PaymentNotification, Promotion,
+        RefundAmount, SceneInfo, TradeState,
+    };
+    use chrono::{DateTime, FixedOffset};
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    #[test]
+    fn test_fen() {
+        assert_eq!(Fen::from_yuan(0.1 + 0.2), Fen(30));
+        assert_eq!(Fen::from_yuan(1.005), Fen(101));
+        assert_eq!(Fen::from_yuan(0.014), Fen(1));
+        assert_eq!(Fen::from_yuan(19.99), Fen(1999));
+        assert_eq!(Fen::from_yuan(-0.5), Fen(-50));
+        assert_eq!(Fen(1999).to_yuan(), 19.99);
+        assert_eq!(Fen(1999).to_string(), "19.99");
+        assert_eq!(Fen(-5).to_string(), "-0.05");
+        assert_eq!(Fen::from(1), Fen(1));
+        assert_eq!(Fen::from(1i64).fen(), 1);
+
+        let params = NativeParams::new("测试支付1分", "1217752501201407033233368018", 1.into());
+        assert_eq!(params.amount.total, Fen(1));
+        let params = NativeParams::new(
+            "测试支付",
+            "1217752501201407033233368018",
+            Fen::from_yuan(0.1).into(),
+        );
+        let json: Value = serde_json::from_str(&params.to_json()).unwrap();
+        assert_eq!(json["amount"], json!({"total": 10}));
+        let refund = serde_json::to_value(RefundAmount::new(1, Fen::from_yuan(0.3))).unwrap();
+        assert_eq!(refund, json!({"refund": 1, "total": 30, "currency": "CNY"}));
+    }
+
+    #[test]
+    fn test_currency() {
+        let amount = AmountInfo::from(100).with_currency("USD");
+        let json: Value = serde_json::to_value(&amount).unwrap();
+        assert_eq!(json, json!({"total": 100, "currency": "USD"}));
+        let amount: AmountInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(amount.currency(), Currency::Other("USD".to_string()));
+        assert_eq!(AmountInfo::from(100).currency(), Currency::CNY);
+        let amount: Amount = serde_json::from_str(
+            r#"{"total":100,"payer_total":680,"currency":"USD","payer_currency":"CNY"}"#,
+        )
+        .unwrap();
+        assert_eq!(amount.currency.to_string(), "USD");
+        assert_eq!(amount.payer_currency, Some(Currency::CNY));
+        let promotion: Promotion =
+            serde_json::from_str(r#"{"coupon_id":"109519","amount":10,"currency":"USD"}"#).unwrap();
+        assert_eq!(promotion.currency, Some(Currency::Other("USD".to_string())));
+    }
+
+    #[test]
+    fn test_with_expire_at() {
+        let expire_at = DateTime::parse_from_rfc3339("2018-06-08T02:34:56.5Z")
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(8 * 3600).unwrap());
+        let params = NativeParams::new("测试支付1分", "1217752501201407033233368018", 1.into())
+            .with_expire_at(expire_at);
+        assert_eq!(
+            params.time_expire.as_deref(),
+            Some("2018-06-08T10:34:56+08:00")
+        );
+        let params = NativeParams::new("测试支付1分", "1217752501201407033233368018", 1.into())
+            .with_expire_in(Duration::from_secs(600));
+        let time_expire = params.time_expire.unwrap();
+        assert!(time_expire.ends_with("+08:00"));
+        assert!(DateTime::parse_from_rfc3339(&time_expire).is_ok());
+        let params = NativeParams::new("测试支付1分", "1217752501201407033233368018", 1.into())
+            .with_expire_in(Duration::MAX);
+        assert_eq!(
+            params.time_expire.as_deref(),
+            Some("9999-12-31T23:59:59+08:00")
+        );
+    }
+
+    #[test]
+    fn test_native_params_optional_fields() {
+        let params = NativeParams::new("测试支付1分", "1217752501201407033233368018", 1.into());
+        let json: Value = serde_json::from_str(&params.to_json()).unwrap();
+        let keys: Vec<&String> = json.as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["amount", "description", "out_trade_no"]);
+
+        let params = params
+            .with_time_expire("2018-06-08T10:34:56+08:00")
+            .with_attach("自定义数据")
+            .with_scene_info(SceneInfo::new("14.23.150.211"));
+        let json: Value = serde_json::from_str(&params.to_json()).unwrap();
+        assert_eq!(json["time_expire"], "2018-06-08T10:34:56+08:00");
+        assert_eq!(json["attach"], "自定义数据");
+        assert_eq!(json["scene_info"]["payer_client_ip"], "14.23.150.211");
+        assert!(json["scene_info"].get("device_id").is_none());
+        assert!(json.get("goods_tag").is_none());
+        assert!(json.get("detail").is_none());
+    }
+
+    #[test]
+    fn test_h5_params_scene_info() {
+        let params = H5Params::new(
+            "测试支付1分",
+            "1217752501201407033233368018",
+            1.into(),
+            "14.23.150.211",
+        );
+        let json: Value = serde_json::from_str(&params.to_json()).unwrap();
+        assert_eq!(json["scene_info"]["payer_client_ip"], "14.23.150.211");
+        assert_eq!(json["scene_info"]["h5_info"]["type"], "Wap");
+
+        let params = params.with_scene_info(H5SceneInfo::new(
+            "183.6.105.141",
+            "ipa软件下载",
+            "https://mydomain.com",
+        ));
+        let json: Value = serde_json::from_str(&params.to_json()).unwrap();
+        assert_eq!(json["scene_info"]["payer_client_ip"], "183.6.105.141");
+        assert_eq!(json["scene_info"]["h5_info"]["app_name"], "ipa软件下载");
+    }
+
+    #[test]
+    fn test_codepay_params_auth_code() {
+        let params = CodepayParams::new(
+            "测试支付1分",
+            "1217752501201407033233368018",
+            1.into(),
+            "134567890123456789",
+        )
+        .with_scene_info(CodepaySceneInfo {
+            store_info: CodepayStoreInfo {
+                out_id: "1234".to_string(),
+            },
+            device_ip: None,
+        });
+        params.validate().expect("validate error");
+        let json: Value = serde_json::from_str(&params.to_json()).unwrap();
+        assert_eq!(json["payer"], json!({"auth_code": "134567890123456789"}));
+        assert_eq!(
+            json["scene_info"],
+            json!({"store_info": {"out_id": "1234"}})
+        );
+
+        let params =
+            CodepayParams::new("测试支付1分", "1217752501201407033233368018", 1.into(), " ");
+        assert!(matches!(
+            params.validate(),
+            Err(PayError::InvalidParam {
+                field: "payer.auth_code",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_order_detail() {
+        let detail = OrderDetail::new(vec![
+            GoodsDetail::new("1246464644", 1, 528800)
+                .with_goods_name("iPhoneX 256G")
+                .with_wechatpay_goods_id("1001"),
+            GoodsDetail::new("1246464645", 2, Fen::from_yuan(1.0)),
+        ])
+        .with_cost_price(Fen::from_yuan(6088.0))
+        .with_invoice_id("微信123");
+        let params = NativeParams::new("测试支付", "1217752501201407033233368018", 529000.into())
+            .with_detail(detail);
+        let json: Value = serde_json::from_str(&params.to_json()).unwrap();
+        assert_eq!(
+            json["detail"],
+            json!({
+                "cost_price": 608800,
+                "invoice_id": "微信123",
+                "goods_detail": [
+                    {"merchant_goods_id": "1246464644", "quantity": 1, "unit_price": 528800, "wechatpay_goods_id": "1001", "goods_name": "iPhoneX 256G"},
+                    {"merchant_goods_id": "1246464645", "quantity": 2, "unit_price": 100}
+                ]
+            })
+        );
+
+        let params = NativeParams::new("测试支付", "1217752501201407033233368018", 1.into())
+            .with_detail(OrderDetail::new(vec![]));
+        let json: Value = serde_json::from_str(&params.to_json()).unwrap();
+        assert!(json.get("detail").is_none());
+    }
+
+    #[test]
+    fn test_jsapi_params_payer() {
+        let params = JsapiParams::new(
+            "测试支付1分",
+            "1217752501201407033233368018",
+            1.into(),
+            "oUpF8uMuAJO_M2pxb1Q9zNjWeS6o".into(),
+        );
+        params.validate().expect("validate error");
+        let json: Value = serde_json::from_str(&params.to_json()).unwrap();
+        assert_eq!(
+            json["payer"],
+            json!({"openid": "oUpF8uMuAJO_M2pxb1Q9zNjWeS6o"})
+        );
+
+        for (kind, field) in [
+            (OpenidKind::Direct, "openid"),
+            (OpenidKind::SpOpenid, "sp_openid"),
+            (OpenidKind::SubOpenid, "sub_openid"),
+        ] {
+            let json: Value =
+                serde_json::from_str(&params.clone().with_openid_kind(kind).to_json()).unwrap();
+            assert_eq!(
+                json["payer"],
+                json!({ field: "oUpF8uMuAJO_M2pxb1Q9zNjWeS6o" })
+            );
+            assert_eq!(json["out_trade_no"], "1217752501201407033233368018");
+        }
+
+        let params = MicroParams::new(
+            "测试支付1分",
+            "1217752501201407033233368018",
+            1.into(),
+            "oUpF8uMuAJO_M2pxb1Q9zNjWeS6o".into(),
+        )
+        .with_openid_kind(OpenidKind::SubOpenid);
+        let json: Value = serde_json::from_str(&params.to_json()).unwrap();
+        assert_eq!(
+            json["payer"],
+            json!({"sub_openid": "oUpF8uMuAJO_M2pxb1Q9zNjWeS6o"})
+        );
+
+        let params = MicroParams::new(
+            "测试支付1分",
+            "1217752501201407033233368018",
+            1.into(),
+            " ".into(),
+        );
+        assert!(matches!(
+            params.validate(),
+            Err(PayError::InvalidParam {
+                field: "payer.openid",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_notification_headers() {
+        let pairs = vec![
+            ("Content-Type".to_string(), "application/json".to_string()),
+            (
+                "WECHATPAY-SERIAL".to_string(),
+                "5157F09EFDC096DE15EBE81A47057A7232F1B8E1".to_string(),
+            ),
+            ("wechatpay-timestamp".to_string(), "1705066785".to_string()),
+            (
+                "Wechatpay-Nonce".to_string(),
+                "Jh9oPZelCJIQeQ47kz4stzvDKpLEUhCX".to_string(),
+            ),
+            ("WeChatPay-Signature".to_string(), "signature".to_string()),
+        ];
+        let headers = NotificationHeaders::from_pairs(pairs.clone()).unwrap();
+        assert_eq!(
+            headers,
+            NotificationHeaders {
+                serial: "5157F09EFDC096DE15EBE81A47057A7232F1B8E1".to_string(),
+                timestamp: "1705066785".to_string(),
+                nonce: "Jh9oPZelCJIQeQ47kz4stzvDKpLEUhCX".to_string(),
+                signature: "signature".to_string(),
+            }
+        );
+
+        let mut header_map = reqwest::header::HeaderMap::new();
+        for (name, value) in &pairs {
+            header_map.insert(
+                reqwest::header::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                value.parse().unwrap(),
+            );
+        }
+        assert_eq!(
+            NotificationHeaders::from_header_map(&header_map).unwrap(),
+            headers
+        );
+
+        let result = NotificationHeaders::from_pairs(pairs.into_iter().take(4));
+        assert!(matches!(
+            result,
+            Err(PayError::VerifyError(message)) if message == "missing header: Wechatpay-Signature"
+        ));
+    }
+
+    #[test]
+    fn test_trade_state() {
+        let states = [
+            ("SUCCESS", TradeState::Success),
+            ("REFUND", TradeState::Refund),
+            ("NOTPAY", TradeState::NotPay),
+            ("CLOSED", TradeState::Closed),
+            ("REVOKED", TradeState::Revoked),
+            ("USERPAYING", TradeState::UserPaying),
+            ("PAYERROR", TradeState::PayError),
+        ];
+        for (value, state) in states {
+            assert_eq!(value.parse::<TradeState>().unwrap(), state);
+            assert_eq!(state.to_string(), value);
+            let json = format!("\"{}\"", value);
+            assert_eq!(serde_json::from_str::<TradeState>(&json).unwrap(), state);
+            assert_eq!(serde_json::to_string(&state).unwrap(), json);
+        }
+        let state: TradeState = serde_json::from_str(r#""ACCEPT""#).unwrap();
+        assert_eq!(state, TradeState::Other("ACCEPT".to_string()));
+        assert_eq!(state.to_string(), "ACCEPT");
+    }
+
+    #[test]
+    fn test_promotion_detail() {
+        let payload = include_str!("../testdata/order_query_promotion.json");
+        let notification: PaymentNotification = serde_json::from_str(payload).unwrap();
+        let promotion = &notification.promotion_detail.as_ref().unwrap()[0];
+        assert_eq!(promotion.coupon_id, "109519");
+        assert_eq!(promotion.promotion_type.as_deref(), Some("CASH"));
+        assert_eq!(promotion.amount, Fen(10));
+        let goods = &promotion.goods_detail.as_ref().unwrap()[0];
+        assert_eq!(goods.goods_id, "M1006");
+        assert_eq!(goods.discount_amount, Fen(10));
+
+        let expected: Value = serde_json::from_str(payload).unwrap();
+        let actual = serde_json::to_value(&notification).unwrap();
+        assert_eq!(actual["promotion_detail"], expected["promotion_detail"]);
+    }
 }
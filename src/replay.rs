@@ -0,0 +1,159 @@
+//! 回调防重放：校验`Wechatpay-Timestamp`与当前时间的偏差（默认5分钟），并可以记录nonce拒绝重复的回调
+use crate::error::PayError;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 默认允许回调时间戳与当前时间相差5分钟
+pub const DEFAULT_NOTIFY_TOLERANCE: Duration = Duration::from_secs(5 * 60);
+
+/// 记录已处理过的回调nonce，可以基于redis等实现多实例共享
+pub trait NonceStore: Send + Sync {
+    /// nonce第一次出现时记录并返回true，重复出现返回false。
+    /// timestamp为回调的时间戳，now为校验回调时使用的当前时间，清理过期记录应以now为准
+    fn insert(&self, nonce: &str, timestamp: i64, now: i64) -> bool;
+}
+
+/// 内存中的nonce记录，插入时清理时间戳早于`now - ttl`的记录，ttl不应小于回调时间戳的容忍范围
+#[derive(Debug)]
+pub struct MemoryNonceStore {
+    nonces: Mutex<HashMap<String, i64>>,
+    ttl: Duration,
+}
+
+impl Default for MemoryNonceStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_NOTIFY_TOLERANCE)
+    }
+}
+
+impl MemoryNonceStore {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            nonces: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+}
+
+impl NonceStore for MemoryNonceStore {
+    fn insert(&self, nonce: &str, timestamp: i64, now: i64) -> bool {
+        let mut nonces = self.nonces.lock().unwrap();
+        // 用当前时间而不是回调时间戳清理，时间戳偏向未来的回调不会提前清掉仍可重放的nonce
+        let expired = now - self.ttl.as_secs() as i64;
+        nonces.retain(|_, seen| *seen >= expired);
+        if nonces.contains_key(nonce) {
+            return false;
+        }
+        nonces.insert(nonce.to_string(), timestamp);
+        true
+    }
+}
+
+fn system_clock() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+/// 回调防重放配置，clone后共享同一个nonce记录
+#[derive(Clone)]
+pub(crate) struct ReplayGuard {
+    pub(crate) tolerance: Duration,
+    pub(crate) clock: fn() -> i64,
+    pub(crate) nonce_store: Option<Arc<dyn NonceStore>>,
+}
+
+impl Debug for ReplayGuard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReplayGuard")
+            .field("tolerance", &self.tolerance)
+            .field("nonce_store", &self.nonce_store.is_some())
+            .finish()
+    }
+}
+
+impl Default for ReplayGuard {
+    fn default() -> Self {
+        Self {
+            tolerance: DEFAULT_NOTIFY_TOLERANCE,
+            clock: system_clock,
+            nonce_store: None,
+        }
+    }
+}
+
+impl ReplayGuard {
+    /// 验证签名前检查时间戳，超出容忍范围返回`PayError::NotificationExpired`
+    pub(crate) fn check_timestamp(&self, timestamp: &str) -> Result<i64, PayError> {
+        let timestamp: i64 = timestamp
+            .parse()
+            .map_err(|_| PayError::VerifyError(format!("invalid timestamp: {}", timestamp)))?;
+        let now = (self.clock)();
+        if now.abs_diff(timestamp) > self.tolerance.as_secs() {
+            return Err(PayError::NotificationExpired { timestamp, now });
+        }
+        Ok(timestamp)
+    }
+
+    /// 签名验证通过后记录nonce，重复的nonce返回`PayError::NotificationReplayed`
+    pub(crate) fn record_nonce(&self, nonce: &str, timestamp: i64) -> Result<(), PayError> {
+        match &self.nonce_store {
+            Some(store) if !store.insert(nonce, timestamp, (self.clock)()) => {
+                Err(PayError::NotificationReplayed(nonce.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PayError;
+    use crate::replay::{MemoryNonceStore, NonceStore, ReplayGuard};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_replay_guard() {
+        let guard = ReplayGuard {
+            clock: || 1705066800,
+            nonce_store: Some(Arc::new(MemoryNonceStore::default())),
+            ..Default::default()
+        };
+        assert_eq!(guard.check_timestamp("1705066785").unwrap(), 1705066785);
+        assert_eq!(guard.check_timestamp("1705067100").unwrap(), 1705067100);
+        assert!(matches!(
+            guard.check_timestamp("1705066499"),
+            Err(PayError::NotificationExpired {
+                timestamp: 1705066499,
+                now: 1705066800
+            })
+        ));
+        assert!(matches!(
+            guard.check_timestamp("abc"),
+            Err(PayError::VerifyError(_))
+        ));
+        guard.record_nonce("nonce", 1705066785).unwrap();
+        assert!(matches!(
+            guard.record_nonce("nonce", 1705066785),
+            Err(PayError::NotificationReplayed(nonce)) if nonce == "nonce"
+        ));
+    }
+
+    #[test]
+    fn test_memory_nonce_store_expire() {
+        let store = MemoryNonceStore::new(Duration::from_secs(300));
+        assert!(store.insert("a", 1000, 1000));
+        assert!(!store.insert("a", 1000, 1200));
+        assert!(store.insert("b", 1400, 1400));
+        assert!(store.insert("a", 1000, 1400));
+    }
+
+    #[test]
+    fn test_memory_nonce_store_future_timestamp() {
+        let store = MemoryNonceStore::new(Duration::from_secs(300));
+        assert!(store.insert("a", 800, 1000));
+        assert!(store.insert("b", 1300, 1000));
+        assert!(!store.insert("a", 800, 1000));
+    }
+}
@@ -1,6 +1,7 @@
+use reqwest::header::HeaderMap;
 use std::fmt::{Display, Formatter};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HttpMethod {
     GET,
     POST,
@@ -9,18 +10,33 @@ pub enum HttpMethod {
     PATCH,
 }
 
-impl Display for HttpMethod {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+impl HttpMethod {
+    /// 大写的请求方法，与签名串第一行一致
+    pub fn as_str(&self) -> &'static str {
         match self {
-            HttpMethod::GET => write!(f, "GET"),
-            HttpMethod::POST => write!(f, "POST"),
-            HttpMethod::PUT => write!(f, "PUT"),
-            HttpMethod::DELETE => write!(f, "DELETE"),
-            HttpMethod::PATCH => write!(f, "PATCH"),
+            HttpMethod::GET => "GET",
+            HttpMethod::POST => "POST",
+            HttpMethod::PUT => "PUT",
+            HttpMethod::DELETE => "DELETE",
+            HttpMethod::PATCH => "PATCH",
         }
     }
 }
 
-unsafe impl Send for HttpMethod {}
+impl Display for HttpMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
-unsafe impl Sync for HttpMethod {}
+/// 签名后、未发送的完整请求，由[WechatPay::build_request](crate::pay::WechatPay::build_request)生成，
+/// 用于契约测试中比对实际发往微信的内容
+#[derive(Debug, Clone)]
+pub struct PreparedRequest {
+    pub method: HttpMethod,
+    /// 带域名的完整请求地址
+    pub url: String,
+    /// 包含`Authorization`在内的全部请求头
+    pub headers: HeaderMap,
+    pub body: String,
+}
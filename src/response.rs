@@ -1,9 +1,26 @@
+use crate::error::PayError;
+use crate::model::{
+    Amount, Currency, FapiaoBuyerInformation, Fen, PayScorePostDiscount, PayScorePostPayment,
+    PayScoreRiskFund, PayScoreTimeRange, PayerInfo, Promotion, ReceiverType, RelationType,
+    TradeState,
+};
+use crate::util;
+use chrono::{DateTime, FixedOffset, TimeZone};
+use rsa::pkcs8::DecodePublicKey;
+use rsa::RsaPublicKey;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 pub trait ResponseTrait: DeserializeOwned {}
 
-#[derive(Debug, Deserialize)]
+/// 无内容的响应，例如关闭订单返回的204
+impl ResponseTrait for () {}
+
+/// 忽略响应体内容，只关心请求是否成功
+impl ResponseTrait for serde::de::IgnoredAny {}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct NativeResponse {
     pub code: Option<String>,
     pub message: Option<String>,
@@ -11,9 +28,18 @@ pub struct NativeResponse {
     pub code_url: Option<String>,
 }
 
+/// 幂等下单结果，商户订单号已被使用时返回查询到的原订单
+#[derive(Debug)]
+pub enum IdempotentPayResponse<R> {
+    /// 本次请求新建了订单
+    Created(R),
+    /// 微信返回`OUT_TRADE_NO_USED`或`ORDERPAID`，订单此前已创建
+    Existing(Box<OrderQueryResponse>),
+}
+
 impl ResponseTrait for NativeResponse {}
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct JsapiResponse {
     pub code: Option<String>,
     pub message: Option<String>,
@@ -35,7 +61,7 @@ pub struct SignData {
 
 impl ResponseTrait for JsapiResponse {}
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AppResponse {
     pub code: Option<String>,
     pub message: Option<String>,
@@ -47,7 +73,7 @@ pub struct AppResponse {
 
 impl ResponseTrait for AppResponse {}
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct MicroResponse {
     pub code: Option<String>,
     pub message: Option<String>,
@@ -59,7 +85,7 @@ pub struct MicroResponse {
 
 impl ResponseTrait for MicroResponse {}
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct H5Response {
     pub code: Option<String>,
     pub message: Option<String>,
@@ -70,7 +96,24 @@ pub struct H5Response {
 
 impl ResponseTrait for H5Response {}
 
-#[derive(Debug, Clone, Deserialize)]
+impl H5Response {
+    /// 在h5_url后追加URL编码的`redirect_url`参数，支付完成后浏览器返回return_to指定的商户页面。
+    /// 微信会校验发起H5支付的页面域名与商户平台配置的H5支付域名一致，
+    /// return_to也需使用该域名，否则会提示"商家参数格式有误"
+    pub fn redirect_url(&self, return_to: &str) -> Option<String> {
+        self.h5_url.as_ref().map(|h5_url| {
+            let separator = if h5_url.contains('?') { '&' } else { '?' };
+            format!(
+                "{}{}redirect_url={}",
+                h5_url,
+                separator,
+                util::url_encode(return_to)
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncryptCertificate {
     pub algorithm: String,
     pub nonce: String,
@@ -78,7 +121,7 @@ pub struct EncryptCertificate {
     pub ciphertext: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Certificate {
     pub serial_no: String,
     pub effective_time: String,
@@ -86,17 +129,1146 @@ pub struct Certificate {
     pub encrypt_certificate: EncryptCertificate,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CertificateResponse {
     pub data: Option<Vec<Certificate>>,
 }
 
 impl ResponseTrait for CertificateResponse {}
 
-#[derive(Debug, Deserialize)]
+/// 解密后的微信支付平台证书
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformCertificate {
+    ///【证书序列号】
+    pub serial_no: String,
+    ///【证书启用时间】
+    pub effective_time: String,
+    ///【证书弃用时间】
+    pub expire_time: String,
+    ///【证书内容】 PEM格式的平台证书
+    pub certificate: String,
+}
+
+impl PlatformCertificate {
+    /// 从证书中解析出平台公钥
+    pub fn public_key(&self) -> Result<RsaPublicKey, PayError> {
+        use x509_cert::der::{DecodePem, Encode};
+        let cert = x509_cert::Certificate::from_pem(self.certificate.as_bytes())
+            .map_err(|e| PayError::CertificateError(e.to_string()))?;
+        let der = cert
+            .tbs_certificate
+            .subject_public_key_info
+            .to_der()
+            .map_err(|e| PayError::CertificateError(e.to_string()))?;
+        RsaPublicKey::from_public_key_der(&der)
+            .map_err(|e| PayError::CertificateError(e.to_string()))
+    }
+
+    /// 证书序列号
+    pub fn serial_no(&self) -> &str {
+        &self.serial_no
+    }
+
+    /// 证书启用时间
+    pub fn not_before(&self) -> Result<DateTime<FixedOffset>, PayError> {
+        parse_certificate_time(&self.effective_time)
+    }
+
+    /// 证书弃用时间
+    pub fn not_after(&self) -> Result<DateTime<FixedOffset>, PayError> {
+        parse_certificate_time(&self.expire_time)
+    }
+
+    /// 证书在指定时间是否处于有效期内，启用、弃用时间无法解析时视为无效
+    pub fn is_valid_at<Tz: TimeZone>(&self, at: &DateTime<Tz>) -> bool {
+        match (self.not_before(), self.not_after()) {
+            (Ok(not_before), Ok(not_after)) => not_before <= *at && *at < not_after,
+            _ => false,
+        }
+    }
+}
+
+fn parse_certificate_time(time: &str) -> Result<DateTime<FixedOffset>, PayError> {
+    DateTime::parse_from_rfc3339(time)
+        .map_err(|e| PayError::CertificateError(format!("invalid time {}: {}", time, e)))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct UploadResponse {
     pub code: Option<String>,
     pub message: Option<String>,
     pub media_id: Option<String>,
 }
 impl ResponseTrait for UploadResponse {}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrderQueryResponse {
+    pub code: Option<String>,
+    pub message: Option<String>,
+    ///【应用ID】
+    pub appid: Option<String>,
+    ///【商户号】
+    pub mchid: Option<String>,
+    ///【商户订单号】
+    pub out_trade_no: Option<String>,
+    ///【微信支付订单号】
+    pub transaction_id: Option<String>,
+    ///【交易类型】 JSAPI、NATIVE、APP、MICROPAY、MWEB、FACEPAY
+    pub trade_type: Option<String>,
+    ///【交易状态】 SUCCESS、REFUND、NOTPAY、CLOSED、REVOKED、USERPAYING、PAYERROR
+    pub trade_state: Option<TradeState>,
+    ///【交易状态描述】
+    pub trade_state_desc: Option<String>,
+    ///【银行类型】
+    pub bank_type: Option<String>,
+    ///【附加数据】
+    pub attach: Option<String>,
+    ///【支付完成时间】
+    pub success_time: Option<String>,
+    ///【支付者】
+    pub payer: Option<PayerInfo>,
+    ///【订单金额】
+    pub amount: Option<Amount>,
+    ///【优惠功能】 使用代金券等优惠时返回
+    pub promotion_detail: Option<Vec<Promotion>>,
+}
+
+impl ResponseTrait for OrderQueryResponse {}
+
+/// 合单子单金额
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CombineSubOrderAmount {
+    ///【标价金额】 子单金额，单位为分
+    pub total_amount: Fen,
+    ///【标价币种】
+    pub currency: Option<Currency>,
+    ///【用户支付金额】 子单用户实际支付的金额
+    pub payer_amount: Option<Fen>,
+    ///【用户支付币种】
+    pub payer_currency: Option<Currency>,
+}
+
+/// 合单查询返回的子单，各子单的交易状态相互独立
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CombineSubOrderResponse {
+    ///【子单商户号】
+    pub mchid: String,
+    ///【二级商户号】
+    pub sub_mchid: Option<String>,
+    ///【子单商户订单号】
+    pub out_trade_no: String,
+    ///【微信支付订单号】
+    pub transaction_id: Option<String>,
+    ///【交易类型】 JSAPI、NATIVE、APP、MWEB
+    pub trade_type: Option<String>,
+    ///【交易状态】 SUCCESS、REFUND、NOTPAY、CLOSED、PAYERROR
+    pub trade_state: TradeState,
+    ///【银行类型】
+    pub bank_type: Option<String>,
+    ///【附加数据】
+    pub attach: Option<String>,
+    ///【支付完成时间】
+    pub success_time: Option<String>,
+    ///【订单金额】
+    pub amount: CombineSubOrderAmount,
+    ///【优惠功能】 使用代金券等优惠时返回
+    pub promotion_detail: Option<Vec<Promotion>>,
+}
+
+/// 合单查询订单
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CombineOrderResponse {
+    ///【合单商户appid】
+    pub combine_appid: String,
+    ///【合单商户号】
+    pub combine_mchid: String,
+    ///【合单商户订单号】
+    pub combine_out_trade_no: String,
+    ///【子单信息】
+    pub sub_orders: Vec<CombineSubOrderResponse>,
+    ///【支付者】
+    pub combine_payer_info: Option<PayerInfo>,
+}
+
+impl ResponseTrait for CombineOrderResponse {}
+
+/// 付款码支付同步返回的支付结果，与查询订单相同。
+/// trade_state为USERPAYING（用户输入密码中）时需要轮询查询订单确认结果
+pub type CodepayResponse = OrderQueryResponse;
+
+/// 退款状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum RefundStatus {
+    /// 退款成功
+    Success,
+    /// 退款关闭
+    Closed,
+    /// 退款处理中
+    Processing,
+    /// 退款异常
+    Abnormal,
+    /// 未知状态，原样保留
+    Other(String),
+}
+
+impl From<String> for RefundStatus {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "SUCCESS" => RefundStatus::Success,
+            "CLOSED" => RefundStatus::Closed,
+            "PROCESSING" => RefundStatus::Processing,
+            "ABNORMAL" => RefundStatus::Abnormal,
+            _ => RefundStatus::Other(value),
+        }
+    }
+}
+
+impl From<RefundStatus> for String {
+    fn from(value: RefundStatus) -> Self {
+        match value {
+            RefundStatus::Success => "SUCCESS".to_string(),
+            RefundStatus::Closed => "CLOSED".to_string(),
+            RefundStatus::Processing => "PROCESSING".to_string(),
+            RefundStatus::Abnormal => "ABNORMAL".to_string(),
+            RefundStatus::Other(value) => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefundResponseAmount {
+    ///【订单金额】 订单总金额，单位为分
+    pub total: Fen,
+    ///【退款金额】 退款标价金额，单位为分
+    pub refund: Fen,
+    ///【用户支付金额】 现金支付金额，单位为分
+    pub payer_total: Option<Fen>,
+    ///【用户退款金额】 退款给用户的金额，单位为分，不包含所有优惠券金额
+    pub payer_refund: Option<Fen>,
+    ///【应结退款金额】 去掉非充值代金券退款金额后的退款金额，单位为分
+    pub settlement_refund: Option<Fen>,
+    ///【应结订单金额】 应结订单金额=订单金额-免充值代金券金额，单位为分
+    pub settlement_total: Option<Fen>,
+    ///【优惠退款金额】 优惠退款金额，单位为分
+    pub discount_refund: Option<Fen>,
+    ///【退款出资账户及金额】 退款需要从指定账户出资时返回
+    pub from: Option<Vec<RefundFundsFrom>>,
+    ///【退款币种】 CNY：人民币
+    pub currency: Option<Currency>,
+}
+
+/// 退款出资的账户类型及金额
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefundFundsFrom {
+    ///【出资账户类型】 AVAILABLE：可用余额 UNAVAILABLE：不可用余额
+    pub account: String,
+    ///【出资金额】 对应账户出资金额，单位为分
+    pub amount: Fen,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefundResponse {
+    ///【微信支付退款单号】 微信支付退款单号
+    pub refund_id: String,
+    ///【商户退款单号】 商户系统内部的退款单号
+    pub out_refund_no: String,
+    ///【微信支付订单号】 微信支付交易订单号
+    pub transaction_id: Option<String>,
+    ///【商户订单号】 原支付交易对应的商户订单号
+    pub out_trade_no: Option<String>,
+    ///【退款渠道】 ORIGINAL：原路退款 BALANCE：退回到余额 OTHER_BALANCE：原账户异常退到其他余额账户 OTHER_BANKCARD：原银行卡异常退到其他银行卡
+    pub channel: Option<String>,
+    ///【退款入账账户】 取当前退款单的退款入账方
+    pub user_received_account: Option<String>,
+    ///【退款成功时间】 退款成功时间，当退款状态为退款成功时有返回
+    pub success_time: Option<String>,
+    ///【退款创建时间】 退款受理时间
+    pub create_time: Option<String>,
+    ///【退款状态】 SUCCESS：退款成功 CLOSED：退款关闭 PROCESSING：退款处理中 ABNORMAL：退款异常
+    pub status: RefundStatus,
+    ///【资金账户】 UNSETTLED：未结算资金 AVAILABLE：可用余额 UNAVAILABLE：不可用余额 OPERATION：运营户 BASIC：基本账户
+    pub funds_account: Option<String>,
+    ///【金额信息】 金额详细信息
+    pub amount: RefundResponseAmount,
+}
+
+impl ResponseTrait for RefundResponse {}
+
+/// 申请交易账单、资金账单返回的下载信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BillResponse {
+    ///【哈希类型】 原始账单（gzip需要解压缩）的摘要值类型，对应哈希值，目前是SHA1
+    pub hash_type: String,
+    ///【哈希值】 原始账单（gzip需要解压缩）的摘要值，用于校验文件的完整性
+    pub hash_value: String,
+    ///【账单下载地址】 供下一步请求账单文件的下载地址，该地址5min内有效
+    pub download_url: String,
+    ///【加密密钥】 加密账单文件使用的加密密钥，密钥用商户证书的公钥进行加密，仅加密账单返回
+    pub encrypt_key: Option<String>,
+    ///【随机字符串】 加密账单文件使用的随机字符串，仅加密账单返回
+    pub nonce: Option<String>,
+}
+
+impl ResponseTrait for BillResponse {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfitSharingResponse {
+    ///【子商户号】 服务商模式下返回
+    pub sub_mchid: Option<String>,
+    ///【微信订单号】 微信支付订单号
+    pub transaction_id: String,
+    ///【商户分账单号】 商户系统内部的分账单号
+    pub out_order_no: String,
+    ///【微信分账单号】 微信分账单号，微信支付系统返回的唯一标识
+    pub order_id: String,
+    ///【分账单状态】 PROCESSING：处理中 FINISHED：分账完成
+    pub state: String,
+    ///【分账接收方列表】 分账接收方列表
+    pub receivers: Option<Vec<ProfitSharingReceiverResult>>,
+}
+
+/// 分账结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum ProfitSharingResult {
+    /// 待分账
+    Pending,
+    /// 分账成功
+    Success,
+    /// 已关闭
+    Closed,
+    /// 未知结果，原样保留
+    Other(String),
+}
+
+impl From<String> for ProfitSharingResult {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "PENDING" => ProfitSharingResult::Pending,
+            "SUCCESS" => ProfitSharingResult::Success,
+            "CLOSED" => ProfitSharingResult::Closed,
+            _ => ProfitSharingResult::Other(value),
+        }
+    }
+}
+
+impl From<ProfitSharingResult> for String {
+    fn from(value: ProfitSharingResult) -> Self {
+        match value {
+            ProfitSharingResult::Pending => "PENDING".to_string(),
+            ProfitSharingResult::Success => "SUCCESS".to_string(),
+            ProfitSharingResult::Closed => "CLOSED".to_string(),
+            ProfitSharingResult::Other(value) => value,
+        }
+    }
+}
+
+/// 分账失败原因
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum ProfitSharingFailReason {
+    /// 分账接收账户异常
+    AccountAbnormal,
+    /// 分账关系已解除
+    NoRelation,
+    /// 高风险接收方
+    ReceiverHighRisk,
+    /// 接收方未实名
+    ReceiverRealNameNotVerified,
+    /// 分账权限已解除
+    NoAuth,
+    /// 接收方已达收款限额
+    ReceiverReceiptLimit,
+    /// 分出方账户异常
+    PayerAccountAbnormal,
+    /// 描述参数设置失败
+    InvalidRequest,
+    /// 未知原因，原样保留
+    Other(String),
+}
+
+impl From<String> for ProfitSharingFailReason {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "ACCOUNT_ABNORMAL" => ProfitSharingFailReason::AccountAbnormal,
+            "NO_RELATION" => ProfitSharingFailReason::NoRelation,
+            "RECEIVER_HIGH_RISK" => ProfitSharingFailReason::ReceiverHighRisk,
+            "RECEIVER_REAL_NAME_NOT_VERIFIED" => {
+                ProfitSharingFailReason::ReceiverRealNameNotVerified
+            }
+            "NO_AUTH" => ProfitSharingFailReason::NoAuth,
+            "RECEIVER_RECEIPT_LIMIT" => ProfitSharingFailReason::ReceiverReceiptLimit,
+            "PAYER_ACCOUNT_ABNORMAL" => ProfitSharingFailReason::PayerAccountAbnormal,
+            "INVALID_REQUEST" => ProfitSharingFailReason::InvalidRequest,
+            _ => ProfitSharingFailReason::Other(value),
+        }
+    }
+}
+
+impl From<ProfitSharingFailReason> for String {
+    fn from(value: ProfitSharingFailReason) -> Self {
+        match value {
+            ProfitSharingFailReason::AccountAbnormal => "ACCOUNT_ABNORMAL".to_string(),
+            ProfitSharingFailReason::NoRelation => "NO_RELATION".to_string(),
+            ProfitSharingFailReason::ReceiverHighRisk => "RECEIVER_HIGH_RISK".to_string(),
+            ProfitSharingFailReason::ReceiverRealNameNotVerified => {
+                "RECEIVER_REAL_NAME_NOT_VERIFIED".to_string()
+            }
+            ProfitSharingFailReason::NoAuth => "NO_AUTH".to_string(),
+            ProfitSharingFailReason::ReceiverReceiptLimit => "RECEIVER_RECEIPT_LIMIT".to_string(),
+            ProfitSharingFailReason::PayerAccountAbnormal => "PAYER_ACCOUNT_ABNORMAL".to_string(),
+            ProfitSharingFailReason::InvalidRequest => "INVALID_REQUEST".to_string(),
+            ProfitSharingFailReason::Other(value) => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfitSharingReceiverResult {
+    ///【分账金额】 分账金额，单位为分
+    pub amount: Fen,
+    ///【分账描述】 分账的原因描述
+    pub description: String,
+    ///【分账接收方类型】 MERCHANT_ID：商户号 PERSONAL_OPENID：个人openid
+    #[serde(rename = "type")]
+    pub receiver_type: ReceiverType,
+    ///【分账接收方账号】 分账接收方账号
+    pub account: String,
+    ///【分账结果】 PENDING：待分账 SUCCESS：分账成功 CLOSED：已关闭
+    pub result: ProfitSharingResult,
+    ///【分账失败原因】 分账结果为CLOSED时返回
+    pub fail_reason: Option<ProfitSharingFailReason>,
+    ///【分账明细单号】 微信分账明细单号，每笔分账业务执行的明细单号
+    pub detail_id: Option<String>,
+    ///【分账创建时间】 遵循rfc3339标准格式
+    pub create_time: Option<String>,
+    ///【分账完成时间】 遵循rfc3339标准格式
+    pub finish_time: Option<String>,
+}
+
+impl ResponseTrait for ProfitSharingResponse {}
+
+/// 添加、删除分账接收方的返回结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfitSharingReceiverResponse {
+    ///【子商户号】 服务商模式下返回
+    pub sub_mchid: Option<String>,
+    ///【分账接收方类型】 MERCHANT_ID：商户号 PERSONAL_OPENID：个人openid PERSONAL_SUB_OPENID：个人sub_openid
+    #[serde(rename = "type")]
+    pub receiver_type: ReceiverType,
+    ///【分账接收方账号】 分账接收方账号
+    pub account: String,
+    ///【分账接收方全称】 添加接收方时返回的密文，可以使用`rsa_decrypt_field`解密
+    pub name: Option<String>,
+    ///【与分账方的关系类型】 添加接收方时返回
+    pub relation_type: Option<RelationType>,
+    ///【自定义的分账关系】 添加接收方时返回
+    pub custom_relation: Option<String>,
+}
+
+impl ResponseTrait for ProfitSharingReceiverResponse {}
+
+/// 订单剩余待分金额
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfitSharingAmountResponse {
+    ///【微信订单号】 微信支付订单号
+    pub transaction_id: String,
+    ///【订单剩余待分金额】 订单剩余待分金额，单位为分
+    pub unsplit_amount: Fen,
+}
+
+impl ResponseTrait for ProfitSharingAmountResponse {}
+
+/// 分账回退结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum ProfitSharingReturnResult {
+    /// 处理中
+    Processing,
+    /// 已成功
+    Success,
+    /// 已失败
+    Failed,
+    /// 未知结果，原样保留
+    Other(String),
+}
+
+impl From<String> for ProfitSharingReturnResult {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "PROCESSING" => ProfitSharingReturnResult::Processing,
+            "SUCCESS" => ProfitSharingReturnResult::Success,
+            "FAILED" => ProfitSharingReturnResult::Failed,
+            _ => ProfitSharingReturnResult::Other(value),
+        }
+    }
+}
+
+impl From<ProfitSharingReturnResult> for String {
+    fn from(value: ProfitSharingReturnResult) -> Self {
+        match value {
+            ProfitSharingReturnResult::Processing => "PROCESSING".to_string(),
+            ProfitSharingReturnResult::Success => "SUCCESS".to_string(),
+            ProfitSharingReturnResult::Failed => "FAILED".to_string(),
+            ProfitSharingReturnResult::Other(value) => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfitSharingReturnResponse {
+    ///【子商户号】 服务商模式下返回
+    pub sub_mchid: Option<String>,
+    ///【微信分账单号】 原发起分账请求时，微信返回的微信分账单号
+    pub order_id: String,
+    ///【商户分账单号】 原发起分账请求时使用的商户分账单号
+    pub out_order_no: String,
+    ///【商户回退单号】 商户系统内部的回退单号
+    pub out_return_no: String,
+    ///【微信回退单号】 微信分账回退单号，微信系统返回的唯一标识
+    pub return_id: String,
+    ///【回退商户号】 只能对原分账请求中成功分给商户接收方进行回退
+    pub return_mchid: String,
+    ///【回退金额】 需要从分账接收方回退的金额，单位为分
+    pub amount: Fen,
+    ///【回退描述】 分账回退的原因描述
+    pub description: String,
+    ///【回退结果】 PROCESSING：处理中 SUCCESS：已成功 FAILED：已失败
+    pub result: ProfitSharingReturnResult,
+    ///【失败原因】 回退结果为FAILED时返回，ACCOUNT_ABNORMAL：分账接收方账户异常 TIME_OUT_CLOSED：超时关单 PAYER_ACCOUNT_ABNORMAL：原分账分出方账户异常 INVALID_REQUEST：描述参数设置失败
+    pub fail_reason: Option<String>,
+    ///【创建时间】 遵循rfc3339标准格式
+    pub create_time: String,
+    ///【完成时间】 遵循rfc3339标准格式
+    pub finish_time: Option<String>,
+}
+
+impl ResponseTrait for ProfitSharingReturnResponse {}
+
+/// 支付分订单状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum PayScoreState {
+    /// 商户已创建服务订单
+    Created,
+    /// 服务订单进行中
+    Doing,
+    /// 服务订单完成
+    Done,
+    /// 商户取消服务订单
+    Revoked,
+    /// 服务订单已失效
+    Expired,
+    /// 未知状态，原样保留
+    Other(String),
+}
+
+impl From<String> for PayScoreState {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "CREATED" => PayScoreState::Created,
+            "DOING" => PayScoreState::Doing,
+            "DONE" => PayScoreState::Done,
+            "REVOKED" => PayScoreState::Revoked,
+            "EXPIRED" => PayScoreState::Expired,
+            _ => PayScoreState::Other(value),
+        }
+    }
+}
+
+impl From<PayScoreState> for String {
+    fn from(value: PayScoreState) -> Self {
+        match value {
+            PayScoreState::Created => "CREATED".to_string(),
+            PayScoreState::Doing => "DOING".to_string(),
+            PayScoreState::Done => "DONE".to_string(),
+            PayScoreState::Revoked => "REVOKED".to_string(),
+            PayScoreState::Expired => "EXPIRED".to_string(),
+            PayScoreState::Other(value) => value,
+        }
+    }
+}
+
+/// 创建、查询支付分订单返回的服务订单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayScoreResponse {
+    ///【应用ID】 调用接口提交的公众账号ID
+    pub appid: String,
+    ///【商户号】 调用接口提交的商户号
+    pub mchid: String,
+    ///【商户服务订单号】 调用接口提交的商户服务订单号
+    pub out_order_no: String,
+    ///【服务ID】 调用该接口提交的服务ID
+    pub service_id: String,
+    ///【服务信息】 用于介绍本订单所提供的服务
+    pub service_introduction: String,
+    ///【服务订单状态】 CREATED：商户已创建服务订单 DOING：服务订单进行中 DONE：服务订单完成 REVOKED：商户取消服务订单 EXPIRED：服务订单已失效
+    pub state: PayScoreState,
+    ///【订单状态说明】 USER_CONFIRM：用户确认 MCH_COMPLETE：商户完结 USER_PAYING：待支付 USER_PAID：已支付
+    pub state_description: Option<String>,
+    ///【后付费项目】 后付费项目列表
+    pub post_payments: Option<Vec<PayScorePostPayment>>,
+    ///【后付费商户优惠】 后付费商户优惠列表
+    pub post_discounts: Option<Vec<PayScorePostDiscount>>,
+    ///【订单风险金】 订单风险金信息
+    pub risk_fund: PayScoreRiskFund,
+    ///【服务时间段】 服务时间范围
+    pub time_range: PayScoreTimeRange,
+    ///【商户数据包】 商户数据包
+    pub attach: Option<String>,
+    ///【商户回调地址】 商户接收用户确认订单和付款成功回调通知的地址
+    pub notify_url: Option<String>,
+    ///【微信支付服务订单号】 微信支付服务订单号，每个微信支付服务订单号与商户号下对应的商户服务订单号一一对应
+    pub order_id: Option<String>,
+    ///【跳转微信侧小程序订单数据】 need_user_confirm为true时返回，用于小程序调起确认订单页
+    pub package: Option<String>,
+    ///【商户收款总金额】 金额，单位为分，完结订单后返回
+    pub total_amount: Option<Fen>,
+    ///【是否需要收款】 true：已完成订单需要收款 false：不需要收款
+    pub need_collection: Option<bool>,
+    ///【用户标识】 用户在商户对应appid下的唯一标识
+    pub openid: Option<String>,
+}
+
+impl ResponseTrait for PayScoreResponse {}
+
+/// 取消支付分订单返回的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayScoreCancelResponse {
+    ///【应用ID】 调用接口提交的公众账号ID
+    pub appid: String,
+    ///【商户号】 调用接口提交的商户号
+    pub mchid: String,
+    ///【商户服务订单号】 调用接口提交的商户服务订单号
+    pub out_order_no: String,
+    ///【服务ID】 调用该接口提交的服务ID
+    pub service_id: String,
+    ///【微信支付服务订单号】 微信支付服务订单号
+    pub order_id: String,
+    ///【服务订单状态】 取消成功后为REVOKED，微信未返回时为None
+    pub state: Option<PayScoreState>,
+}
+
+impl ResponseTrait for PayScoreCancelResponse {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferBatchResponse {
+    ///【商家批次单号】 商户系统内部的商家批次单号
+    pub out_batch_no: String,
+    ///【微信批次单号】 微信批次单号，微信商家转账系统返回的唯一标识
+    pub batch_id: String,
+    ///【批次创建时间】 批次受理成功时返回，遵循rfc3339标准格式
+    pub create_time: String,
+    ///【批次状态】 ACCEPTED：已受理 PROCESSING：转账中 FINISHED：已完成 CLOSED：已关闭
+    pub batch_status: Option<TransferBatchStatus>,
+}
+
+impl ResponseTrait for TransferBatchResponse {}
+
+/// 转账批次状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum TransferBatchStatus {
+    /// 已受理，批次已受理成功，若发起批量转账的30分钟后，转账批次单仍处于该状态，可能原因是商户账户余额不足等
+    Accepted,
+    /// 转账中，已开始处理批次内的转账明细单
+    Processing,
+    /// 已完成，批次内的所有转账明细单都已处理完成
+    Finished,
+    /// 已关闭，可查询具体的批次关闭原因确认
+    Closed,
+    /// 未知状态，原样保留
+    Other(String),
+}
+
+impl From<String> for TransferBatchStatus {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "ACCEPTED" => TransferBatchStatus::Accepted,
+            "PROCESSING" => TransferBatchStatus::Processing,
+            "FINISHED" => TransferBatchStatus::Finished,
+            "CLOSED" => TransferBatchStatus::Closed,
+            _ => TransferBatchStatus::Other(value),
+        }
+    }
+}
+
+impl From<TransferBatchStatus> for String {
+    fn from(value: TransferBatchStatus) -> Self {
+        match value {
+            TransferBatchStatus::Accepted => "ACCEPTED".to_string(),
+            TransferBatchStatus::Processing => "PROCESSING".to_string(),
+            TransferBatchStatus::Finished => "FINISHED".to_string(),
+            TransferBatchStatus::Closed => "CLOSED".to_string(),
+            TransferBatchStatus::Other(value) => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferBatchInfo {
+    ///【商户号】 微信支付分配的商户号
+    pub mchid: String,
+    ///【商家批次单号】 商户系统内部的商家批次单号
+    pub out_batch_no: String,
+    ///【微信批次单号】 微信批次单号，微信商家转账系统返回的唯一标识
+    pub batch_id: String,
+    ///【商户appid】 申请商户号的appid或商户号绑定的appid
+    pub appid: Option<String>,
+    ///【批次状态】 ACCEPTED：已受理 PROCESSING：转账中 FINISHED：已完成 CLOSED：已关闭
+    pub batch_status: TransferBatchStatus,
+    ///【批次类型】 API：API方式发起 WEB：页面方式发起
+    pub batch_type: Option<String>,
+    ///【批次名称】 该笔批量转账的名称
+    pub batch_name: Option<String>,
+    ///【批次备注】 批次备注
+    pub batch_remark: Option<String>,
+    ///【批次关闭原因】 如果批次单状态为"CLOSED"（已关闭），则有关闭原因
+    pub close_reason: Option<String>,
+    ///【转账总金额】 转账金额单位为"分"
+    pub total_amount: Fen,
+    ///【转账总笔数】 一个转账批次单最多发起三千笔转账
+    pub total_num: i32,
+    ///【批次创建时间】 遵循rfc3339标准格式
+    pub create_time: Option<String>,
+    ///【批次更新时间】 遵循rfc3339标准格式
+    pub update_time: Option<String>,
+    ///【转账成功金额】 转账成功的金额，单位为"分"
+    pub success_amount: Option<Fen>,
+    ///【转账成功笔数】 转账成功的笔数
+    pub success_num: Option<i32>,
+    ///【转账失败金额】 转账失败的金额，单位为"分"
+    pub fail_amount: Option<Fen>,
+    ///【转账失败笔数】 转账失败的笔数
+    pub fail_num: Option<i32>,
+    ///【转账场景ID】 指定的转账场景ID
+    pub transfer_scene_id: Option<String>,
+}
+
+/// 转账明细单状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum TransferDetailStatus {
+    /// 初始态，系统转账校验中
+    Init,
+    /// 待确认，待商户确认，符合免密条件时系统会自动扭转为转账中
+    WaitPay,
+    /// 转账中，正在处理中，转账结果尚未明确
+    Processing,
+    /// 转账成功
+    Success,
+    /// 转账失败，需要确认失败原因后再决定是否重新发起转账
+    Fail,
+    /// 未知状态，原样保留
+    Other(String),
+}
+
+impl From<String> for TransferDetailStatus {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "INIT" => TransferDetailStatus::Init,
+            "WAIT_PAY" => TransferDetailStatus::WaitPay,
+            "PROCESSING" => TransferDetailStatus::Processing,
+            "SUCCESS" => TransferDetailStatus::Success,
+            "FAIL" => TransferDetailStatus::Fail,
+            _ => TransferDetailStatus::Other(value),
+        }
+    }
+}
+
+impl From<TransferDetailStatus> for String {
+    fn from(value: TransferDetailStatus) -> Self {
+        match value {
+            TransferDetailStatus::Init => "INIT".to_string(),
+            TransferDetailStatus::WaitPay => "WAIT_PAY".to_string(),
+            TransferDetailStatus::Processing => "PROCESSING".to_string(),
+            TransferDetailStatus::Success => "SUCCESS".to_string(),
+            TransferDetailStatus::Fail => "FAIL".to_string(),
+            TransferDetailStatus::Other(value) => value,
+        }
+    }
+}
+
+/// 通过商家明细单号查询明细单的结果，收款用户姓名已用商户私钥解密
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferDetailResponse {
+    ///【商户号】 微信支付分配的商户号
+    pub mchid: String,
+    ///【商家批次单号】 商户系统内部的商家批次单号
+    pub out_batch_no: String,
+    ///【微信批次单号】 微信批次单号，微信商家转账系统返回的唯一标识
+    pub batch_id: String,
+    ///【商户appid】 申请商户号的appid或商户号绑定的appid
+    pub appid: Option<String>,
+    ///【商家明细单号】 商户系统内部区分转账批次单下不同转账明细单的唯一标识
+    pub out_detail_no: String,
+    ///【微信明细单号】 微信支付系统内部区分转账批次单下不同转账明细单的唯一标识
+    pub detail_id: String,
+    ///【明细状态】 INIT：初始态 WAIT_PAY：待确认 PROCESSING：转账中 SUCCESS：转账成功 FAIL：转账失败
+    pub detail_status: TransferDetailStatus,
+    ///【转账金额】 转账金额单位为"分"
+    pub transfer_amount: Fen,
+    ///【转账备注】 单条转账备注（微信用户会收到该备注）
+    pub transfer_remark: Option<String>,
+    ///【明细失败原因】 如果转账失败则有失败原因，例如ACCOUNT_FROZEN：该用户账户被冻结
+    pub fail_reason: Option<String>,
+    ///【收款用户openid】 商户appid下，某用户的openid
+    pub openid: Option<String>,
+    ///【收款用户姓名】 收款方真实姓名
+    pub user_name: Option<String>,
+    ///【转账发起时间】 遵循rfc3339标准格式
+    pub initiate_time: Option<String>,
+    ///【明细更新时间】 遵循rfc3339标准格式
+    pub update_time: Option<String>,
+}
+
+impl ResponseTrait for TransferDetailResponse {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferDetailBrief {
+    ///【微信明细单号】 微信支付系统内部区分转账批次单下不同转账明细单的唯一标识
+    pub detail_id: String,
+    ///【商家明细单号】 商户系统内部区分转账批次单下不同转账明细单的唯一标识
+    pub out_detail_no: String,
+    ///【明细状态】 INIT：初始态 WAIT_PAY：待确认 PROCESSING：转账中 SUCCESS：转账成功 FAIL：转账失败
+    pub detail_status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferBatchQueryResponse {
+    ///【请求资源起始位置】 该次请求资源（转账明细单）的起始位置
+    pub offset: Option<u32>,
+    ///【最大资源条数】 该次请求可返回的最大资源（转账明细单）条数
+    pub limit: Option<u32>,
+    ///【转账批次单】 转账批次单基本信息
+    pub transfer_batch: TransferBatchInfo,
+    ///【转账明细单列表】 need_query_detail为true时返回
+    pub transfer_detail_list: Option<Vec<TransferDetailBrief>>,
+}
+
+impl ResponseTrait for TransferBatchQueryResponse {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CouponStockResponse {
+    ///【批次号】 微信为每个代金券批次分配的唯一ID
+    pub stock_id: String,
+    ///【创建时间】 创建时间，遵循rfc3339标准格式
+    pub create_time: String,
+}
+
+impl ResponseTrait for CouponStockResponse {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartCouponStockResponse {
+    ///【批次号】 微信为每个代金券批次分配的唯一ID
+    pub stock_id: String,
+    ///【生效时间】 生效时间，遵循rfc3339标准格式
+    pub start_time: String,
+}
+
+impl ResponseTrait for StartCouponStockResponse {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendCouponResponse {
+    ///【代金券id】 发放给用户的代金券id
+    pub coupon_id: String,
+}
+
+impl ResponseTrait for SendCouponResponse {}
+
+/// 代金券批次状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum CouponStockStatus {
+    /// 未激活
+    Unactivated,
+    /// 审核中
+    Audit,
+    /// 运行中
+    Running,
+    /// 已停止
+    Stoped,
+    /// 暂停发放
+    Paused,
+    /// 未知状态，原样保留
+    Other(String),
+}
+
+impl From<String> for CouponStockStatus {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "unactivated" => CouponStockStatus::Unactivated,
+            "audit" => CouponStockStatus::Audit,
+            "running" => CouponStockStatus::Running,
+            "stoped" => CouponStockStatus::Stoped,
+            "paused" => CouponStockStatus::Paused,
+            _ => CouponStockStatus::Other(value),
+        }
+    }
+}
+
+impl From<CouponStockStatus> for String {
+    fn from(value: CouponStockStatus) -> Self {
+        match value {
+            CouponStockStatus::Unactivated => "unactivated".to_string(),
+            CouponStockStatus::Audit => "audit".to_string(),
+            CouponStockStatus::Running => "running".to_string(),
+            CouponStockStatus::Stoped => "stoped".to_string(),
+            CouponStockStatus::Paused => "paused".to_string(),
+            CouponStockStatus::Other(value) => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CouponStockInfo {
+    ///【批次号】 微信为每个代金券批次分配的唯一ID
+    pub stock_id: String,
+    ///【创建批次的商户号】 批次创建方商户号
+    pub stock_creator_mchid: String,
+    ///【批次名称】 批次名称
+    pub stock_name: String,
+    ///【批次状态】 unactivated：未激活 audit：审核中 running：运行中 stoped：已停止 paused：暂停发放
+    pub status: CouponStockStatus,
+    ///【创建时间】 遵循rfc3339标准格式
+    pub create_time: String,
+    ///【使用说明】 批次描述信息
+    pub description: Option<String>,
+    ///【满减券批次使用规则】 普通发券批次特定信息
+    pub stock_use_rule: Option<Value>,
+    ///【可用开始时间】 遵循rfc3339标准格式
+    pub available_begin_time: String,
+    ///【可用结束时间】 遵循rfc3339标准格式
+    pub available_end_time: String,
+    ///【已发券数量】 已发券数量
+    pub distributed_coupons: u64,
+    ///【是否无资金流】 ture：是 false：否
+    pub no_cash: bool,
+    ///【激活批次的时间】 遵循rfc3339标准格式
+    pub start_time: Option<String>,
+    ///【终止批次的时间】 遵循rfc3339标准格式
+    pub stop_time: Option<String>,
+    ///【是否单品优惠】 true：是 false：否
+    pub singleitem: Option<bool>,
+    ///【批次类型】 NORMAL：代金券批次 DISCOUNT_CUT：立减与折扣 OTHER：其他
+    pub stock_type: String,
+}
+
+impl ResponseTrait for CouponStockInfo {}
+
+/// 投诉单状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum ComplaintState {
+    /// 待处理
+    Pending,
+    /// 处理中
+    Processing,
+    /// 已处理完成
+    Processed,
+    /// 未知状态，原样保留
+    Other(String),
+}
+
+impl From<String> for ComplaintState {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "PENDING" => ComplaintState::Pending,
+            "PROCESSING" => ComplaintState::Processing,
+            "PROCESSED" => ComplaintState::Processed,
+            _ => ComplaintState::Other(value),
+        }
+    }
+}
+
+impl From<ComplaintState> for String {
+    fn from(value: ComplaintState) -> Self {
+        match value {
+            ComplaintState::Pending => "PENDING".to_string(),
+            ComplaintState::Processing => "PROCESSING".to_string(),
+            ComplaintState::Processed => "PROCESSED".to_string(),
+            ComplaintState::Other(value) => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplaintInfo {
+    ///【投诉单号】 投诉单对应的投诉单号
+    pub complaint_id: String,
+    ///【投诉时间】 投诉时间，遵循rfc3339标准格式
+    pub complaint_time: String,
+    ///【投诉详情】 投诉的具体描述
+    pub complaint_detail: String,
+    ///【投诉单状态】 PENDING：待处理 PROCESSING：处理中 PROCESSED：已处理完成
+    pub complaint_state: ComplaintState,
+    ///【被诉商户号】 投诉单对应的被诉商户号
+    pub complainted_mchid: Option<String>,
+    ///【投诉人联系方式】 查询时已使用商户私钥解密为明文
+    pub payer_phone: Option<String>,
+    ///【投诉单是否已全额退款】 投诉单下所有订单是否已全部全额退款
+    pub complaint_full_refunded: Option<bool>,
+    ///【投诉资料列表】 用户上传的投诉相关资料
+    pub complaint_media_list: Option<Vec<Value>>,
+    ///【投诉单关联订单信息】 投诉单关联订单信息
+    pub complaint_order_info: Option<Vec<Value>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplaintListResponse {
+    ///【用户投诉信息详情】 用户投诉信息详情
+    pub data: Vec<ComplaintInfo>,
+    ///【分页大小】 设置该次请求返回的最大投诉条数
+    pub limit: Option<u32>,
+    ///【分页开始位置】 该次请求的分页开始位置
+    pub offset: Option<u32>,
+    ///【投诉总条数】 投诉总条数
+    pub total_count: Option<u32>,
+}
+
+impl ResponseTrait for ComplaintListResponse {}
+
+/// 开具电子发票的结果，微信受理后返回202无内容，开票结果通过回调或查询获取
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FapiaoResponse {
+    ///【发票申请单号】
+    pub fapiao_apply_id: String,
+}
+
+impl ResponseTrait for FapiaoBuyerInformation {}
+
+/// 网页授权通过code换取的access_token及用户openid，需要开启`oauth`
+#[cfg(feature = "oauth")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthResponse {
+    ///【网页授权接口调用凭证】 与基础支持中的access_token不同
+    pub access_token: String,
+    ///【凭证超时时间】 单位为秒
+    pub expires_in: u64,
+    ///【刷新凭证】 用于刷新access_token
+    pub refresh_token: String,
+    ///【用户唯一标识】 JSAPI下单使用的openid
+    pub openid: String,
+    ///【授权作用域】 snsapi_base或snsapi_userinfo
+    pub scope: String,
+    ///【是否为快照页模式虚拟账号】 为1时openid不能用于支付
+    pub is_snapshotuser: Option<u8>,
+    ///【用户统一标识】 绑定了开放平台时返回
+    pub unionid: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{Currency, Fen, PaymentNotification, TradeState};
+    use crate::response::{
+        H5Response, JsapiResponse, OrderQueryResponse, ProfitSharingResponse, RefundResponse,
+        RefundStatus, TransferDetailResponse,
+    };
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use serde_json::Value;
+
+    /// 去掉序列化结果中值为null的字段，微信返回的JSON中不存在的字段反序列化为None后会序列化为null
+    fn strip_null(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                map.retain(|_, v| !v.is_null());
+                map.values_mut().for_each(strip_null);
+            }
+            Value::Array(values) => values.iter_mut().for_each(strip_null),
+            _ => {}
+        }
+    }
+
+    fn assert_round_trip<T: Serialize + DeserializeOwned>(payload: &str) {
+        let response: T = serde_json::from_str(payload).unwrap();
+        let mut value = serde_json::to_value(&response).unwrap();
+        strip_null(&mut value);
+        assert_eq!(value, serde_json::from_str::<Value>(payload).unwrap());
+        let again: T = serde_json::from_value(value.clone()).unwrap();
+        let mut again = serde_json::to_value(&again).unwrap();
+        strip_null(&mut again);
+        assert_eq!(again, value);
+    }
+
+    #[test]
+    fn test_refund_status() {
+        let cases = [
+            ("SUCCESS", RefundStatus::Success),
+            ("CLOSED", RefundStatus::Closed),
+            ("PROCESSING", RefundStatus::Processing),
+            ("ABNORMAL", RefundStatus::Abnormal),
+            ("REFUNDING", RefundStatus::Other("REFUNDING".to_string())),
+        ];
+        for (raw, expected) in cases {
+            let status: RefundStatus = serde_json::from_str(&format!(r#""{}""#, raw)).unwrap();
+            assert_eq!(status, expected);
+        }
+    }
+
+    #[test]
+    fn test_order_query_promotion_detail() {
+        let payload = include_str!("../testdata/order_query_promotion.json");
+        let response: OrderQueryResponse = serde_json::from_str(payload).unwrap();
+        let promotion_detail = response.promotion_detail.unwrap();
+        assert_eq!(promotion_detail.len(), 1);
+        assert_eq!(promotion_detail[0].scope.as_deref(), Some("SINGLE"));
+        assert_eq!(promotion_detail[0].wechatpay_contribute, Some(Fen(10)));
+        assert_eq!(response.amount.unwrap().payer_total, Some(Fen(90)));
+    }
+
+    #[test]
+    fn test_order_query_not_pay() {
+        let response: OrderQueryResponse = serde_json::from_str(
+            r#"{"amount":{"payer_currency":"CNY","total":1},"appid":"wxd678efh567hg6787","mchid":"1230000109","out_trade_no":"1217752501201407033233368018","promotion_detail":[],"scene_info":{"device_id":""},"trade_state":"NOTPAY","trade_state_desc":"订单未支付"}"#,
+        )
+        .unwrap();
+        assert_eq!(response.trade_state, Some(TradeState::NotPay));
+        let amount = response.amount.unwrap();
+        assert_eq!(amount.total, Fen(1));
+        assert_eq!(amount.currency, Currency::CNY);
+        assert_eq!(amount.payer_total, None);
+        let response: OrderQueryResponse =
+            serde_json::from_str(r#"{"amount":{},"trade_state":"NOTPAY"}"#).unwrap();
+        assert_eq!(response.amount.unwrap().total, Fen(0));
+    }
+
+    #[test]
+    fn test_response_round_trip() {
+        assert_round_trip::<OrderQueryResponse>(include_str!(
+            "../testdata/order_query_promotion.json"
+        ));
+        assert_round_trip::<PaymentNotification>(include_str!(
+            "../testdata/order_query_promotion.json"
+        ));
+        assert_round_trip::<JsapiResponse>(
+            r#"{"prepay_id":"wx201410272009395522657a690389285100"}"#,
+        );
+        assert_round_trip::<RefundResponse>(
+            r#"{"refund_id":"50000000382019052709732678859","out_refund_no":"1217752501201407033233368019","transaction_id":"1217752501201407033233368018","out_trade_no":"1217752501201407033233368018","channel":"ORIGINAL","user_received_account":"招商银行信用卡0403","create_time":"2020-12-01T16:18:12+08:00","status":"REFUNDING","funds_account":"AVAILABLE","amount":{"total":1,"refund":1,"from":[],"payer_total":1,"payer_refund":1,"settlement_refund":1,"settlement_total":1,"discount_refund":0,"currency":"CNY"}}"#,
+        );
+        assert_round_trip::<ProfitSharingResponse>(
+            r#"{"transaction_id":"4208450740201411110007820472","out_order_no":"P20150806125346","order_id":"3008450740201411110007820472","state":"FINISHED","receivers":[{"amount":100,"description":"分给商户A","type":"MERCHANT_ID","account":"86693852","result":"SUCCESS","detail_id":"36011111111111111111111","create_time":"2015-05-20T13:29:35.120+08:00"},{"amount":50,"description":"分给个人B","type":"PERSONAL_OPENID","account":"oUpF8uMuAJO_M2pxb1Q9zNjWeS6o","result":"CLOSED","fail_reason":"NO_RELATION"}]}"#,
+        );
+        assert_round_trip::<TransferDetailResponse>(
+            r#"{"mchid":"1230000109","out_batch_no":"plfk2020042013","batch_id":"1030000071100999991182020050700019480001","appid":"wxd678efh567hg6787","out_detail_no":"x23zy545Bd5436","detail_id":"1040000071100999991182020050700019500100","detail_status":"SUCCESS","transfer_amount":200000,"transfer_remark":"2020年4月报销","openid":"o-MYE42l80oelYMDE34nYD456Xoy","initiate_time":"2015-05-20T13:29:35.120+08:00","update_time":"2015-05-20T13:29:35.120+08:00"}"#,
+        );
+    }
+
+    #[test]
+    fn test_h5_redirect_url() {
+        let response: H5Response = serde_json::from_str(
+            r#"{"h5_url":"https://wx.tenpay.com/cgi-bin/mmpayweb-bin/checkmweb?prepay_id=wx2016121516420242444321ca0631331346&package=1405458241"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            response
+                .redirect_url("https://www.example.com/order?id=1&from=h5")
+                .as_deref(),
+            Some("https://wx.tenpay.com/cgi-bin/mmpayweb-bin/checkmweb?prepay_id=wx2016121516420242444321ca0631331346&package=1405458241&redirect_url=https%3A%2F%2Fwww.example.com%2Forder%3Fid%3D1%26from%3Dh5")
+        );
+        let response: H5Response = serde_json::from_str("{}").unwrap();
+        assert_eq!(response.redirect_url("https://www.example.com"), None);
+    }
+}
@@ -0,0 +1,111 @@
+//! GET请求（查询订单、查询退款、下载账单等）的重试策略，遇到连接错误、超时或5xx时按指数退避重试，
+//! 遇到429时按`Retry-After`等待
+use crate::error::PayError;
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::time::Duration;
+
+/// 重试策略，默认不重试
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// 最大重试次数，不含首次请求
+    pub max_retries: u32,
+    /// 首次重试的延迟，之后每次翻倍
+    pub base_delay: Duration,
+    /// 单次重试的最大延迟
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32, base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            max_retries,
+            base_delay,
+            max_delay,
+        }
+    }
+
+    /// 不重试
+    pub fn none() -> Self {
+        Self::new(0, Duration::from_millis(100), Duration::from_secs(2))
+    }
+
+    /// 第attempt次重试（从0开始）前的等待时间，在退避时间的[1/2, 1]之间随机
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let half = backoff / 2;
+        half + rand::thread_rng().gen_range(Duration::ZERO..=half)
+    }
+
+    /// 被限频（429）后第attempt次重试前的等待时间，响应头带`Retry-After`时按其等待，否则同[delay](Self::delay)
+    pub(crate) fn rate_limit_delay(&self, attempt: u32, headers: &HeaderMap) -> Duration {
+        retry_after(headers).unwrap_or_else(|| self.delay(attempt))
+    }
+
+    /// 连接失败、超时可以重试
+    pub(crate) fn is_retryable_error(error: &PayError) -> bool {
+        match error {
+            PayError::Timeout(_) => true,
+            PayError::Network(e) => e.is_connect(),
+            _ => false,
+        }
+    }
+}
+
+/// 解析秒数形式的`Retry-After`，HTTP日期形式返回None
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::retry::{retry_after, RetryPolicy};
+    use reqwest::header::{HeaderMap, RETRY_AFTER};
+    use std::time::Duration;
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RetryPolicy::new(5, Duration::from_millis(100), Duration::from_millis(500));
+        for _ in 0..20 {
+            let delay = policy.delay(0);
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100));
+            let delay = policy.delay(2);
+            assert!(delay >= Duration::from_millis(200) && delay <= Duration::from_millis(400));
+            let delay = policy.delay(10);
+            assert!(delay >= Duration::from_millis(250) && delay <= Duration::from_millis(500));
+        }
+        assert_eq!(RetryPolicy::default().max_retries, 0);
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, "3".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(3)));
+        let policy = RetryPolicy::new(1, Duration::from_millis(10), Duration::from_millis(20));
+        assert_eq!(policy.rate_limit_delay(0, &headers), Duration::from_secs(3));
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&headers), None);
+        assert!(policy.rate_limit_delay(0, &headers) <= Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::error::PayError;
+use crate::mock::TEST_PRIVATE_KEY;
+use crate::mock::{
+    bytes_response, header, json_response, response, run, test_wechat_pay, MockServer,
+};
+use crate::model::{AccountType, BillType};
+use crate::response::BillResponse;
+use crate::util;
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes256Gcm, KeyInit};
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::{Oaep, RsaPrivateKey, RsaPublicKey};
+
+const BILL: &str =
+    "交易时间,公众账号ID,商户号\n`2018-06-08 10:34:56,`wxd678efh567hg6787,`1230000109\n";
+const TRADE_BILL_GZIP: &[u8] = include_bytes!("../../testdata/trade_bill.csv.gz");
+
+#[test]
+pub fn test_trade_bill() {
+    let server = MockServer::start(vec![json_response(
+        200,
+        r#"{"hash_type":"SHA1","hash_value":"79bb0f45fc4c42234a918000b2668d689e2bde04","download_url":"https://api.mch.weixin.qq.com/v3/billdownload/file?token=xxx"}"#,
+    )]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let bill = run(wechat_pay.trade_bill("2019-06-11", Some(BillType::All), None))
+        .expect("trade_bill error");
+    assert_eq!(bill.hash_type, "SHA1");
+    assert_eq!(
+        bill.download_url,
+        "https://api.mch.weixin.qq.com/v3/billdownload/file?token=xxx"
+    );
+    let request = server.requests().remove(0);
+    assert!(request.starts_with("GET /v3/bill/tradebill?bill_date=2019-06-11&bill_type=ALL "));
+}
+
+#[test]
+pub fn test_download_bill() {
+    let server = MockServer::start(vec![response(200, &[], BILL), response(200, &[], BILL)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let mut bill = BillResponse {
+        hash_type: "SHA1".to_string(),
+        hash_value: util::sha1_hex(BILL),
+        download_url: format!("{}/v3/billdownload/file?token=xxx", server.base_url),
+        encrypt_key: None,
+        nonce: None,
+    };
+    let data = run(wechat_pay.download_bill(&bill)).expect("download_bill error");
+    assert_eq!(data, BILL.as_bytes());
+    let request = server.requests().remove(0);
+    assert!(request.starts_with("GET /v3/billdownload/file?token=xxx "));
+    assert!(header(&request, "authorization").is_none());
+
+    bill.hash_value = util::sha1_hex("tampered");
+    let result = run(wechat_pay.download_bill(&bill));
+    assert!(matches!(result, Err(PayError::BillHashMismatch { .. })));
+}
+
+#[test]
+pub fn test_download_bill_gzip() {
+    let server = MockServer::start(vec![
+        bytes_response(200, TRADE_BILL_GZIP),
+        bytes_response(200, TRADE_BILL_GZIP),
+    ]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let mut bill = BillResponse {
+        hash_type: "SHA1".to_string(),
+        hash_value: util::sha1_hex(TRADE_BILL_GZIP),
+        download_url: format!("{}/v3/billdownload/file?token=xxx", server.base_url),
+        encrypt_key: None,
+        nonce: None,
+    };
+    let data = run(wechat_pay.download_bill(&bill)).expect("download_bill error");
+    assert_eq!(data, include_bytes!("../../testdata/trade_bill.csv"));
+
+    // 摘要按压缩包计算，按解压后的内容计算的摘要不能通过校验
+    bill.hash_value = util::sha1_hex(&data);
+    let result = run(wechat_pay.download_bill(&bill));
+    assert!(matches!(result, Err(PayError::BillHashMismatch { .. })));
+}
+
+#[test]
+pub fn test_fund_flow_bill_encrypted() {
+    let aes_key = b"0123456789abcdef0123456789abcdef";
+    let nonce = "d4e5f6a7b8c9";
+    let cipher = Aes256Gcm::new(aes_key.into());
+    let encrypted = cipher
+        .encrypt(nonce.as_bytes().into(), BILL.as_bytes())
+        .unwrap();
+    let public_key = RsaPublicKey::from(RsaPrivateKey::from_pkcs8_pem(TEST_PRIVATE_KEY).unwrap());
+    let encrypt_key = public_key
+        .encrypt(&mut rand::thread_rng(), Oaep::new::<sha1::Sha1>(), aes_key)
+        .unwrap();
+    let bill_json = serde_json::json!({
+        "hash_type": "SHA1",
+        "hash_value": util::sha1_hex(&encrypted),
+        "download_url": "https://api.mch.weixin.qq.com/v3/billdownload/file?token=xxx",
+        "encrypt_key": util::base64_encode(encrypt_key),
+        "nonce": nonce,
+    });
+    let server = MockServer::start(vec![json_response(200, &bill_json.to_string())]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let mut bill = run(wechat_pay.fund_flow_bill("2019-06-11", Some(AccountType::Operation), None))
+        .expect("fund_flow_bill error");
+    let request = server.requests().remove(0);
+    assert!(request
+        .starts_with("GET /v3/bill/fundflowbill?bill_date=2019-06-11&account_type=OPERATION "));
+
+    let server = MockServer::start(vec![bytes_response(200, &encrypted)]);
+    bill.download_url = format!("{}/v3/billdownload/file?token=xxx", server.base_url);
+    let data = run(wechat_pay.download_bill(&bill)).expect("download_bill error");
+    assert_eq!(data, BILL.as_bytes());
+}
@@ -0,0 +1,129 @@
+use crate::error::PayError;
+use crate::mock::{
+    body, header, json_response, response, run, test_wechat_pay, verify_authorization, MockServer,
+};
+use crate::model::{CombineCloseSubOrder, CombineParams, CombineSubOrder, TradeState};
+
+#[test]
+pub fn test_combine_jsapi_pay() {
+    let server = MockServer::start(vec![json_response(
+        200,
+        r#"{"prepay_id":"wx201410272009395522657a690389285100"}"#,
+    )]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let mut params = CombineParams::new(
+        "P20150806125346",
+        vec![
+            CombineSubOrder::new(
+                "1900000109",
+                "20150806125346",
+                "腾讯充值中心-QQ会员充值",
+                10.into(),
+            ),
+            CombineSubOrder::new(
+                "1900000110",
+                "20150806125347",
+                "腾讯充值中心-QQ会员充值",
+                20.into(),
+            ),
+        ],
+    );
+    params.combine_payer_info = Some("oUpF8uMuAJO_M2pxb1Q9zNjWeS6o".into());
+    let result = run(wechat_pay.combine_jsapi_pay(params)).expect("combine_jsapi_pay error");
+    let sign_data = result.sign_data.unwrap();
+    assert_eq!(
+        sign_data.package,
+        "prepay_id=wx201410272009395522657a690389285100"
+    );
+    let request = server.requests().remove(0);
+    assert!(request.starts_with("POST /v3/combine-transactions/jsapi "));
+    let body: serde_json::Value = serde_json::from_str(&body(&request)).unwrap();
+    assert_eq!(body["combine_appid"], "wxd678efh567hg6787");
+    assert_eq!(body["combine_mchid"], "1230000109");
+    assert_eq!(body["notify_url"], "https://mydomain.com/notify");
+    assert!(body.get("mchid").is_none());
+    assert!(body.get("appid").is_none());
+    assert_eq!(body["sub_orders"][1]["mchid"], "1900000110");
+    assert_eq!(body["sub_orders"][1]["amount"]["total_amount"], 20);
+    assert_eq!(
+        body["combine_payer_info"]["openid"],
+        "oUpF8uMuAJO_M2pxb1Q9zNjWeS6o"
+    );
+}
+
+#[test]
+pub fn test_query_combine_order() {
+    let json = r#"{"combine_appid":"wxd678efh567hg6787","combine_mchid":"1230000109","combine_payer_info":{"openid":"oUpF8uMuAJO_M2pxb1Q9zNjWeS6o"},"sub_orders":[{"mchid":"1900000109","trade_type":"JSAPI","trade_state":"SUCCESS","bank_type":"CMC","attach":"深圳分店","success_time":"2015-05-20T13:29:35.120+08:00","transaction_id":"1009660380201506130728806387","out_trade_no":"20150806125346","amount":{"total_amount":10,"currency":"CNY","payer_amount":10,"payer_currency":"CNY"}},{"mchid":"1900000110","trade_state":"NOTPAY","out_trade_no":"20150806125347","amount":{"total_amount":20,"currency":"CNY"}}],"combine_out_trade_no":"P20150806125346"}"#;
+    let server = MockServer::start(vec![json_response(200, json)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result =
+        run(wechat_pay.query_combine_order("P20150806125346")).expect("query_combine_order error");
+    assert_eq!(result.combine_out_trade_no, "P20150806125346");
+    assert_eq!(result.sub_orders.len(), 2);
+    assert_eq!(result.sub_orders[0].trade_state, TradeState::Success);
+    assert_eq!(result.sub_orders[0].amount.payer_amount, Some(10.into()));
+    assert_eq!(result.sub_orders[1].trade_state, TradeState::NotPay);
+    assert_eq!(result.sub_orders[1].transaction_id, None);
+    let request = server.requests().remove(0);
+    let url = "/v3/combine-transactions/out-trade-no/P20150806125346";
+    assert!(request.starts_with(&format!("GET {} ", url)));
+    let authorization = header(&request, "authorization").unwrap();
+    verify_authorization(&authorization, "GET", url, "");
+}
+
+#[test]
+pub fn test_close_combine_order() {
+    let server = MockServer::start(vec![response(204, &[], "")]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let sub_order = CombineSubOrder::new(
+        "1900000109",
+        "20150806125346",
+        "腾讯充值中心-QQ会员充值",
+        10.into(),
+    );
+    let mut second = CombineCloseSubOrder::new("1900000110", "20150806125347");
+    second.sub_mchid = Some("1900000111".to_string());
+    run(wechat_pay.close_combine_order("P20150806125346", vec![(&sub_order).into(), second]))
+        .expect("close_combine_order error");
+    let request = server.requests().remove(0);
+    let url = "/v3/combine-transactions/out-trade-no/P20150806125346/close";
+    assert!(request.starts_with(&format!("POST {} ", url)));
+    let body = body(&request);
+    let authorization = header(&request, "authorization").unwrap();
+    verify_authorization(&authorization, "POST", url, &body);
+    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "combine_appid": "wxd678efh567hg6787",
+            "sub_orders": [
+                {"mchid": "1900000109", "out_trade_no": "20150806125346"},
+                {"mchid": "1900000110", "out_trade_no": "20150806125347", "sub_mchid": "1900000111"}
+            ]
+        })
+    );
+}
+
+#[test]
+pub fn test_combine_pay_invalid_out_trade_no() {
+    let server = MockServer::start(Vec::<String>::new());
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let params = CombineParams::new(
+        "P20150806125346",
+        vec![CombineSubOrder::new(
+            "1900000109",
+            "2015080612534#",
+            "腾讯充值中心-QQ会员充值",
+            10.into(),
+        )],
+    );
+    let result = run(wechat_pay.combine_jsapi_pay(params));
+    assert!(matches!(
+        result,
+        Err(PayError::InvalidParam {
+            field: "out_trade_no",
+            ..
+        })
+    ));
+    assert!(server.requests().is_empty());
+}
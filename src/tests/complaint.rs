@@ -0,0 +1,109 @@
+use crate::error::PayError;
+use crate::mock::{
+    body, header, json_response, response, run, test_wechat_pay, verify_authorization, MockServer,
+    TEST_PRIVATE_KEY,
+};
+use crate::model::ComplaintResponseParams;
+use crate::response::ComplaintState;
+use crate::util;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::{Oaep, RsaPrivateKey};
+
+#[test]
+pub fn test_query_complaints() {
+    let public_key = RsaPrivateKey::from_pkcs8_pem(TEST_PRIVATE_KEY)
+        .unwrap()
+        .to_public_key();
+    let payer_phone = util::base64_encode(
+        public_key
+            .encrypt(
+                &mut rand::thread_rng(),
+                Oaep::new::<sha1::Sha1>(),
+                b"13800138000",
+            )
+            .unwrap(),
+    );
+    let json = serde_json::json!({
+        "data": [
+            {
+                "complaint_id": "200201820200101080076610000",
+                "complaint_time": "2015-05-20T13:29:35.120+08:00",
+                "complaint_detail": "反馈一个重复扣费的问题",
+                "complaint_state": "PENDING",
+                "complainted_mchid": "1900012181",
+                "payer_phone": payer_phone,
+                "complaint_full_refunded": false
+            },
+            {
+                "complaint_id": "200201820200101080076610001",
+                "complaint_time": "2015-05-21T13:29:35.120+08:00",
+                "complaint_detail": "商品未发货",
+                "complaint_state": "PROCESSED"
+            }
+        ],
+        "limit": 5,
+        "offset": 10,
+        "total_count": 2
+    })
+    .to_string();
+    let server = MockServer::start(vec![json_response(200, &json)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result =
+        run(wechat_pay.query_complaints("2019-01-01", "2019-01-30", 5, 10, Some("1900012181")))
+            .expect("query_complaints error");
+    assert_eq!(result.total_count, Some(2));
+    assert_eq!(result.data[0].complaint_state, ComplaintState::Pending);
+    assert_eq!(result.data[0].payer_phone.as_deref(), Some("13800138000"));
+    assert_eq!(result.data[1].complaint_state, ComplaintState::Processed);
+    assert!(result.data[1].payer_phone.is_none());
+    let request = server.requests().remove(0);
+    let url = "/v3/merchant-service/complaints-v2?limit=5&offset=10&begin_date=2019-01-01&end_date=2019-01-30&complainted_mchid=1900012181";
+    assert!(request.starts_with(&format!("GET {} ", url)));
+    let authorization = header(&request, "authorization").unwrap();
+    verify_authorization(&authorization, "GET", url, "");
+}
+
+#[test]
+pub fn test_respond_complaint() {
+    let server = MockServer::start(vec![response(204, &[], ""), response(204, &[], "")]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let params = ComplaintResponseParams::new("1900012181", "已为您办理退款")
+        .with_response_images(vec!["file23578_21798531.jpg".to_string()]);
+    run(wechat_pay.respond_complaint("200201820200101080076610000", params))
+        .expect("respond_complaint error");
+    run(wechat_pay.complete_complaint("200201820200101080076610000", "1900012181"))
+        .expect("complete_complaint error");
+    let requests = server.requests();
+    let url = "/v3/merchant-service/complaints-v2/200201820200101080076610000/response";
+    assert!(requests[0].starts_with(&format!("POST {} ", url)));
+    let body_json: serde_json::Value = serde_json::from_str(&body(&requests[0])).unwrap();
+    assert_eq!(
+        body_json,
+        serde_json::json!({
+            "complainted_mchid": "1900012181",
+            "response_content": "已为您办理退款",
+            "response_images": ["file23578_21798531.jpg"]
+        })
+    );
+    let authorization = header(&requests[0], "authorization").unwrap();
+    verify_authorization(&authorization, "POST", url, &body(&requests[0]));
+    assert!(requests[1].starts_with(
+        "POST /v3/merchant-service/complaints-v2/200201820200101080076610000/complete "
+    ));
+    assert_eq!(body(&requests[1]), r#"{"complainted_mchid":"1900012181"}"#);
+}
+
+#[test]
+pub fn test_respond_complaint_error() {
+    let error = r#"{"code":"PARAM_ERROR","message":"回复内容不能为空"}"#;
+    let server = MockServer::start(vec![json_response(400, error)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result = run(wechat_pay.respond_complaint(
+        "200201820200101080076610000",
+        ComplaintResponseParams::new("1900012181", ""),
+    ));
+    assert!(matches!(
+        result,
+        Err(PayError::WechatApiError { status: 400, code, .. }) if code == "PARAM_ERROR"
+    ));
+}
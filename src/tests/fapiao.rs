@@ -0,0 +1,87 @@
+use crate::mock::{
+    body, header, json_response, response, run, test_platform_certificate, test_wechat_pay,
+    verify_authorization, MockServer, TEST_PLATFORM_KEY, TEST_PLATFORM_SERIAL,
+};
+use crate::model::{
+    FapiaoBuyerInformation, FapiaoBuyerType, FapiaoInformation, FapiaoItem, FapiaoParams,
+    FapiaoScene,
+};
+use crate::util;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::{Oaep, RsaPrivateKey};
+
+#[test]
+pub fn test_get_fapiao_user_title() {
+    let json = r#"{"type":"ORGANIZATION","name":"深圳市南山区测试企业","taxpayer_id":"202003261233701778","address":"深圳市南山区深南大道10000号","telephone":"075512345678","bank_name":"测试银行","bank_account":"139329626168","phone":"13900000000","email":"test@example.com"}"#;
+    let server = MockServer::start(vec![json_response(200, json)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result = run(wechat_pay
+        .get_fapiao_user_title("4200000444201910177461284488", FapiaoScene::WithWechatpay))
+    .expect("get_fapiao_user_title error");
+    assert_eq!(result.buyer_type, FapiaoBuyerType::Organization);
+    assert_eq!(result.taxpayer_id.as_deref(), Some("202003261233701778"));
+    let request = server.requests().remove(0);
+    let url = "/v3/new-tax-control-fapiao/user-title?fapiao_apply_id=4200000444201910177461284488&scene=WITH_WECHATPAY";
+    assert!(request.starts_with(&format!("GET {} ", url)));
+    let authorization = header(&request, "authorization").unwrap();
+    verify_authorization(&authorization, "GET", url, "");
+}
+
+#[test]
+pub fn test_create_fapiao() {
+    let server = MockServer::start(vec![response(202, &[], "")]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    wechat_pay.load_platform_certificates(vec![test_platform_certificate()]);
+    let mut buyer = FapiaoBuyerInformation::new(FapiaoBuyerType::Individual, "张三");
+    buyer.email = Some("test@example.com".to_string());
+    let mut item = FapiaoItem::new("3010101020203000000", "出行服务", 100000000, 417.into());
+    item.tax_rate = Some(600);
+    let fapiao = FapiaoInformation::new(
+        "20200701123456",
+        vec![
+            item,
+            FapiaoItem::new("3010101020203000000", "停车服务", 200000000, 583.into()),
+        ],
+    );
+    let params = FapiaoParams::new("4200000444201910177461284488", buyer, vec![fapiao]);
+    let result = run(wechat_pay.create_fapiao(params)).expect("create_fapiao error");
+    assert_eq!(result.fapiao_apply_id, "4200000444201910177461284488");
+    let request = server.requests().remove(0);
+    let url = "/v3/new-tax-control-fapiao/fapiao-applications";
+    assert!(request.starts_with(&format!("POST {} ", url)));
+    assert_eq!(
+        header(&request, "wechatpay-serial").as_deref(),
+        Some(TEST_PLATFORM_SERIAL)
+    );
+    let body = body(&request);
+    let authorization = header(&request, "authorization").unwrap();
+    verify_authorization(&authorization, "POST", url, &body);
+    let mut body: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(body.get("appid").is_none());
+    assert!(body.get("mchid").is_none());
+    let email = body["buyer_information"]["email"].take();
+    let platform_key = RsaPrivateKey::from_pkcs8_pem(TEST_PLATFORM_KEY).unwrap();
+    let email = platform_key
+        .decrypt(
+            Oaep::new::<sha1::Sha1>(),
+            &util::base64_decode(email.as_str().unwrap()).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(String::from_utf8(email).unwrap(), "test@example.com");
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "scene": "WITH_WECHATPAY",
+            "fapiao_apply_id": "4200000444201910177461284488",
+            "buyer_information": {"type": "INDIVIDUAL", "name": "张三", "email": null},
+            "fapiao_information": [{
+                "fapiao_id": "20200701123456",
+                "total_amount": 1000,
+                "items": [
+                    {"tax_code": "3010101020203000000", "goods_name": "出行服务", "quantity": 100000000, "total_amount": 417, "tax_rate": 600},
+                    {"tax_code": "3010101020203000000", "goods_name": "停车服务", "quantity": 200000000, "total_amount": 583}
+                ]
+            }]
+        })
+    );
+}
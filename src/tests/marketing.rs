@@ -0,0 +1,128 @@
+use crate::mock::{
+    body, header, json_response, run, test_wechat_pay, verify_authorization, MockServer,
+};
+use crate::model::{CouponStockParams, CouponStockUseRule, CouponUseRule, Fen, FixedNormalCoupon};
+use crate::response::CouponStockStatus;
+
+#[test]
+pub fn test_create_coupon_stock() {
+    let server = MockServer::start(vec![
+        json_response(
+            200,
+            r#"{"stock_id":"98065001","create_time":"2015-05-20T13:29:35.120+08:00"}"#,
+        ),
+        json_response(
+            200,
+            r#"{"stock_id":"98065001","start_time":"2015-05-20T13:29:35.120+08:00"}"#,
+        ),
+    ]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let params = CouponStockParams::new(
+        "微信支付代金券",
+        "2015-05-20T13:29:35+08:00",
+        "2015-06-20T13:29:35+08:00",
+        CouponStockUseRule {
+            max_coupons: 100,
+            max_amount: Fen(5000),
+            max_amount_by_day: None,
+            max_coupons_per_user: 3,
+            natural_person_limit: false,
+            prevent_api_abuse: false,
+        },
+        CouponUseRule {
+            fixed_normal_coupon: FixedNormalCoupon {
+                coupon_amount: Fen(50),
+                transaction_minimum: Fen(100),
+            },
+            goods_tag: None,
+            available_merchants: vec!["1230000109".to_string()],
+        },
+        "example_out_request_no",
+    )
+    .with_comment("零售批次");
+    let result = run(wechat_pay.create_coupon_stock(params)).expect("create_coupon_stock error");
+    assert_eq!(result.stock_id, "98065001");
+    assert_eq!(result.create_time, "2015-05-20T13:29:35.120+08:00");
+    let started = run(wechat_pay.start_coupon_stock("98065001")).expect("start_coupon_stock error");
+    assert_eq!(started.start_time, "2015-05-20T13:29:35.120+08:00");
+
+    let requests = server.requests();
+    let url = "/v3/marketing/favor/coupon-stocks";
+    assert!(requests[0].starts_with(&format!("POST {} ", url)));
+    let request_body = body(&requests[0]);
+    let json: serde_json::Value = serde_json::from_str(&request_body).unwrap();
+    assert_eq!(json["belong_merchant"], "1230000109");
+    assert_eq!(json["stock_type"], "NORMAL");
+    assert_eq!(json["no_cash"], false);
+    assert_eq!(json["comment"], "零售批次");
+    assert_eq!(
+        json["coupon_use_rule"]["fixed_normal_coupon"],
+        serde_json::json!({"coupon_amount": 50, "transaction_minimum": 100})
+    );
+    assert!(json.get("appid").is_none());
+    assert!(json.get("notify_url").is_none());
+    let authorization = header(&requests[0], "authorization").unwrap();
+    verify_authorization(&authorization, "POST", url, &request_body);
+    assert!(requests[1].starts_with("POST /v3/marketing/favor/stocks/98065001/start "));
+    assert_eq!(
+        body(&requests[1]),
+        r#"{"stock_creator_mchid":"1230000109"}"#
+    );
+}
+
+#[test]
+pub fn test_query_coupon_stock() {
+    let json = r#"{"stock_id":"98065001","stock_creator_mchid":"1230000109","stock_name":"微信支付代金券","status":"running","create_time":"2015-05-20T13:29:35.120+08:00","description":"微信支付营销","available_begin_time":"2015-05-20T13:29:35.120+08:00","available_end_time":"2015-06-20T13:29:35.120+08:00","distributed_coupons":100,"no_cash":false,"start_time":"2015-05-20T13:29:35.120+08:00","singleitem":false,"stock_type":"NORMAL"}"#;
+    let server = MockServer::start(vec![json_response(200, json)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result = run(wechat_pay.query_coupon_stock("98065001")).expect("query_coupon_stock error");
+    assert_eq!(result.status, CouponStockStatus::Running);
+    assert_eq!(result.distributed_coupons, 100);
+    let request = server.requests().remove(0);
+    let url = "/v3/marketing/favor/stocks/98065001?stock_creator_mchid=1230000109";
+    assert!(request.starts_with(&format!("GET {} ", url)));
+    let authorization = header(&request, "authorization").unwrap();
+    verify_authorization(&authorization, "GET", url, "");
+}
+
+#[test]
+pub fn test_send_coupon() {
+    let coupon = r#"{"coupon_id":"9867041"}"#;
+    let server = MockServer::start(vec![json_response(200, coupon), json_response(200, coupon)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let first = run(wechat_pay.send_coupon(
+        "9856000",
+        "oUpF8uMuAJO_M2pxb1Q9/zNjWeS6o",
+        "89560002019101000121",
+        "wxd678efh567hg6787",
+    ))
+    .expect("send_coupon error");
+    // 相同商户单据号重复请求，微信返回同一张券
+    let second = run(wechat_pay.send_coupon(
+        "9856000",
+        "oUpF8uMuAJO_M2pxb1Q9/zNjWeS6o",
+        "89560002019101000121",
+        "wxd678efh567hg6787",
+    ))
+    .expect("send_coupon error");
+    assert_eq!(first.coupon_id, "9867041");
+    assert_eq!(second.coupon_id, first.coupon_id);
+
+    let requests = server.requests();
+    let url = "/v3/marketing/favor/users/oUpF8uMuAJO_M2pxb1Q9%2FzNjWeS6o/coupons";
+    assert!(requests[0].starts_with(&format!("POST {} ", url)));
+    let request_body = body(&requests[0]);
+    let json: serde_json::Value = serde_json::from_str(&request_body).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "stock_id": "9856000",
+            "out_request_no": "89560002019101000121",
+            "appid": "wxd678efh567hg6787",
+            "stock_creator_mchid": "1230000109"
+        })
+    );
+    let authorization = header(&requests[0], "authorization").unwrap();
+    verify_authorization(&authorization, "POST", url, &request_body);
+    assert_eq!(body(&requests[1]), request_body);
+}
@@ -0,0 +1,13 @@
+//! 各接口的请求与解析测试，异步和阻塞两种实现共用同一份，通过[crate::mock::run]驱动
+mod bill;
+mod combine;
+mod complaint;
+mod fapiao;
+mod marketing;
+mod notify;
+#[cfg(feature = "oauth")]
+mod oauth;
+mod pay;
+mod payscore;
+mod profit_sharing;
+mod transfer;
@@ -0,0 +1,70 @@
+use crate::error::PayError;
+use crate::mock::{
+    certificates_json, json_response, notification_json, run, sign_notification, test_wechat_pay,
+    MockServer, TEST_PLATFORM_SERIAL,
+};
+use crate::model::{Notification, NotificationHeaders, TradeState};
+use crate::response::RefundStatus;
+
+fn notify_headers(body: &str) -> NotificationHeaders {
+    let timestamp = "1705066785";
+    let nonce = "Jh9oPZelCJIQeQ47kz4stzvDKpLEUhCX";
+    NotificationHeaders {
+        serial: TEST_PLATFORM_SERIAL.to_string(),
+        timestamp: timestamp.to_string(),
+        nonce: nonce.to_string(),
+        signature: sign_notification(timestamp, nonce, body),
+    }
+}
+
+#[test]
+pub fn test_handle_notification() {
+    let server = MockServer::start(vec![json_response(200, &certificates_json())]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let plaintext = r#"{"mchid":"1230000109","appid":"wxd678efh567hg6787","out_trade_no":"1217752501201407033233368018","transaction_id":"1217752501201407033233368018","trade_type":"NATIVE","trade_state":"SUCCESS","trade_state_desc":"支付成功","bank_type":"CMC","attach":"","success_time":"2018-06-08T10:34:56+08:00","payer":{"openid":"oUpF8uMuAJO_M2pxb1Q9zNjWeS6o"},"amount":{"total":100,"payer_total":100,"currency":"CNY","payer_currency":"CNY"}}"#;
+    let body = notification_json("TRANSACTION.SUCCESS", "transaction", plaintext);
+    let notification = run(wechat_pay.handle_notification(&notify_headers(&body), &body))
+        .expect("handle_notification error");
+    match notification {
+        Notification::Transaction(data) => {
+            assert_eq!(data.trade_state, TradeState::Success);
+            assert_eq!(data.amount.total.fen(), 100);
+        }
+        other => panic!("unexpected notification: {:?}", other),
+    }
+
+    let plaintext = r#"{"mchid":"1230000109","out_trade_no":"1217752501201407033233368018","transaction_id":"1217752501201407033233368018","out_refund_no":"1217752501201407033233368018","refund_id":"50000000382019052709732678859","refund_status":"SUCCESS","success_time":"2018-06-08T10:34:56+08:00","user_received_account":"招商银行信用卡0403","amount":{"total":999,"refund":999,"payer_total":999,"payer_refund":999}}"#;
+    let body = notification_json("REFUND.SUCCESS", "refund", plaintext);
+    let notification = run(wechat_pay.handle_notification(&notify_headers(&body), &body))
+        .expect("handle_notification error");
+    assert!(
+        matches!(notification, Notification::Refund(data) if data.refund_status == RefundStatus::Success)
+    );
+
+    let tampered = body.replace("REFUND.SUCCESS", "REFUND.CLOSED");
+    let result = run(wechat_pay.handle_notification(&notify_headers(&body), &tampered));
+    assert!(matches!(result, Err(PayError::SignatureVerifyFailed)));
+    let result = NotificationHeaders::from_header_map(&reqwest::header::HeaderMap::new());
+    assert!(matches!(result, Err(PayError::VerifyError(_))));
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+pub fn test_handle_refund_abnormal_notification() {
+    let server = MockServer::start(vec![json_response(200, &certificates_json())]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let plaintext = r#"{"mchid":"1230000109","out_trade_no":"1217752501201407033233368018","transaction_id":"1217752501201407033233368018","out_refund_no":"1217752501201407033233368019","refund_id":"50000000382019052709732678860","refund_status":"ABNORMAL","user_received_account":"招商银行信用卡0403","amount":{"total":999,"refund":500,"payer_total":899,"payer_refund":400}}"#;
+    let body = notification_json("REFUND.ABNORMAL", "refund", plaintext);
+    let notification = run(wechat_pay.handle_notification(&notify_headers(&body), &body))
+        .expect("handle_notification error");
+    match notification {
+        Notification::Refund(data) => {
+            assert_eq!(data.refund_status, RefundStatus::Abnormal);
+            assert!(data.success_time.is_none());
+            assert_eq!(data.amount.refund.fen(), 500);
+            assert_eq!(data.amount.payer_refund.fen(), 400);
+            assert_eq!(data.amount.payer_total.fen(), 899);
+        }
+        other => panic!("unexpected notification: {:?}", other),
+    }
+}
@@ -0,0 +1,34 @@
+use crate::error::PayError;
+use crate::mock::{run, test_wechat_pay, FakeTransport};
+use crate::pay::BASE_URL;
+
+#[test]
+pub fn test_get_openid() {
+    let transport = FakeTransport::new(vec![
+        (
+            200,
+            r#"{"access_token":"ACCESS_TOKEN","expires_in":7200,"refresh_token":"REFRESH_TOKEN","openid":"oUpF8uMuAJO_M2pxb1Q9zNjWeS6o","scope":"snsapi_base","is_snapshotuser":1,"unionid":"o6_bmasdasdsad6_2sgVt7hMZOPfL"}"#,
+        ),
+        (200, r#"{"errcode":40029,"errmsg":"invalid code"}"#),
+    ]);
+    let wechat_pay = test_wechat_pay(BASE_URL).with_transport(transport.clone());
+    let result = run(wechat_pay.get_openid("app&secret", "061Ab2ll2z4bNf48oKll2Bndll2Ab2lE"))
+        .expect("get_openid error");
+    assert_eq!(result.openid, "oUpF8uMuAJO_M2pxb1Q9zNjWeS6o");
+    assert_eq!(result.refresh_token, "REFRESH_TOKEN");
+    assert_eq!(result.is_snapshotuser, Some(1));
+    let request = transport.requests().remove(0);
+    assert_eq!(request.method, "GET");
+    assert_eq!(
+        request.url,
+        "https://api.weixin.qq.com/sns/oauth2/access_token?appid=wxd678efh567hg6787&secret=app%26secret&code=061Ab2ll2z4bNf48oKll2Bndll2Ab2lE&grant_type=authorization_code"
+    );
+    assert!(request.headers.get("authorization").is_none());
+
+    let result = run(wechat_pay.get_openid("secret", "used-code"));
+    assert!(matches!(
+        result,
+        Err(PayError::WechatApiError { code, message, .. })
+            if code == "40029" && message == "invalid code"
+    ));
+}
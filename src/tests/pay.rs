@@ -0,0 +1,736 @@
+use crate::error::PayError;
+use crate::mock::{
+    body, certificates_json, header, json_response, response, run, test_platform_certificate,
+    test_wechat_pay, verify_authorization, MockServer, TEST_NOTIFY_BODY, TEST_NOTIFY_NONCE,
+    TEST_NOTIFY_SIGNATURE, TEST_NOTIFY_TIMESTAMP, TEST_NOW, TEST_PLATFORM_SERIAL,
+};
+use crate::model::{
+    CodepayParams, CodepaySceneInfo, CodepayStoreInfo, InjectFields, NativeParams, ParamsTrait,
+    RefundAmount, RefundParams, TradeState,
+};
+use crate::pay::DEFAULT_USER_AGENT;
+use crate::request::HttpMethod;
+use crate::response::{IdempotentPayResponse, PlatformCertificate, RefundStatus, ResponseTrait};
+use crate::retry::RetryPolicy;
+use std::time::Duration;
+
+const ORDER_QUERY_JSON: &str = r#"{"amount":{"currency":"CNY","payer_currency":"CNY","payer_total":1,"total":1},"appid":"wxd678efh567hg6787","attach":"","bank_type":"OTHERS","mchid":"1230000109","out_trade_no":"1217752501201407033233368018","payer":{"openid":"oUpF8uMuAJO_M2pxb1Q9zNjWeS6o"},"promotion_detail":[],"success_time":"2018-06-08T10:34:56+08:00","trade_state":"SUCCESS","trade_state_desc":"支付成功","trade_type":"NATIVE","transaction_id":"1217752501201407033233368018"}"#;
+
+const CODEPAY_JSON: &str = r#"{"amount":{"currency":"CNY","payer_currency":"CNY","payer_total":1,"total":1},"appid":"wxd678efh567hg6787","attach":"","bank_type":"OTHERS","mchid":"1230000109","out_trade_no":"1217752501201407033233368018","payer":{"openid":"oUpF8uMuAJO_M2pxb1Q9zNjWeS6o"},"success_time":"2018-06-08T10:34:56+08:00","trade_state":"SUCCESS","trade_state_desc":"支付成功","trade_type":"MICROPAY","transaction_id":"1217752501201407033233368018"}"#;
+
+const REFUND_JSON: &str = r#"{"refund_id":"50000000382019052709732678859","out_refund_no":"1217752501201407033233368019","transaction_id":"1217752501201407033233368018","out_trade_no":"1217752501201407033233368018","channel":"ORIGINAL","user_received_account":"招商银行信用卡0403","create_time":"2020-12-01T16:18:12+08:00","status":"PROCESSING","funds_account":"AVAILABLE","amount":{"total":1,"refund":1,"from":[],"payer_total":1,"payer_refund":1,"settlement_refund":1,"settlement_total":1,"discount_refund":0,"currency":"CNY"}}"#;
+
+#[derive(serde::Serialize)]
+struct CustomParams {
+    out_request_no: String,
+}
+
+impl ParamsTrait for CustomParams {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    fn inject_fields(&self) -> InjectFields {
+        InjectFields {
+            appid: false,
+            mchid: true,
+            notify_url: false,
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct CustomResponse {
+    state: String,
+}
+
+impl ResponseTrait for CustomResponse {}
+
+#[test]
+pub fn test_user_agent() {
+    let server = MockServer::start(vec![response(204, &[], ""), response(204, &[], "")]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    run(wechat_pay.close_order("1217752501201407033233368018")).expect("close_order error");
+    run(wechat_pay
+        .clone()
+        .set_user_agent("my-shop/1.0 wechat-pay-rust-sdk")
+        .unwrap()
+        .close_order("1217752501201407033233368018"))
+    .expect("close_order error");
+    let requests = server.requests();
+    assert_eq!(
+        header(&requests[0], "user-agent").as_deref(),
+        Some(DEFAULT_USER_AGENT)
+    );
+    assert!(DEFAULT_USER_AGENT.starts_with("wechat-pay-rust-sdk/"));
+    assert_eq!(
+        header(&requests[1], "user-agent").as_deref(),
+        Some("my-shop/1.0 wechat-pay-rust-sdk")
+    );
+}
+
+#[test]
+pub fn test_timeout() {
+    let server = MockServer::hang();
+    let wechat_pay = test_wechat_pay(&server.base_url).set_timeout(Duration::from_millis(200));
+    let result = run(wechat_pay.certificates());
+    assert!(matches!(result, Err(PayError::Timeout(_))), "{:?}", result);
+}
+
+#[test]
+pub fn test_query_order_by_out_trade_no() {
+    let server = MockServer::start(vec![json_response(200, ORDER_QUERY_JSON)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let order = run(wechat_pay.query_order_by_out_trade_no("1217752501201407033233368018"))
+        .expect("query_order_by_out_trade_no error");
+    assert_eq!(order.trade_state, Some(TradeState::Success));
+    assert_eq!(order.amount.as_ref().unwrap().payer_total, Some(1.into()));
+    assert_eq!(order.amount.unwrap().total.fen(), 1);
+    assert_eq!(order.payer.unwrap().openid, "oUpF8uMuAJO_M2pxb1Q9zNjWeS6o");
+    let request = server.requests().remove(0);
+    assert!(request.starts_with(
+        "GET /v3/pay/transactions/out-trade-no/1217752501201407033233368018?mchid=1230000109 "
+    ));
+}
+
+#[test]
+pub fn test_get_pay_retry() {
+    let busy = r#"{"code":"SYSTEM_ERROR","message":"系统繁忙，请稍后重试"}"#;
+    let server = MockServer::start(vec![
+        json_response(503, busy),
+        json_response(500, busy),
+        json_response(200, ORDER_QUERY_JSON),
+    ]);
+    let wechat_pay = test_wechat_pay(&server.base_url).set_retry_policy(RetryPolicy::new(
+        2,
+        Duration::from_millis(10),
+        Duration::from_millis(50),
+    ));
+    let order = run(wechat_pay.query_order_by_out_trade_no("1217752501201407033233368018"))
+        .expect("query_order_by_out_trade_no error");
+    assert_eq!(order.trade_state, Some(TradeState::Success));
+    assert_eq!(server.requests().len(), 3);
+
+    let server = MockServer::start(vec![json_response(503, busy)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result = run(wechat_pay.query_order_by_out_trade_no("1217752501201407033233368018"));
+    assert!(matches!(
+        result,
+        Err(PayError::WechatApiError { status: 503, .. })
+    ));
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+pub fn test_set_proxy() {
+    let proxy = MockServer::start(vec![json_response(200, ORDER_QUERY_JSON)]);
+    let wechat_pay = test_wechat_pay("http://api.mch.weixin.invalid")
+        .set_proxy(reqwest::Proxy::all(&proxy.base_url).unwrap())
+        .unwrap();
+    let order = run(wechat_pay.query_order_by_out_trade_no("1217752501201407033233368018"))
+        .expect("query_order_by_out_trade_no error");
+    assert_eq!(order.trade_state, Some(TradeState::Success));
+    let request = proxy.requests().remove(0);
+    assert!(request.starts_with(
+        "GET http://api.mch.weixin.invalid/v3/pay/transactions/out-trade-no/1217752501201407033233368018?mchid=1230000109 "
+    ));
+}
+
+#[test]
+pub fn test_query_order_by_transaction_id() {
+    let server = MockServer::start(vec![json_response(200, ORDER_QUERY_JSON)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let order = run(wechat_pay.query_order_by_transaction_id("1217752501201407033233368018"))
+        .expect("query_order_by_transaction_id error");
+    assert_eq!(
+        order.transaction_id.as_deref(),
+        Some("1217752501201407033233368018")
+    );
+    let request = server.requests().remove(0);
+    let url = "/v3/pay/transactions/id/1217752501201407033233368018?mchid=1230000109";
+    assert!(request.starts_with(&format!("GET {} ", url)));
+    let authorization = header(&request, "authorization").unwrap();
+    verify_authorization(&authorization, "GET", url, "");
+}
+
+#[test]
+pub fn test_close_order() {
+    let server = MockServer::start(vec![response(204, &[], "")]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    run(wechat_pay.close_order("1217752501201407033233368018")).expect("close_order error");
+    let request = server.requests().remove(0);
+    assert!(request
+        .starts_with("POST /v3/pay/transactions/out-trade-no/1217752501201407033233368018/close "));
+    assert_eq!(body(&request), r#"{"mchid":"1230000109"}"#);
+}
+
+#[test]
+pub fn test_order_partner_mode() {
+    let server = MockServer::start(vec![
+        json_response(200, ORDER_QUERY_JSON),
+        response(204, &[], ""),
+        json_response(200, "{}"),
+    ]);
+    let wechat_pay = test_wechat_pay(&server.base_url).with_sub_merchant("1900000109", None);
+    run(wechat_pay.query_order_by_out_trade_no("1217752501201407033233368018"))
+        .expect("query_order_by_out_trade_no error");
+    run(wechat_pay.close_order("1217752501201407033233368018")).expect("close_order error");
+    run(wechat_pay.reverse_micropay("1217752501201407033233368018"))
+        .expect("reverse_micropay error");
+    let requests = server.requests();
+    let url = "/v3/pay/partner/transactions/out-trade-no/1217752501201407033233368018?sp_mchid=1230000109&sub_mchid=1900000109";
+    assert!(requests[0].starts_with(&format!("GET {} ", url)));
+    verify_authorization(
+        &header(&requests[0], "authorization").unwrap(),
+        "GET",
+        url,
+        "",
+    );
+    assert!(requests[1].starts_with(
+        "POST /v3/pay/partner/transactions/out-trade-no/1217752501201407033233368018/close "
+    ));
+    assert_eq!(
+        body(&requests[1]),
+        r#"{"sp_mchid":"1230000109","sub_mchid":"1900000109"}"#
+    );
+    assert!(requests[2].starts_with(
+        "POST /v3/pay/partner/transactions/out-trade-no/1217752501201407033233368018/reverse "
+    ));
+}
+
+#[test]
+pub fn test_close_order_error() {
+    let error = r#"{"code":"ORDER_CLOSED","message":"订单已关闭"}"#;
+    let server = MockServer::start(vec![response(
+        400,
+        &[
+            ("Content-Type", "application/json"),
+            ("Request-ID", "08F78BB5AF0610ACFF2B18F0C36D649A0C"),
+        ],
+        error,
+    )]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result = run(wechat_pay.close_order("1217752501201407033233368018"));
+    assert!(matches!(
+        result,
+        Err(PayError::WechatApiError { status: 400, code, request_id, .. })
+            if code == "ORDER_CLOSED"
+                && request_id.as_deref() == Some("08F78BB5AF0610ACFF2B18F0C36D649A0C")
+    ));
+}
+
+#[test]
+pub fn test_refund() {
+    let server = MockServer::start(vec![json_response(200, REFUND_JSON)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let mut params = RefundParams::new(
+        "1217752501201407033233368018",
+        "1217752501201407033233368019",
+        RefundAmount::new(1, 1),
+    );
+    params.reason = Some("商品已售完".to_string());
+    let refund = run(wechat_pay.refund(params)).expect("refund error");
+    assert_eq!(refund.refund_id, "50000000382019052709732678859");
+    assert_eq!(refund.status, RefundStatus::Processing);
+    assert_eq!(refund.amount.refund.fen(), 1);
+    let request = server.requests().remove(0);
+    assert!(request.starts_with("POST /v3/refund/domestic/refunds "));
+    // 退款请求体不注入appid、mchid、notify_url
+    let body: serde_json::Value = serde_json::from_str(&body(&request)).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "out_trade_no": "1217752501201407033233368018",
+            "out_refund_no": "1217752501201407033233368019",
+            "reason": "商品已售完",
+            "amount": {"refund": 1, "total": 1, "currency": "CNY"}
+        })
+    );
+}
+
+#[test]
+pub fn test_native_pay_inject_fields() {
+    let server = MockServer::start(vec![json_response(
+        200,
+        r#"{"code_url":"weixin://wxpay/bizpayurl?pr=p4lpSuKzz"}"#,
+    )]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result = run(wechat_pay.native_pay(NativeParams::new(
+        "Image形象店-深圳腾大-QQ公仔",
+        "1217752501201407033233368018",
+        1.into(),
+    )))
+    .expect("native_pay error");
+    assert_eq!(
+        result.code_url.as_deref(),
+        Some("weixin://wxpay/bizpayurl?pr=p4lpSuKzz")
+    );
+    let request = server.requests().remove(0);
+    let body: serde_json::Value = serde_json::from_str(&body(&request)).unwrap();
+    assert_eq!(body["appid"], "wxd678efh567hg6787");
+    assert_eq!(body["mchid"], "1230000109");
+    assert_eq!(body["notify_url"], "https://mydomain.com/notify");
+}
+
+#[test]
+pub fn test_codepay() {
+    let server = MockServer::start(vec![json_response(200, CODEPAY_JSON)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result = run(wechat_pay.codepay(
+        CodepayParams::new(
+            "Image形象店-深圳腾大-QQ公仔",
+            "1217752501201407033233368018",
+            1.into(),
+            "134567890123456789",
+        )
+        .with_scene_info(CodepaySceneInfo {
+            store_info: CodepayStoreInfo {
+                out_id: "1234".to_string(),
+            },
+            device_ip: Some("14.23.150.211".to_string()),
+        }),
+    ))
+    .expect("codepay error");
+    assert_eq!(result.trade_state, Some(TradeState::Success));
+    assert_eq!(result.trade_type.as_deref(), Some("MICROPAY"));
+    let request = server.requests().remove(0);
+    assert!(request.starts_with("POST /v3/pay/transactions/codepay "));
+    let body: serde_json::Value = serde_json::from_str(&body(&request)).unwrap();
+    assert_eq!(body["payer"]["auth_code"], "134567890123456789");
+    assert_eq!(body["scene_info"]["store_info"]["out_id"], "1234");
+    assert_eq!(body["appid"], "wxd678efh567hg6787");
+    assert_eq!(body["mchid"], "1230000109");
+    assert!(body.get("notify_url").is_none());
+}
+
+#[test]
+pub fn test_query_refund() {
+    let server = MockServer::start(vec![json_response(200, REFUND_JSON)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let refund =
+        run(wechat_pay.query_refund("1217752501201407033233368019")).expect("query_refund error");
+    assert_eq!(refund.out_refund_no, "1217752501201407033233368019");
+    assert_eq!(refund.status, RefundStatus::Processing);
+    let request = server.requests().remove(0);
+    let url = "/v3/refund/domestic/refunds/1217752501201407033233368019";
+    assert!(request.starts_with(&format!("GET {} ", url)));
+    let authorization = header(&request, "authorization").unwrap();
+    verify_authorization(&authorization, "GET", url, "");
+}
+
+#[test]
+pub fn test_refund_partner_mode() {
+    let server = MockServer::start(vec![
+        json_response(200, REFUND_JSON),
+        json_response(200, REFUND_JSON),
+    ]);
+    let wechat_pay = test_wechat_pay(&server.base_url).with_sub_merchant("1900000109", None);
+    let params = RefundParams::new(
+        "1217752501201407033233368018",
+        "1217752501201407033233368019",
+        RefundAmount::new(1, 1),
+    );
+    run(wechat_pay.refund(params)).expect("refund error");
+    run(wechat_pay.query_refund("1217752501201407033233368019")).expect("query_refund error");
+    let requests = server.requests();
+    assert!(requests[0].starts_with("POST /v3/refund/domestic/refunds "));
+    let body: serde_json::Value = serde_json::from_str(&body(&requests[0])).unwrap();
+    assert_eq!(body["sub_mchid"], "1900000109");
+    assert!(body.get("sp_mchid").is_none());
+    assert!(body.get("mchid").is_none());
+    let url = "/v3/refund/domestic/refunds/1217752501201407033233368019?sub_mchid=1900000109";
+    assert!(requests[1].starts_with(&format!("GET {} ", url)));
+    verify_authorization(
+        &header(&requests[1], "authorization").unwrap(),
+        "GET",
+        url,
+        "",
+    );
+}
+
+#[test]
+pub fn test_native_pay_partner_mode() {
+    let server = MockServer::start(vec![json_response(
+        200,
+        r#"{"code_url":"weixin://wxpay/bizpayurl?pr=p4lpSuKzz"}"#,
+    )]);
+    let wechat_pay = test_wechat_pay(&server.base_url)
+        .with_sub_merchant("1900000109", Some("wxd678efh567hg6999"));
+    run(wechat_pay.native_pay(NativeParams::new(
+        "Image形象店-深圳腾大-QQ公仔",
+        "1217752501201407033233368018",
+        1.into(),
+    )))
+    .expect("native_pay error");
+    let request = server.requests().remove(0);
+    assert!(request.starts_with("POST /v3/pay/partner/transactions/native "));
+    let body: serde_json::Value = serde_json::from_str(&body(&request)).unwrap();
+    assert_eq!(body["sp_appid"], "wxd678efh567hg6787");
+    assert_eq!(body["sp_mchid"], "1230000109");
+    assert_eq!(body["sub_appid"], "wxd678efh567hg6999");
+    assert_eq!(body["sub_mchid"], "1900000109");
+    assert!(body.get("appid").is_none());
+    assert!(body.get("mchid").is_none());
+}
+
+#[test]
+pub fn test_upload_image_error() {
+    let server = MockServer::start(vec![response(
+        400,
+        &[
+            ("Content-Type", "application/json"),
+            ("Request-ID", "08F78BB5AF0610ACFF2B18F0C36D649A0C"),
+        ],
+        r#"{"code":"PARAM_ERROR","message":"图片sha256不匹配"}"#,
+    )]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result = run(wechat_pay.upload_image(b"\x89PNG\r\n\x1a\n".to_vec(), "logo.png"));
+    assert!(matches!(
+        result,
+        Err(PayError::WechatApiError { status: 400, code, request_id, .. })
+            if code == "PARAM_ERROR"
+                && request_id.as_deref() == Some("08F78BB5AF0610ACFF2B18F0C36D649A0C")
+    ));
+}
+
+#[test]
+pub fn test_upload_image() {
+    let server = MockServer::start(vec![json_response(
+        200,
+        r#"{"media_id":"H1ihR9JUtVj-J7CJqBUY5ZOrG_Je75H-rKit7K7bxQ"}"#,
+    )]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let image = b"\x89PNG\r\n\x1a\n".to_vec();
+    let result = run(wechat_pay.upload_image(image, "logo.png")).expect("upload_image error");
+    assert_eq!(
+        result.media_id.as_deref(),
+        Some("H1ihR9JUtVj-J7CJqBUY5ZOrG_Je75H-rKit7K7bxQ")
+    );
+    let request = server.requests().remove(0);
+    assert!(request.starts_with("POST /v3/merchant/media/upload "));
+    assert!(header(&request, "content-type")
+        .unwrap()
+        .starts_with("multipart/form-data; boundary="));
+    let head = request
+        .split("\r\n\r\n")
+        .next()
+        .unwrap()
+        .to_ascii_lowercase();
+    assert_eq!(head.matches("\r\ncontent-type:").count(), 1);
+    let result = run(wechat_pay.upload_image(b"\x89PNG\r\n\x1a\n".to_vec(), "logo.gif"));
+    assert!(matches!(result, Err(PayError::UnsupportedImageFormat(_))));
+    let result = run(wechat_pay.upload_image(vec![0xFF, 0xD8, 0xFF], "logo.png"));
+    assert!(matches!(result, Err(PayError::ImageFormatMismatch { .. })));
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+pub fn test_upload_video() {
+    let server = MockServer::start(vec![json_response(
+        200,
+        r#"{"media_id":"H1ihR9JUtVj-J7CJqBUY5ZOrG_Je75H-rKit7K7bxQ"}"#,
+    )]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result =
+        run(wechat_pay.upload_video(vec![0; 1024], "intro.mp4")).expect("upload_video error");
+    assert!(result.media_id.is_some());
+    let request = server.requests().remove(0);
+    assert!(request.starts_with("POST /v3/merchant/media/video_upload "));
+    assert!(request.to_lowercase().contains("content-type: video/mp4"));
+    let result = run(wechat_pay.upload_video(vec![0; 1024], "intro.png"));
+    assert!(matches!(result, Err(PayError::UnsupportedVideoFormat(_))));
+}
+
+#[test]
+pub fn test_verify_signature_refresh_certificates() {
+    let server = MockServer::start(vec![json_response(200, &certificates_json())]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let shared = wechat_pay.clone();
+    run(wechat_pay.verify_signature(
+        TEST_PLATFORM_SERIAL,
+        TEST_NOTIFY_TIMESTAMP,
+        TEST_NOTIFY_NONCE,
+        TEST_NOTIFY_BODY,
+        TEST_NOTIFY_SIGNATURE,
+    ))
+    .expect("verify_signature error");
+    assert_eq!(server.requests().len(), 1);
+    assert!(server.requests()[0].starts_with("GET /v3/certificates "));
+    run(
+        // 第二次验签使用缓存的证书，不再请求微信
+        shared.verify_signature(
+            TEST_PLATFORM_SERIAL,
+            TEST_NOTIFY_TIMESTAMP,
+            TEST_NOTIFY_NONCE,
+            TEST_NOTIFY_BODY,
+            TEST_NOTIFY_SIGNATURE,
+        ),
+    )
+    .expect("verify_signature error");
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+pub fn test_verify_signature_expired_timestamp() {
+    let server = MockServer::start(vec![json_response(200, &certificates_json())]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let timestamp = (TEST_NOW - 3600).to_string();
+    let result = run(wechat_pay.verify_signature(
+        "UNKNOWN_SERIAL",
+        &timestamp,
+        TEST_NOTIFY_NONCE,
+        TEST_NOTIFY_BODY,
+        TEST_NOTIFY_SIGNATURE,
+    ));
+    assert!(matches!(result, Err(PayError::NotificationExpired { .. })));
+    // 过期的回调不会触发下载平台证书
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+pub fn test_verify_signature_expired_certificate() {
+    let server = MockServer::start(vec![json_response(200, &certificates_json())]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    wechat_pay.load_platform_certificates(vec![PlatformCertificate {
+        effective_time: "2020-01-01T08:00:00+08:00".to_string(),
+        expire_time: "2023-01-01T08:00:00+08:00".to_string(),
+        ..test_platform_certificate()
+    }]);
+    run(
+        // 缓存中的证书已过期，验签前先刷新平台证书
+        wechat_pay.verify_signature(
+            TEST_PLATFORM_SERIAL,
+            TEST_NOTIFY_TIMESTAMP,
+            TEST_NOTIFY_NONCE,
+            TEST_NOTIFY_BODY,
+            TEST_NOTIFY_SIGNATURE,
+        ),
+    )
+    .expect("verify_signature error");
+    assert_eq!(server.requests().len(), 1);
+    assert!(server.requests()[0].starts_with("GET /v3/certificates "));
+}
+
+#[test]
+pub fn test_custom_endpoint() {
+    let server = MockServer::start(vec![
+        json_response(200, r#"{"state":"ACCEPTED"}"#),
+        json_response(200, r#"{"state":"FINISHED"}"#),
+    ]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let params = CustomParams {
+        out_request_no: "1217752501201407033233368018".to_string(),
+    };
+    let response: CustomResponse =
+        run(wechat_pay.pay(HttpMethod::POST, "/v3/custom/endpoint", params)).expect("pay error");
+    assert_eq!(response.state, "ACCEPTED");
+    let response: CustomResponse =
+        run(wechat_pay.get_pay("/v3/custom/endpoint/1217752501201407033233368018"))
+            .expect("get_pay error");
+    assert_eq!(response.state, "FINISHED");
+
+    let requests = server.requests();
+    assert!(requests[0].starts_with("POST /v3/custom/endpoint "));
+    assert_eq!(
+        body(&requests[0]),
+        r#"{"mchid":"1230000109","out_request_no":"1217752501201407033233368018"}"#
+    );
+    let authorization = header(&requests[0], "authorization").unwrap();
+    verify_authorization(
+        &authorization,
+        "POST",
+        "/v3/custom/endpoint",
+        &body(&requests[0]),
+    );
+    assert!(requests[1].starts_with("GET /v3/custom/endpoint/1217752501201407033233368018 "));
+    for request in &requests {
+        assert_eq!(
+            header(request, "accept").as_deref(),
+            Some("application/json")
+        );
+        assert_eq!(
+            header(request, "content-type").as_deref(),
+            Some("application/json")
+        );
+    }
+}
+
+#[test]
+pub fn test_native_pay_idempotent() {
+    let server = MockServer::start(vec![
+        response(
+            400,
+            &[],
+            r#"{"code":"OUT_TRADE_NO_USED","message":"商户订单号重复"}"#,
+        ),
+        json_response(200, ORDER_QUERY_JSON),
+    ]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let params = NativeParams::new("测试支付1分", "1217752501201407033233368018", 1.into());
+    let result =
+        run(wechat_pay.native_pay_idempotent(params)).expect("native_pay_idempotent error");
+    match result {
+        IdempotentPayResponse::Existing(order) => {
+            assert_eq!(order.trade_state, Some(TradeState::Success))
+        }
+        IdempotentPayResponse::Created(_) => panic!("expected existing order"),
+    }
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].starts_with("POST /v3/pay/transactions/native "));
+    assert!(requests[1].starts_with(
+        "GET /v3/pay/transactions/out-trade-no/1217752501201407033233368018?mchid=1230000109 "
+    ));
+
+    let server = MockServer::start(vec![response(
+        400,
+        &[],
+        r#"{"code":"PARAM_ERROR","message":"参数错误"}"#,
+    )]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let params = NativeParams::new("测试支付1分", "1217752501201407033233368018", 1.into());
+    let result = run(wechat_pay.native_pay_idempotent(params));
+    assert!(matches!(&result, Err(e) if e.wechat_code() == Some("PARAM_ERROR")));
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+pub fn test_native_pay_idempotent_partner_mode() {
+    let server = MockServer::start(vec![
+        response(
+            400,
+            &[],
+            r#"{"code":"OUT_TRADE_NO_USED","message":"商户订单号重复"}"#,
+        ),
+        json_response(200, ORDER_QUERY_JSON),
+    ]);
+    let wechat_pay = test_wechat_pay(&server.base_url).with_sub_merchant("1900000109", None);
+    let params = NativeParams::new("测试支付1分", "1217752501201407033233368018", 1.into());
+    let result =
+        run(wechat_pay.native_pay_idempotent(params)).expect("native_pay_idempotent error");
+    assert!(matches!(result, IdempotentPayResponse::Existing(_)));
+    let requests = server.requests();
+    assert!(requests[0].starts_with("POST /v3/pay/partner/transactions/native "));
+    assert!(requests[1].starts_with(
+        "GET /v3/pay/partner/transactions/out-trade-no/1217752501201407033233368018?sp_mchid=1230000109&sub_mchid=1900000109 "
+    ));
+}
+
+#[test]
+pub fn test_get_weixin_not_found() {
+    let page = "<html><body>商家参数格式有误，请联系商家解决</body></html>";
+    let server = MockServer::start(vec![response(200, &[], page)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let h5_url = format!("{}/cgi-bin/mmpayweb-bin/checkmweb", server.base_url);
+    let result = run(wechat_pay.get_weixin(h5_url.as_str(), "https://mydomain.com"));
+    assert!(
+        matches!(&result, Err(PayError::WeixinNotFound { body_snippet }) if body_snippet == page),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+pub fn test_request_hook() {
+    let server = MockServer::start(vec![response(204, &[], "")]);
+    let wechat_pay = test_wechat_pay(&server.base_url).with_request_hook(|request| {
+        request
+            .headers_mut()
+            .insert("X-Correlation-Id", "trace-1217752501".parse().unwrap());
+    });
+    run(wechat_pay.close_order("1217752501201407033233368018")).expect("close_order error");
+    let request = server.requests().remove(0);
+    assert_eq!(
+        header(&request, "x-correlation-id").as_deref(),
+        Some("trace-1217752501")
+    );
+    let authorization = header(&request, "authorization").unwrap();
+    verify_authorization(
+        &authorization,
+        "POST",
+        "/v3/pay/transactions/out-trade-no/1217752501201407033233368018/close",
+        &body(&request),
+    );
+}
+
+#[test]
+pub fn test_rate_limited() {
+    let limited = r#"{"code":"FREQUENCY_LIMITED","message":"频率超限"}"#;
+    let server = MockServer::start(vec![
+        response(429, &[("Retry-After", "0")], limited),
+        json_response(200, ORDER_QUERY_JSON),
+    ]);
+    let wechat_pay = test_wechat_pay(&server.base_url).set_retry_policy(RetryPolicy::new(
+        1,
+        Duration::from_millis(10),
+        Duration::from_millis(50),
+    ));
+    let order = run(wechat_pay.query_order_by_out_trade_no("1217752501201407033233368018"))
+        .expect("query_order_by_out_trade_no error");
+    assert_eq!(order.trade_state, Some(TradeState::Success));
+    assert_eq!(server.requests().len(), 2);
+
+    let server = MockServer::start(vec![
+        response(429, &[("Retry-After", "30")], limited),
+        json_response(403, limited),
+    ]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result = run(wechat_pay.query_order_by_out_trade_no("1217752501201407033233368018"));
+    assert!(
+        matches!(result, Err(PayError::RateLimited { retry_after: Some(d) }) if d == Duration::from_secs(30))
+    );
+    let result = run(wechat_pay.close_order("1217752501201407033233368018"));
+    assert!(matches!(
+        result,
+        Err(PayError::RateLimited { retry_after: None })
+    ));
+}
+
+#[test]
+pub fn test_reverse_micropay() {
+    let server = MockServer::start(vec![
+        json_response(
+            200,
+            r#"{"appid":"wxd678efh567hg6787","mchid":"1230000109","out_trade_no":"1217752501201407033233368018"}"#,
+        ),
+        response(
+            400,
+            &[],
+            r#"{"code":"TRADE_ERROR","message":"订单已完成，不能撤销"}"#,
+        ),
+    ]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    run(wechat_pay.reverse_micropay("1217752501201407033233368018"))
+        .expect("reverse_micropay error");
+    let request = server.requests().remove(0);
+    let url = "/v3/pay/transactions/out-trade-no/1217752501201407033233368018/reverse";
+    assert!(request.starts_with(&format!("POST {} ", url)));
+    let body = body(&request);
+    assert_eq!(
+        body,
+        r#"{"appid":"wxd678efh567hg6787","mchid":"1230000109"}"#
+    );
+    let authorization = header(&request, "authorization").unwrap();
+    verify_authorization(&authorization, "POST", url, &body);
+    let result = run(wechat_pay.reverse_micropay("1217752501201407033233368018"));
+    assert!(matches!(&result, Err(e) if e.wechat_code() == Some("TRADE_ERROR")));
+}
+
+#[test]
+pub fn test_ping() {
+    let server = MockServer::start(vec![
+        json_response(200, &certificates_json()),
+        json_response(
+            401,
+            r#"{"code":"SIGN_ERROR","message":"签名错误，请检查后再试"}"#,
+        ),
+    ]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    run(wechat_pay.ping()).expect("ping error");
+    let result = run(wechat_pay.ping());
+    assert!(
+        matches!(&result, Err(PayError::AuthFailed { code, .. }) if code == "SIGN_ERROR"),
+        "{:?}",
+        result
+    );
+    assert!(server.requests()[0].starts_with("GET /v3/certificates "));
+}
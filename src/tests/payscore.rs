@@ -0,0 +1,187 @@
+use crate::error::PayError;
+use crate::mock::{
+    body, header, json_response, run, test_wechat_pay, verify_authorization, MockServer,
+};
+use crate::model::{
+    Fen, PayScoreModifyParams, PayScoreParams, PayScorePostPayment, PayScoreRiskFund,
+    PayScoreTimeRange,
+};
+use crate::response::PayScoreState;
+
+const PAYSCORE_JSON: &str = r#"{"appid":"wxd678efh567hg6787","mchid":"1230000109","out_order_no":"1234323JKHDFE1243252","service_id":"500001","service_introduction":"某某酒店","state":"CREATED","state_description":"MCH_COMPLETE","risk_fund":{"name":"ESTIMATE_ORDER_COST","amount":10000,"description":"就餐的预估费用"},"time_range":{"start_time":"20091225091010","end_time":"20091225121010"},"notify_url":"https://api.test.com","order_id":"15646546545165651651","package":"DJIOSQPYWDxsjdldeuwhdodwxasd_dDiodnwjh9we"}"#;
+
+fn payscore_params() -> PayScoreParams {
+    let mut time_range = PayScoreTimeRange::new("20091225091010");
+    time_range.end_time = Some("20091225121010".to_string());
+    let mut risk_fund = PayScoreRiskFund::new("ESTIMATE_ORDER_COST", 10000);
+    risk_fund.description = Some("就餐的预估费用".to_string());
+    PayScoreParams::new(
+        "1234323JKHDFE1243252",
+        "500001",
+        "某某酒店",
+        time_range,
+        risk_fund,
+    )
+}
+
+#[test]
+pub fn test_create_payscore_order() {
+    let server = MockServer::start(vec![json_response(200, PAYSCORE_JSON)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result = run(wechat_pay.create_payscore_order(payscore_params()))
+        .expect("create_payscore_order error");
+    assert_eq!(result.state, PayScoreState::Created);
+    assert_eq!(result.order_id.as_deref(), Some("15646546545165651651"));
+    assert_eq!(
+        result.package.as_deref(),
+        Some("DJIOSQPYWDxsjdldeuwhdodwxasd_dDiodnwjh9we")
+    );
+    assert_eq!(result.risk_fund.amount, Fen(10000));
+    let request = server.requests().remove(0);
+    assert!(request.starts_with("POST /v3/payscore/serviceorder "));
+    let body: serde_json::Value = serde_json::from_str(&body(&request)).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "appid": "wxd678efh567hg6787",
+            "notify_url": "https://mydomain.com/notify",
+            "out_order_no": "1234323JKHDFE1243252",
+            "service_id": "500001",
+            "service_introduction": "某某酒店",
+            "time_range": {
+                "start_time": "20091225091010",
+                "end_time": "20091225121010"
+            },
+            "risk_fund": {
+                "name": "ESTIMATE_ORDER_COST",
+                "amount": 10000,
+                "description": "就餐的预估费用"
+            },
+            "need_user_confirm": true
+        })
+    );
+
+    let params = payscore_params();
+    let params = PayScoreParams {
+        need_user_confirm: false,
+        ..params
+    };
+    assert!(matches!(
+        run(wechat_pay.create_payscore_order(params)),
+        Err(PayError::InvalidParam {
+            field: "openid",
+            ..
+        })
+    ));
+    let params = payscore_params().without_user_confirm("oUpF8uMuAJO_M2pxb1Q9zNjWeS6o");
+    let body: serde_json::Value =
+        serde_json::from_str(&wechat_pay.build_direct_body(&params).unwrap()).unwrap();
+    assert_eq!(body["openid"], "oUpF8uMuAJO_M2pxb1Q9zNjWeS6o");
+    assert_eq!(body["need_user_confirm"], false);
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+pub fn test_query_payscore_order() {
+    let json = PAYSCORE_JSON.replace(r#""state":"CREATED""#, r#""state":"DONE""#);
+    let server = MockServer::start(vec![json_response(200, &json)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result = run(wechat_pay.query_payscore_order("1234323JKHDFE1243252", "500001"))
+        .expect("query_payscore_order error");
+    assert_eq!(result.state, PayScoreState::Done);
+    let request = server.requests().remove(0);
+    let url = "/v3/payscore/serviceorder?out_order_no=1234323JKHDFE1243252&service_id=500001&appid=wxd678efh567hg6787";
+    assert!(request.starts_with(&format!("GET {} ", url)));
+    let authorization = header(&request, "authorization").unwrap();
+    verify_authorization(&authorization, "GET", url, "");
+}
+
+#[test]
+pub fn test_cancel_and_modify_payscore_order() {
+    let cancel_json = r#"{"appid":"wxd678efh567hg6787","mchid":"1230000109","out_order_no":"1234323JKHDFE1243252","service_id":"500001","order_id":"15646546545165651651","state":"REVOKED"}"#;
+    let modify_json = PAYSCORE_JSON
+        .replace(r#""state":"CREATED""#, r#""state":"DOING""#)
+        .replace(r#""notify_url""#, r#""total_amount":40000,"notify_url""#);
+    let server = MockServer::start(vec![
+        json_response(200, &modify_json),
+        json_response(200, cancel_json),
+    ]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let params = PayScoreModifyParams::new(
+        "500001",
+        vec![PayScorePostPayment {
+            name: "就餐费用".to_string(),
+            amount: Some(Fen(40000)),
+            description: Some("就餐人均100元".to_string()),
+            count: Some(4),
+        }],
+        40000,
+        "用户投诉",
+    );
+    let result = run(wechat_pay.modify_payscore_order("1234323JKHDFE1243252", params))
+        .expect("modify_payscore_order error");
+    assert_eq!(result.state, PayScoreState::Doing);
+    assert_eq!(result.total_amount, Some(Fen(40000)));
+    let result =
+        run(wechat_pay.cancel_payscore_order("1234323JKHDFE1243252", "500001", "用户取消"))
+            .expect("cancel_payscore_order error");
+    assert_eq!(result.state, Some(PayScoreState::Revoked));
+    assert_eq!(result.order_id, "15646546545165651651");
+
+    let requests = server.requests();
+    let url = "/v3/payscore/serviceorder/1234323JKHDFE1243252/modify";
+    assert!(requests[0].starts_with(&format!("POST {} ", url)));
+    let authorization = header(&requests[0], "authorization").unwrap();
+    verify_authorization(&authorization, "POST", url, &body(&requests[0]));
+    let body0: serde_json::Value = serde_json::from_str(&body(&requests[0])).unwrap();
+    assert_eq!(
+        body0,
+        serde_json::json!({
+            "appid": "wxd678efh567hg6787",
+            "service_id": "500001",
+            "post_payments": [{
+                "name": "就餐费用",
+                "amount": 40000,
+                "description": "就餐人均100元",
+                "count": 4
+            }],
+            "total_amount": 40000,
+            "reason": "用户投诉"
+        })
+    );
+    let url = "/v3/payscore/serviceorder/1234323JKHDFE1243252/cancel";
+    assert!(requests[1].starts_with(&format!("POST {} ", url)));
+    let authorization = header(&requests[1], "authorization").unwrap();
+    verify_authorization(&authorization, "POST", url, &body(&requests[1]));
+    let body1: serde_json::Value = serde_json::from_str(&body(&requests[1])).unwrap();
+    assert_eq!(
+        body1,
+        serde_json::json!({
+            "appid": "wxd678efh567hg6787",
+            "service_id": "500001",
+            "reason": "用户取消"
+        })
+    );
+}
+
+#[test]
+pub fn test_payscore_partner_mode() {
+    let server = MockServer::start(vec![
+        json_response(200, PAYSCORE_JSON),
+        json_response(200, PAYSCORE_JSON),
+    ]);
+    let wechat_pay = test_wechat_pay(&server.base_url)
+        .with_sub_merchant("1900000109", Some("wx8888888888888888"));
+    run(wechat_pay.create_payscore_order(payscore_params())).expect("create_payscore_order error");
+    let params = PayScoreModifyParams::new("500001", vec![], 40000, "用户投诉");
+    run(wechat_pay.modify_payscore_order("1234323JKHDFE1243252", params))
+        .expect("modify_payscore_order error");
+    // 支付分是直连商户接口，服务商模式下仍然只注入appid
+    for request in server.requests() {
+        let body: serde_json::Value = serde_json::from_str(&body(&request)).unwrap();
+        assert_eq!(body["appid"], "wxd678efh567hg6787");
+        assert!(body.get("sp_appid").is_none());
+        assert!(body.get("sub_appid").is_none());
+        assert!(body.get("sub_mchid").is_none());
+    }
+}
@@ -0,0 +1,270 @@
+use crate::error::PayError;
+use crate::mock::{
+    body, header, json_response, run, test_platform_certificate, test_wechat_pay,
+    verify_authorization, MockServer, TEST_PLATFORM_KEY, TEST_PLATFORM_SERIAL,
+};
+use crate::model::{
+    Fen, ProfitSharingParams, ProfitSharingReceiver, ProfitSharingReceiverAddParams,
+    ProfitSharingReceiverDeleteParams, ProfitSharingReturnParams, ProfitSharingUnfreezeParams,
+    ReceiverType, RelationType,
+};
+use crate::response::{ProfitSharingFailReason, ProfitSharingResult, ProfitSharingReturnResult};
+use crate::util;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::{Oaep, RsaPrivateKey};
+
+const PROFIT_SHARING_JSON: &str = r#"{"transaction_id":"4208450740201411110007820472","out_order_no":"P20150806125346","order_id":"3008450740201411110007820472","state":"PROCESSING","receivers":[]}"#;
+
+fn profit_sharing_params() -> ProfitSharingParams {
+    ProfitSharingParams::new(
+        "4208450740201411110007820472",
+        "P20150806125346",
+        vec![ProfitSharingReceiver::new(
+            ReceiverType::MerchantId,
+            "86693852",
+            888,
+            "分给商户A",
+        )],
+        true,
+    )
+}
+
+#[test]
+pub fn test_profit_sharing() {
+    let server = MockServer::start(vec![json_response(200, PROFIT_SHARING_JSON)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result =
+        run(wechat_pay.profit_sharing(profit_sharing_params())).expect("profit_sharing error");
+    assert_eq!(result.order_id, "3008450740201411110007820472");
+    assert_eq!(result.state, "PROCESSING");
+    let request = server.requests().remove(0);
+    assert!(request.starts_with("POST /v3/profitsharing/orders "));
+    let body: serde_json::Value = serde_json::from_str(&body(&request)).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "appid": "wxd678efh567hg6787",
+            "transaction_id": "4208450740201411110007820472",
+            "out_order_no": "P20150806125346",
+            "receivers": [{
+                "type": "MERCHANT_ID",
+                "account": "86693852",
+                "amount": 888,
+                "description": "分给商户A"
+            }],
+            "unfreeze_unsplit": true
+        })
+    );
+}
+
+#[test]
+pub fn test_profit_sharing_partner_mode() {
+    let server = MockServer::start(vec![json_response(200, PROFIT_SHARING_JSON)]);
+    let wechat_pay = test_wechat_pay(&server.base_url)
+        .with_sub_merchant("1900000109", Some("wxd678efh567hg6999"));
+    run(wechat_pay.profit_sharing(profit_sharing_params())).expect("profit_sharing error");
+    let request = server.requests().remove(0);
+    let body: serde_json::Value = serde_json::from_str(&body(&request)).unwrap();
+    assert_eq!(body["appid"], "wxd678efh567hg6787");
+    assert_eq!(body["sub_mchid"], "1900000109");
+    assert_eq!(body["sub_appid"], "wxd678efh567hg6999");
+    assert!(body.get("notify_url").is_none());
+}
+
+#[test]
+pub fn test_query_profit_sharing() {
+    let json = r#"{"transaction_id":"4208450740201411110007820472","out_order_no":"P20150806125346","order_id":"3008450740201411110007820472","state":"FINISHED","receivers":[{"amount":100,"description":"分给商户A","type":"MERCHANT_ID","account":"86693852","result":"SUCCESS","fail_reason":null,"detail_id":"36011111111111111111111","create_time":"2015-05-20T13:29:35.120+08:00","finish_time":"2015-05-20T13:29:35.120+08:00"},{"amount":50,"description":"分给个人B","type":"PERSONAL_OPENID","account":"oUpF8uMuAJO_M2pxb1Q9zNjWeS6o","result":"CLOSED","fail_reason":"NO_RELATION","detail_id":"36011111111111111111112","create_time":"2015-05-20T13:29:35.120+08:00"},{"amount":20,"description":"分给商户C","type":"MERCHANT_ID","account":"86693853","result":"PENDING"}]}"#;
+    let server = MockServer::start(vec![json_response(200, json)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result =
+        run(wechat_pay.query_profit_sharing("4208450740201411110007820472", "P20150806125346"))
+            .expect("query_profit_sharing error");
+    assert_eq!(result.state, "FINISHED");
+    let receivers = result.receivers.unwrap();
+    assert_eq!(receivers.len(), 3);
+    assert_eq!(receivers[0].result, ProfitSharingResult::Success);
+    assert_eq!(receivers[0].fail_reason, None);
+    assert_eq!(receivers[1].receiver_type, ReceiverType::PersonalOpenid);
+    assert_eq!(receivers[1].result, ProfitSharingResult::Closed);
+    assert_eq!(
+        receivers[1].fail_reason,
+        Some(ProfitSharingFailReason::NoRelation)
+    );
+    assert_eq!(receivers[2].result, ProfitSharingResult::Pending);
+    let request = server.requests().remove(0);
+    let url =
+        "/v3/profitsharing/orders/P20150806125346?transaction_id=4208450740201411110007820472";
+    assert!(request.starts_with(&format!("GET {} ", url)));
+    let authorization = header(&request, "authorization").unwrap();
+    verify_authorization(&authorization, "GET", url, "");
+}
+
+#[test]
+pub fn test_add_profit_sharing_receiver() {
+    let json = r#"{"sub_mchid":"1900000109","type":"MERCHANT_ID","account":"86693852","name":"hu89ohu89ohu89o","relation_type":"STORE"}"#;
+    let server = MockServer::start(vec![json_response(200, json)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    wechat_pay.load_platform_certificates(vec![test_platform_certificate()]);
+    let params = ProfitSharingReceiverAddParams::new(
+        ReceiverType::MerchantId,
+        "86693852",
+        RelationType::Store,
+    )
+    .with_name("腾讯科技有限公司");
+    let result = run(wechat_pay.add_profit_sharing_receiver(params))
+        .expect("add_profit_sharing_receiver error");
+    assert_eq!(result.account, "86693852");
+    assert_eq!(result.receiver_type, ReceiverType::MerchantId);
+    assert_eq!(result.relation_type, Some(RelationType::Store));
+    let request = server.requests().remove(0);
+    assert!(request.starts_with("POST /v3/profitsharing/receivers/add "));
+    assert_eq!(
+        header(&request, "wechatpay-serial").as_deref(),
+        Some(TEST_PLATFORM_SERIAL)
+    );
+    let body: serde_json::Value = serde_json::from_str(&body(&request)).unwrap();
+    assert_eq!(body["appid"], "wxd678efh567hg6787");
+    assert_eq!(body["type"], "MERCHANT_ID");
+    assert_eq!(body["relation_type"], "STORE");
+    let platform_key = RsaPrivateKey::from_pkcs8_pem(TEST_PLATFORM_KEY).unwrap();
+    let name = platform_key
+        .decrypt(
+            Oaep::new::<sha1::Sha1>(),
+            &util::base64_decode(body["name"].as_str().unwrap()).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(String::from_utf8(name).unwrap(), "腾讯科技有限公司");
+}
+
+#[test]
+pub fn test_delete_profit_sharing_receiver_and_unfreeze() {
+    let server = MockServer::start(vec![
+        json_response(
+            200,
+            r#"{"type":"PERSONAL_OPENID","account":"oUpF8uMuAJO_M2pxb1Q9zNjWeS6o"}"#,
+        ),
+        json_response(200, PROFIT_SHARING_JSON),
+    ]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result = run(wechat_pay.delete_profit_sharing_receiver(
+        ProfitSharingReceiverDeleteParams::new(
+            ReceiverType::PersonalOpenid,
+            "oUpF8uMuAJO_M2pxb1Q9zNjWeS6o",
+        ),
+    ))
+    .expect("delete_profit_sharing_receiver error");
+    assert_eq!(result.receiver_type, ReceiverType::PersonalOpenid);
+    assert!(result.relation_type.is_none());
+    let result = run(
+        wechat_pay.unfreeze_profit_sharing(ProfitSharingUnfreezeParams::new(
+            "4208450740201411110007820472",
+            "P20150806125346",
+            "解冻全部剩余资金",
+        )),
+    )
+    .expect("unfreeze_profit_sharing error");
+    assert_eq!(result.order_id, "3008450740201411110007820472");
+
+    let requests = server.requests();
+    assert!(requests[0].starts_with("POST /v3/profitsharing/receivers/delete "));
+    assert!(header(&requests[0], "wechatpay-serial").is_none());
+    let body0: serde_json::Value = serde_json::from_str(&body(&requests[0])).unwrap();
+    assert_eq!(
+        body0,
+        serde_json::json!({
+            "appid": "wxd678efh567hg6787",
+            "type": "PERSONAL_OPENID",
+            "account": "oUpF8uMuAJO_M2pxb1Q9zNjWeS6o"
+        })
+    );
+    assert!(requests[1].starts_with("POST /v3/profitsharing/orders/unfreeze "));
+    let body1: serde_json::Value = serde_json::from_str(&body(&requests[1])).unwrap();
+    assert_eq!(
+        body1,
+        serde_json::json!({
+            "transaction_id": "4208450740201411110007820472",
+            "out_order_no": "P20150806125346",
+            "description": "解冻全部剩余资金"
+        })
+    );
+}
+
+#[test]
+pub fn test_query_profit_sharing_amount() {
+    let json = r#"{"transaction_id":"4208450740201411110007820472","unsplit_amount":1000}"#;
+    let server = MockServer::start(vec![json_response(200, json)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result = run(wechat_pay.query_profit_sharing_amount("4208450740201411110007820472"))
+        .expect("query_profit_sharing_amount error");
+    assert_eq!(result.transaction_id, "4208450740201411110007820472");
+    assert_eq!(result.unsplit_amount, Fen(1000));
+    let request = server.requests().remove(0);
+    let url = "/v3/profitsharing/transactions/4208450740201411110007820472/amounts";
+    assert!(request.starts_with(&format!("GET {} ", url)));
+    let authorization = header(&request, "authorization").unwrap();
+    verify_authorization(&authorization, "GET", url, "");
+}
+
+#[test]
+pub fn test_return_profit_sharing() {
+    let server = MockServer::start(vec![
+        json_response(
+            200,
+            r#"{"order_id":"3008450740201411110007820472","out_order_no":"P20150806125346","out_return_no":"R20190516001","return_id":"3008450740201411110007820472","return_mchid":"86693852","amount":10,"description":"用户退款","result":"PROCESSING","create_time":"2015-05-20T13:29:35.120+08:00"}"#,
+        ),
+        json_response(
+            200,
+            r#"{"order_id":"3008450740201411110007820472","out_order_no":"P20150806125346","out_return_no":"R20190516001","return_id":"3008450740201411110007820472","return_mchid":"86693852","amount":10,"description":"用户退款","result":"FAILED","create_time":"2015-05-20T13:29:35.120+08:00","fail_reason":"ACCOUNT_ABNORMAL","finish_time":"2015-05-20T13:29:35.120+08:00"}"#,
+        ),
+    ]);
+    let wechat_pay = test_wechat_pay(&server.base_url).with_sub_merchant("1900000109", None);
+    let params = ProfitSharingReturnParams::new(
+        "P20150806125346",
+        "R20190516001",
+        "86693852",
+        10,
+        "用户退款",
+    );
+    let result =
+        run(wechat_pay.return_profit_sharing(params)).expect("return_profit_sharing error");
+    assert_eq!(result.result, ProfitSharingReturnResult::Processing);
+    assert_eq!(result.amount, Fen(10));
+    let result = run(wechat_pay.query_profit_sharing_return("P20150806125346", "R20190516001"))
+        .expect("query_profit_sharing_return error");
+    assert_eq!(result.result, ProfitSharingReturnResult::Failed);
+    assert_eq!(result.fail_reason.as_deref(), Some("ACCOUNT_ABNORMAL"));
+    assert_eq!(
+        result.finish_time.as_deref(),
+        Some("2015-05-20T13:29:35.120+08:00")
+    );
+
+    let requests = server.requests();
+    assert!(requests[0].starts_with("POST /v3/profitsharing/return-orders "));
+    let body: serde_json::Value = serde_json::from_str(&body(&requests[0])).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "sub_mchid": "1900000109",
+            "out_order_no": "P20150806125346",
+            "out_return_no": "R20190516001",
+            "return_mchid": "86693852",
+            "amount": 10,
+            "description": "用户退款"
+        })
+    );
+    let url = "/v3/profitsharing/return-orders/R20190516001?out_order_no=P20150806125346&sub_mchid=1900000109";
+    assert!(requests[1].starts_with(&format!("GET {} ", url)));
+
+    let params = ProfitSharingReturnParams {
+        out_order_no: None,
+        ..ProfitSharingReturnParams::new("", "R20190516001", "86693852", 10, "用户退款")
+    };
+    assert!(matches!(
+        run(wechat_pay.return_profit_sharing(params)),
+        Err(PayError::InvalidParam {
+            field: "out_order_no",
+            ..
+        })
+    ));
+    assert_eq!(server.requests().len(), 2);
+}
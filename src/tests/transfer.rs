@@ -0,0 +1,154 @@
+use crate::mock::{
+    body, header, json_response, run, test_platform_certificate, test_wechat_pay,
+    verify_authorization, MockServer, TEST_PLATFORM_KEY, TEST_PLATFORM_SERIAL, TEST_PRIVATE_KEY,
+};
+use crate::model::{TransferBatchParams, TransferDetail};
+use crate::response::{TransferBatchStatus, TransferDetailStatus};
+use crate::util;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::{Oaep, RsaPrivateKey};
+
+const TRANSFER_BATCH_JSON: &str = r#"{"out_batch_no":"plfk2020042013","batch_id":"1030000071100999991182020050700019480001","create_time":"2015-05-20T13:29:35.120+08:00","batch_status":"ACCEPTED"}"#;
+
+#[test]
+pub fn test_batch_transfer() {
+    let server = MockServer::start(vec![json_response(200, TRANSFER_BATCH_JSON)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    wechat_pay.load_platform_certificates(vec![test_platform_certificate()]);
+    let mut detail = TransferDetail::new(
+        "x23zy545Bd5436",
+        200000,
+        "2020年4月报销",
+        "o-MYE42l80oelYMDE34nYD456Xoy",
+    );
+    detail.user_name = Some("张三".to_string());
+    let params = TransferBatchParams::new(
+        "plfk2020042013",
+        "2019年1月深圳分部报销单",
+        "2019年1月深圳分部报销单",
+        vec![detail],
+    );
+    let result = run(wechat_pay.batch_transfer(params)).expect("batch_transfer error");
+    assert_eq!(result.batch_id, "1030000071100999991182020050700019480001");
+    assert_eq!(result.create_time, "2015-05-20T13:29:35.120+08:00");
+    let request = server.requests().remove(0);
+    assert!(request.starts_with("POST /v3/transfer/batches "));
+    assert_eq!(
+        header(&request, "wechatpay-serial").as_deref(),
+        Some(TEST_PLATFORM_SERIAL)
+    );
+    let body: serde_json::Value = serde_json::from_str(&body(&request)).unwrap();
+    assert_eq!(body["appid"], "wxd678efh567hg6787");
+    assert_eq!(body["total_amount"], 200000);
+    assert_eq!(body["total_num"], 1);
+    assert!(body.get("mchid").is_none());
+    let user_name = body["transfer_detail_list"][0]["user_name"]
+        .as_str()
+        .unwrap();
+    let platform_key = RsaPrivateKey::from_pkcs8_pem(TEST_PLATFORM_KEY).unwrap();
+    let user_name = platform_key
+        .decrypt(
+            Oaep::new::<sha1::Sha1>(),
+            &util::base64_decode(user_name).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(String::from_utf8(user_name).unwrap(), "张三");
+}
+
+#[test]
+pub fn test_batch_transfer_partner_mode() {
+    let server = MockServer::start(vec![json_response(200, TRANSFER_BATCH_JSON)]);
+    let wechat_pay = test_wechat_pay(&server.base_url)
+        .with_sub_merchant("1900000109", Some("wx8888888888888888"));
+    let params = TransferBatchParams::new(
+        "plfk2020042013",
+        "2019年1月深圳分部报销单",
+        "2019年1月深圳分部报销单",
+        vec![TransferDetail::new(
+            "x23zy545Bd5436",
+            200000,
+            "2020年4月报销",
+            "o-MYE42l80oelYMDE34nYD456Xoy",
+        )],
+    );
+    run(wechat_pay.batch_transfer(params)).expect("batch_transfer error");
+    // 商家转账是直连商户接口，服务商模式下仍然只注入appid
+    let body: serde_json::Value = serde_json::from_str(&body(&server.requests()[0])).unwrap();
+    assert_eq!(body["appid"], "wxd678efh567hg6787");
+    assert!(body.get("sp_appid").is_none());
+    assert!(body.get("sub_appid").is_none());
+    assert!(body.get("sub_mchid").is_none());
+}
+
+#[test]
+pub fn test_query_transfer_batch() {
+    let json = r#"{"limit":2,"offset":20,"transfer_batch":{"mchid":"1230000109","out_batch_no":"plfk2020042013","batch_id":"1030000071100999991182020050700019480001","appid":"wxd678efh567hg6787","batch_status":"FINISHED","batch_type":"API","batch_name":"2019年1月深圳分部报销单","batch_remark":"2019年1月深圳分部报销单","total_amount":4000000,"total_num":200,"create_time":"2015-05-20T13:29:35.120+08:00","update_time":"2015-05-20T13:29:35.120+08:00","success_amount":3900000,"success_num":199,"fail_amount":100000,"fail_num":1},"transfer_detail_list":[{"detail_id":"1040000071100999991182020050700019500100","out_detail_no":"x23zy545Bd5436","detail_status":"SUCCESS"},{"detail_id":"1040000071100999991182020050700019500101","out_detail_no":"x23zy545Bd5437","detail_status":"FAIL"}]}"#;
+    let server = MockServer::start(vec![json_response(200, json)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result = run(wechat_pay.query_transfer_batch("plfk2020042013", true, 20, 2))
+        .expect("query_transfer_batch error");
+    assert_eq!(result.offset, Some(20));
+    assert_eq!(result.limit, Some(2));
+    assert_eq!(
+        result.transfer_batch.batch_status,
+        TransferBatchStatus::Finished
+    );
+    assert_eq!(result.transfer_detail_list.unwrap().len(), 2);
+    let request = server.requests().remove(0);
+    let url =
+        "/v3/transfer/batches/out-batch-no/plfk2020042013?need_query_detail=true&offset=20&limit=2";
+    assert!(request.starts_with(&format!("GET {} ", url)));
+    let authorization = header(&request, "authorization").unwrap();
+    verify_authorization(&authorization, "GET", url, "");
+}
+
+#[test]
+pub fn test_query_transfer_detail() {
+    let public_key = RsaPrivateKey::from_pkcs8_pem(TEST_PRIVATE_KEY)
+        .unwrap()
+        .to_public_key();
+    let user_name = util::base64_encode(
+        public_key
+            .encrypt(
+                &mut rand::thread_rng(),
+                Oaep::new::<sha1::Sha1>(),
+                "张三".as_bytes(),
+            )
+            .unwrap(),
+    );
+    let json = serde_json::json!({
+        "mchid": "1230000109",
+        "out_batch_no": "plfk2020042013",
+        "batch_id": "1030000071100999991182020050700019480001",
+        "appid": "wxd678efh567hg6787",
+        "out_detail_no": "x23zy545Bd5436",
+        "detail_id": "1040000071100999991182020050700019500100",
+        "detail_status": "FAIL",
+        "transfer_amount": 200000,
+        "transfer_remark": "2020年4月报销",
+        "fail_reason": "ACCOUNT_FROZEN",
+        "openid": "o-MYE42l80oelYMDE34nYD456Xoy",
+        "user_name": user_name,
+        "initiate_time": "2015-05-20T13:29:35.120+08:00",
+        "update_time": "2015-05-20T13:29:35.120+08:00"
+    })
+    .to_string();
+    let server = MockServer::start(vec![json_response(200, &json)]);
+    let wechat_pay = test_wechat_pay(&server.base_url);
+    let result = run(wechat_pay.query_transfer_detail("plfk2020042013", "x23zy545Bd5436"))
+        .expect("query_transfer_detail error");
+    assert_eq!(result.detail_status, TransferDetailStatus::Fail);
+    assert_eq!(result.fail_reason.as_deref(), Some("ACCOUNT_FROZEN"));
+    assert_eq!(result.transfer_amount.fen(), 200000);
+    assert_eq!(result.user_name.as_deref(), Some("张三"));
+    let request = server.requests().remove(0);
+    let url =
+        "/v3/transfer/batches/out-batch-no/plfk2020042013/details/out-detail-no/x23zy545Bd5436";
+    assert!(request.starts_with(&format!("GET {} ", url)));
+    let authorization = header(&request, "authorization").unwrap();
+    verify_authorization(&authorization, "GET", url, "");
+    assert_eq!(
+        TransferDetailStatus::from("WAIT_PAY".to_string()),
+        TransferDetailStatus::WaitPay
+    );
+}
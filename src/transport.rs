@@ -0,0 +1,45 @@
+//! 发送http请求的传输层，默认使用reqwest client，单元测试时可以替换为返回预设响应的实现，
+//! 签名、验签、响应解析等逻辑不变
+use crate::error::PayError;
+use std::fmt::Debug;
+#[cfg(not(feature = "blocking"))]
+use std::future::Future;
+#[cfg(not(feature = "blocking"))]
+use std::pin::Pin;
+
+/// [Transport::execute]返回的future
+#[cfg(not(feature = "blocking"))]
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<reqwest::Response, PayError>> + Send + 'a>>;
+
+/// 发送已签名的请求并返回原始响应，响应可以用`reqwest::Response::from(http::Response)`构造
+#[cfg(not(feature = "blocking"))]
+pub trait Transport: Debug + Send + Sync {
+    fn execute(&self, request: reqwest::Request) -> TransportFuture<'_>;
+}
+
+#[cfg(not(feature = "blocking"))]
+impl Transport for reqwest::Client {
+    fn execute(&self, request: reqwest::Request) -> TransportFuture<'_> {
+        Box::pin(async move { Ok(reqwest::Client::execute(self, request).await?) })
+    }
+}
+
+/// 发送已签名的请求并返回原始响应，响应可以用`reqwest::blocking::Response::from(http::Response)`构造
+#[cfg(feature = "blocking")]
+pub trait Transport: Debug + Send + Sync {
+    fn execute(
+        &self,
+        request: reqwest::blocking::Request,
+    ) -> Result<reqwest::blocking::Response, PayError>;
+}
+
+#[cfg(feature = "blocking")]
+impl Transport for reqwest::blocking::Client {
+    fn execute(
+        &self,
+        request: reqwest::blocking::Request,
+    ) -> Result<reqwest::blocking::Response, PayError> {
+        Ok(reqwest::blocking::Client::execute(self, request)?)
+    }
+}
@@ -1,7 +1,175 @@
+use crate::error::PayError;
 use base64::engine::general_purpose;
 use base64::{DecodeError, Engine};
+use chrono::{DateTime, FixedOffset, SecondsFormat, TimeZone, Utc};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde_json::Value;
+use std::time::Duration;
 use uuid::Uuid;
 
+/// RFC3986中不需要编码的字符
+const URL_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// 图片上传最大2M
+pub(crate) const MAX_IMAGE_SIZE: usize = 2 * 1024 * 1024;
+
+/// 视频上传最大5M
+pub(crate) const MAX_VIDEO_SIZE: usize = 5 * 1024 * 1024;
+
+/// 文件扩展名（小写）
+fn file_extension(filename: &str) -> Result<String, PayError> {
+    match filename.rsplit_once('.') {
+        Some((_, ext)) if !ext.is_empty() => Ok(ext.to_lowercase()),
+        _ => Err(PayError::MissingFileExtension),
+    }
+}
+
+/// 根据文件头识别图片格式，返回对应的MIME类型
+fn detect_image_mime(image: &[u8]) -> Option<&'static str> {
+    if image.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if image.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if image.starts_with(b"BM") {
+        Some("image/bmp")
+    } else {
+        None
+    }
+}
+
+/// 校验上传图片的大小、扩展名，并确认文件内容与扩展名一致，返回图片的MIME类型
+pub(crate) fn check_image(image: &[u8], filename: &str) -> Result<&'static str, PayError> {
+    check_image_head(image, image.len(), filename)
+}
+
+/// 同[check_image]，head为文件开头的若干字节，size为文件大小
+fn check_image_head(head: &[u8], size: usize, filename: &str) -> Result<&'static str, PayError> {
+    if size > MAX_IMAGE_SIZE {
+        return Err(PayError::ImageTooLarge {
+            size,
+            max: MAX_IMAGE_SIZE,
+        });
+    }
+    let ext = file_extension(filename)?;
+    let mime = match ext.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "bmp" => "image/bmp",
+        _ => return Err(PayError::UnsupportedImageFormat(ext)),
+    };
+    match detect_image_mime(head) {
+        Some(detected) if detected == mime => Ok(mime),
+        detected => Err(PayError::ImageFormatMismatch {
+            extension: ext,
+            detected: detected.unwrap_or("unknown").to_string(),
+        }),
+    }
+}
+
+/// 校验商户单号的长度和字符，extra为数字、大小写字母之外允许的字符
+fn validate_out_no(
+    field: &'static str,
+    value: &str,
+    min: usize,
+    max: usize,
+    extra: &str,
+) -> Result<(), PayError> {
+    let len = value.chars().count();
+    if len < min || len > max {
+        return Err(PayError::InvalidParam {
+            field,
+            reason: format!("length must be {}-{}, got {}", min, max, len),
+        });
+    }
+    match value
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !extra.contains(*c))
+    {
+        Some(c) => Err(PayError::InvalidParam {
+            field,
+            reason: format!("illegal character {:?}", c),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// 商户订单号：6-32个字符，只能是数字、大小写字母和_-|*
+pub fn validate_out_trade_no(out_trade_no: &str) -> Result<(), PayError> {
+    validate_out_no("out_trade_no", out_trade_no, 6, 32, "_-|*")
+}
+
+/// 商户退款单号：1-64个字符，只能是数字、大小写字母和_-|*@
+pub fn validate_out_refund_no(out_refund_no: &str) -> Result<(), PayError> {
+    validate_out_no("out_refund_no", out_refund_no, 1, 64, "_-|*@")
+}
+
+/// 商户回退单号：1-64个字符，只能是数字、大小写字母和_-|*@
+pub fn validate_out_return_no(out_return_no: &str) -> Result<(), PayError> {
+    validate_out_no("out_return_no", out_return_no, 1, 64, "_-|*@")
+}
+
+/// 支付分商户服务订单号：1-32个字符，只能是数字、大小写字母和_-|*
+pub fn validate_out_order_no(out_order_no: &str) -> Result<(), PayError> {
+    validate_out_no("out_order_no", out_order_no, 1, 32, "_-|*")
+}
+
+/// 商家批次单号：5-32个字符，只能是数字、大小写字母
+pub fn validate_out_batch_no(out_batch_no: &str) -> Result<(), PayError> {
+    validate_out_no("out_batch_no", out_batch_no, 5, 32, "")
+}
+
+/// 发票申请单号：1-64个字符，只能是数字、大小写字母和_-
+pub fn validate_fapiao_apply_id(fapiao_apply_id: &str) -> Result<(), PayError> {
+    validate_out_no("fapiao_apply_id", fapiao_apply_id, 1, 64, "_-")
+}
+
+/// 用户付款码：付款码支付必填，扫码失败得到空串时在本地提前报错
+pub fn validate_auth_code(auth_code: &str) -> Result<(), PayError> {
+    if auth_code.trim().is_empty() {
+        return Err(PayError::InvalidParam {
+            field: "payer.auth_code",
+            reason: "must not be empty".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// 支付者openid：JSAPI和小程序下单必填，为空时微信返回的错误信息不明确，在本地提前校验
+pub fn validate_openid(openid: &str) -> Result<(), PayError> {
+    if openid.trim().is_empty() {
+        return Err(PayError::InvalidParam {
+            field: "payer.openid",
+            reason: "must not be empty".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// 北京时间时区（+08:00）
+pub fn beijing_offset() -> FixedOffset {
+    FixedOffset::east_opt(8 * 3600).unwrap()
+}
+
+/// 当前时间duration之后的北京时间，超出rfc3339能表示的范围时取`9999-12-31T23:59:59+08:00`
+pub(crate) fn beijing_after(duration: Duration) -> DateTime<FixedOffset> {
+    let max = beijing_offset()
+        .with_ymd_and_hms(9999, 12, 31, 23, 59, 59)
+        .unwrap();
+    chrono::Duration::from_std(duration)
+        .ok()
+        .and_then(|duration| Utc::now().checked_add_signed(duration))
+        .map_or(max, |time| time.with_timezone(&beijing_offset()).min(max))
+}
+
+/// 格式化为微信要求的rfc3339格式，例如`2018-06-08T10:34:56+08:00`，不含毫秒，UTC时间也使用`+00:00`
+pub fn format_rfc3339(time: &DateTime<FixedOffset>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
 pub fn random_trade_no() -> String {
     Uuid::new_v4().simple().to_string()
 }
@@ -19,3 +187,344 @@ where
 {
     general_purpose::STANDARD.decode(content.as_ref())
 }
+
+/// url编码，用于拼接路径参数和查询参数
+pub fn url_encode<S>(content: S) -> String
+where
+    S: AsRef<str>,
+{
+    utf8_percent_encode(content.as_ref(), URL_ENCODE_SET).to_string()
+}
+
+/// 计算sha1摘要，返回小写16进制字符串
+pub fn sha1_hex<S>(content: S) -> String
+where
+    S: AsRef<[u8]>,
+{
+    use sha1::{Digest, Sha1};
+    hex::encode(Sha1::digest(content.as_ref()))
+}
+
+/// 错误信息中附带的响应体长度（字符数），开启`log-bodies`时不截断
+const BODY_SNIPPET_CHARS: usize = 500;
+
+/// 截取响应体开头用于错误诊断，开启`log-bodies`时返回完整内容
+pub(crate) fn body_snippet(body: &str) -> String {
+    if cfg!(feature = "log-bodies") {
+        return body.to_string();
+    }
+    match body.char_indices().nth(BODY_SNIPPET_CHARS) {
+        Some((index, _)) => body[..index].to_string(),
+        None => body.to_string(),
+    }
+}
+
+/// 从H5支付中间页中提取`weixin://`拉起链接，兼容多行、压缩成一行、单双引号以及
+/// `window.location.href=`跳转的写法
+pub(crate) fn find_weixin_url(html: &str) -> Option<String> {
+    const SCHEME: &str = "weixin://";
+    let start = html.find(SCHEME)?;
+    let rest = html[start..].replace("&amp;", "&");
+    let end = rest
+        .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>' | ';' | '\\'))
+        .unwrap_or(rest.len());
+    (end > SCHEME.len()).then(|| rest[..end].to_string())
+}
+
+/// 校验上传视频的大小、扩展名，返回视频的MIME类型
+pub(crate) fn check_video(video: &[u8], filename: &str) -> Result<&'static str, PayError> {
+    check_video_size(video.len(), filename)
+}
+
+fn check_video_size(size: usize, filename: &str) -> Result<&'static str, PayError> {
+    if size > MAX_VIDEO_SIZE {
+        return Err(PayError::VideoTooLarge {
+            size,
+            max: MAX_VIDEO_SIZE,
+        });
+    }
+    let ext = file_extension(filename)?;
+    match ext.as_str() {
+        "avi" => Ok("video/x-msvideo"),
+        "wmv" => Ok("video/x-ms-wmv"),
+        "mpeg" => Ok("video/mpeg"),
+        "mp4" => Ok("video/mp4"),
+        "mov" => Ok("video/quicktime"),
+        "mkv" => Ok("video/x-matroska"),
+        "flv" => Ok("video/x-flv"),
+        "f4v" => Ok("video/x-f4v"),
+        "m4v" => Ok("video/x-m4v"),
+        "rmvb" => Ok("application/vnd.rn-realmedia-vbr"),
+        _ => Err(PayError::UnsupportedVideoFormat(ext)),
+    }
+}
+
+/// 上传的媒体类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MediaKind {
+    Image,
+    Video,
+}
+
+impl MediaKind {
+    /// 上传接口地址
+    pub(crate) fn upload_url(&self) -> &'static str {
+        match self {
+            MediaKind::Image => "/v3/merchant/media/upload",
+            MediaKind::Video => "/v3/merchant/media/video_upload",
+        }
+    }
+}
+
+/// 按扩展名判断是图片还是视频并校验，扩展名既不是图片也不是视频时返回`UnsupportedVideoFormat`
+pub(crate) fn check_media(
+    head: &[u8],
+    size: usize,
+    filename: &str,
+) -> Result<(MediaKind, &'static str), PayError> {
+    match check_image_head(head, size, filename) {
+        Err(PayError::UnsupportedImageFormat(_)) => {
+            check_video_size(size, filename).map(|mime| (MediaKind::Video, mime))
+        }
+        result => result.map(|mime| (MediaKind::Image, mime)),
+    }
+}
+
+/// 日志中替换敏感信息的占位
+const REDACTED: &str = "***";
+
+/// 隐藏请求体/响应体中的openid（包括sp_openid、sub_openid），不是json时原样返回
+pub fn redact_body(body: &str) -> String {
+    fn redact(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if key.ends_with("openid") && value.is_string() {
+                        *value = Value::String(REDACTED.to_string());
+                    } else {
+                        redact(value);
+                    }
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(redact),
+            _ => {}
+        }
+    }
+    match serde_json::from_str::<Value>(body) {
+        Ok(mut value) => {
+            redact(&mut value);
+            value.to_string()
+        }
+        Err(_) => body.to_string(),
+    }
+}
+
+/// 隐藏`Authorization`中的签名
+pub fn redact_authorization(authorization: &str) -> String {
+    match authorization.split_once("signature=\"") {
+        Some((head, tail)) => {
+            let tail = tail
+                .split_once('"')
+                .map(|(_, tail)| tail)
+                .unwrap_or_default();
+            format!("{}signature=\"{}\"{}", head, REDACTED, tail)
+        }
+        None => authorization.to_string(),
+    }
+}
+
+/// 日志中的请求路径：去掉查询参数，隐藏路径中的openid（`/users/{openid}/...`）
+pub fn redact_path(url: &str) -> String {
+    let path = url.split('?').next().unwrap_or_default();
+    let mut segments: Vec<&str> = path.split('/').collect();
+    for index in 1..segments.len() {
+        if segments[index - 1] == "users" {
+            segments[index] = REDACTED;
+        }
+    }
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PayError;
+    use crate::util::{
+        beijing_after, beijing_offset, body_snippet, check_image, check_video, find_weixin_url,
+        format_rfc3339, random_trade_no, redact_authorization, redact_body, redact_path,
+        validate_out_batch_no, validate_out_refund_no, validate_out_trade_no, MAX_IMAGE_SIZE,
+        MAX_VIDEO_SIZE,
+    };
+    use chrono::{DateTime, FixedOffset, Utc};
+    use std::time::Duration;
+
+    const WEIXIN_URL: &str = "weixin://wap/pay?prepayid%3Dwx2016121516420242444321ca0631331346&package=1405458241&noncestr=1481791343&sign=66d3e9e1e5d2d6b2c2f3c3f5d1f0b4b8";
+
+    #[test]
+    fn test_format_rfc3339() {
+        let time = DateTime::parse_from_rfc3339("2018-06-08T10:34:56.789+08:00").unwrap();
+        assert_eq!(format_rfc3339(&time), "2018-06-08T10:34:56+08:00");
+        let utc = time.with_timezone(&FixedOffset::east_opt(0).unwrap());
+        assert_eq!(format_rfc3339(&utc), "2018-06-08T02:34:56+00:00");
+
+        let expire = beijing_after(Duration::from_secs(1800));
+        assert_eq!(expire.offset(), &beijing_offset());
+        let remaining = expire.timestamp() - Utc::now().timestamp();
+        assert!((1799..=1800).contains(&remaining));
+        let formatted = format_rfc3339(&expire);
+        assert!(formatted.ends_with("+08:00"));
+        assert!(!formatted.contains('.'));
+        for duration in [Duration::MAX, Duration::from_secs(i64::MAX as u64 / 1000)] {
+            assert_eq!(
+                format_rfc3339(&beijing_after(duration)),
+                "9999-12-31T23:59:59+08:00"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_out_trade_no() {
+        assert!(validate_out_trade_no("123456").is_ok());
+        assert!(validate_out_trade_no(&"a".repeat(32)).is_ok());
+        assert!(validate_out_trade_no("Ab_-|*09").is_ok());
+        assert!(validate_out_trade_no(&random_trade_no()).is_ok());
+        for invalid in [
+            "12345",
+            &"a".repeat(33),
+            "123456@",
+            "订单号123456",
+            "123 456",
+        ] {
+            assert!(
+                matches!(
+                    validate_out_trade_no(invalid),
+                    Err(PayError::InvalidParam {
+                        field: "out_trade_no",
+                        ..
+                    })
+                ),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_out_refund_no_and_out_batch_no() {
+        assert!(validate_out_refund_no("1").is_ok());
+        assert!(validate_out_refund_no(&"a".repeat(64)).is_ok());
+        assert!(validate_out_refund_no("refund@1").is_ok());
+        assert!(validate_out_refund_no("").is_err());
+        assert!(validate_out_refund_no(&"a".repeat(65)).is_err());
+        assert!(validate_out_refund_no("refund#1").is_err());
+        assert!(validate_out_batch_no("plfk2020042013").is_ok());
+        assert!(validate_out_batch_no("abcd").is_err());
+        assert!(validate_out_batch_no(&"a".repeat(33)).is_err());
+        assert!(matches!(
+            validate_out_batch_no("plfk_2020"),
+            Err(PayError::InvalidParam {
+                field: "out_batch_no",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_find_weixin_url() {
+        let multiline = include_str!("../testdata/h5_multiline.html");
+        assert_eq!(find_weixin_url(multiline).as_deref(), Some(WEIXIN_URL));
+        let minified = include_str!("../testdata/h5_minified.html");
+        assert_eq!(find_weixin_url(minified).as_deref(), Some(WEIXIN_URL));
+        assert_eq!(find_weixin_url("<html>weixin://</html>"), None);
+        assert_eq!(find_weixin_url("<html></html>"), None);
+    }
+
+    #[test]
+    fn test_body_snippet() {
+        assert_eq!(body_snippet("<html></html>"), "<html></html>");
+        let page = "支付".repeat(400);
+        if cfg!(feature = "log-bodies") {
+            assert_eq!(body_snippet(&page), page);
+        } else {
+            assert_eq!(body_snippet(&page), "支付".repeat(250));
+        }
+    }
+
+    #[test]
+    fn test_check_image() {
+        let png = b"\x89PNG\r\n\x1a\n".to_vec();
+        let jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0];
+        assert_eq!(check_image(&png, "logo.png").unwrap(), "image/png");
+        assert_eq!(check_image(&jpeg, "logo.JPG").unwrap(), "image/jpeg");
+        assert!(matches!(
+            check_image(&jpeg, "logo.png"),
+            Err(PayError::ImageFormatMismatch { extension, detected })
+                if extension == "png" && detected == "image/jpeg"
+        ));
+        assert!(matches!(
+            check_image(&png, "logo.gif"),
+            Err(PayError::UnsupportedImageFormat(ext)) if ext == "gif"
+        ));
+        assert!(matches!(
+            check_image(&png, "logo"),
+            Err(PayError::MissingFileExtension)
+        ));
+        let large = vec![0; MAX_IMAGE_SIZE + 1];
+        assert!(matches!(
+            check_image(&large, "logo.png"),
+            Err(PayError::ImageTooLarge { size, max }) if size == MAX_IMAGE_SIZE + 1 && max == MAX_IMAGE_SIZE
+        ));
+    }
+
+    #[test]
+    fn test_check_video() {
+        assert_eq!(check_video(&[0; 16], "intro.MP4").unwrap(), "video/mp4");
+        assert_eq!(
+            check_video(&[0; 16], "intro.rmvb").unwrap(),
+            "application/vnd.rn-realmedia-vbr"
+        );
+        assert!(matches!(
+            check_video(&[0; 16], "intro.gif"),
+            Err(PayError::UnsupportedVideoFormat(ext)) if ext == "gif"
+        ));
+        assert!(matches!(
+            check_video(&[0; 16], "intro."),
+            Err(PayError::MissingFileExtension)
+        ));
+        let large = vec![0; MAX_VIDEO_SIZE + 1];
+        assert!(matches!(
+            check_video(&large, "intro.mp4"),
+            Err(PayError::VideoTooLarge { max, .. }) if max == MAX_VIDEO_SIZE
+        ));
+        assert!(check_video(&vec![0; MAX_IMAGE_SIZE + 1], "intro.mp4").is_ok());
+    }
+
+    #[test]
+    fn test_redact() {
+        let body = r#"{"appid":"wxd678efh567hg6787","payer":{"openid":"oUpF8uMuAJO_M2pxb1Q9zNjWeS6o"},"sub_openid":"o2"}"#;
+        let redacted = redact_body(body);
+        assert!(!redacted.contains("oUpF8uMuAJO_M2pxb1Q9zNjWeS6o"));
+        assert!(redacted.contains(r#""payer":{"openid":"***"}"#));
+        assert!(redacted.contains(r#""sub_openid":"***""#));
+        assert!(redacted.contains("wxd678efh567hg6787"));
+        assert_eq!(redact_body("not json openid"), "not json openid");
+
+        let authorization = r#"WECHATPAY2-SHA256-RSA2048 mchid="1230000109",nonce_str="abc",signature="c2lnbmF0dXJl",timestamp="1554208460",serial_no="1DDE55AD98ED71D6EDD4A4A16996DE7B47773A8C""#;
+        assert_eq!(
+            redact_authorization(authorization),
+            r#"WECHATPAY2-SHA256-RSA2048 mchid="1230000109",nonce_str="abc",signature="***",timestamp="1554208460",serial_no="1DDE55AD98ED71D6EDD4A4A16996DE7B47773A8C""#
+        );
+
+        assert_eq!(
+            redact_path(
+                "/v3/marketing/favor/users/oUpF8uMuAJO_M2pxb1Q9zNjWeS6o/coupons?stock_id=1"
+            ),
+            "/v3/marketing/favor/users/***/coupons"
+        );
+        assert_eq!(
+            redact_path(
+                "/v3/pay/transactions/out-trade-no/1217752501201407033233368018?mchid=1230000109"
+            ),
+            "/v3/pay/transactions/out-trade-no/1217752501201407033233368018"
+        );
+    }
+}